@@ -0,0 +1,34 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use semistr::SemiStr;
+
+/// Build a column whose lengths alternate between inline and heap
+/// in a pseudo-random order, so the length check is unpredictable.
+fn mixed_column(n: usize) -> Vec<SemiStr> {
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    (0..n).map(|i| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        if seed & 1 == 0 {
+            SemiStr::new(&format!("s{}", i % 1000))
+        } else {
+            SemiStr::new(&format!("a longer heap string {}", i))
+        }
+    }).collect()
+}
+
+fn bench_as_str(c: &mut Criterion) {
+    let mixed = mixed_column(4096);
+    let inline: Vec<SemiStr> = (0..4096).map(|i| SemiStr::new(&format!("s{}", i))).collect();
+    let heap: Vec<SemiStr> = (0..4096).map(|i| SemiStr::new(&format!("a longer heap string {}", i))).collect();
+    for (name, col) in [("inline", &inline), ("heap", &heap), ("mixed", &mixed)] {
+        c.bench_function(&format!("as_str/{}", name), |b| {
+            b.iter(|| {
+                black_box(col).iter().map(|s| s.as_str().as_bytes()[s.len() - 1] as u64).sum::<u64>()
+            })
+        });
+    }
+}
+
+criterion_group!(benches, bench_as_str);
+criterion_main!(benches);
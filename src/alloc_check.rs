@@ -0,0 +1,107 @@
+//! Checks of heap allocation in latency-critical sections.
+//!
+//! Every heap block allocated for SemiStr, SemiString, arenas and
+//! builders is reported to a thread-local state, so that tests can
+//! assert that a hot path only ever produces inline strings.
+
+use std::cell::Cell;
+
+thread_local! {
+    // depth of nested deny_alloc() calls.
+    static DENY: Cell<usize> = const { Cell::new(0) };
+    // number of allocations in count_allocs().
+    static COUNT: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Run f, and panic if it allocates any heap block on this thread.
+///
+/// ```
+/// use semistr::{alloc_check, SemiStr};
+///
+/// let s = alloc_check::deny_alloc(|| SemiStr::new("short"));
+/// assert_eq!(s, "short");
+/// ```
+#[inline]
+pub fn deny_alloc<R, F: FnOnce() -> R>(f: F) -> R {
+    DENY.with(|d| d.set(d.get() + 1));
+    let _guard = Restore(|| DENY.with(|d| d.set(d.get() - 1)));
+    f()
+}
+
+/// Run f, and returns its result with number of heap blocks allocated
+/// on this thread.
+#[inline]
+pub fn count_allocs<R, F: FnOnce() -> R>(f: F) -> (R, usize) {
+    let prev = COUNT.with(|c| c.replace(0));
+    let _guard = Restore(|| COUNT.with(|c| c.set(c.get() + prev)));
+    let res = f();
+    (res, COUNT.with(Cell::get))
+}
+
+/// Report allocation of a heap block of given size.
+///
+/// # Panics
+///
+/// Panics if called inside deny_alloc().
+#[inline]
+pub(crate) fn on_alloc(size: usize) {
+    COUNT.with(|c| c.set(c.get() + 1));
+    if DENY.with(Cell::get) > 0 {
+        panic!("heap allocation of {} bytes in deny_alloc()", size);
+    }
+}
+
+/// Run the closure on drop, so that state is restored on panic.
+struct Restore<F: FnMut()>(F);
+
+impl<F: FnMut()> Drop for Restore<F> {
+    #[inline]
+    fn drop(&mut self) {
+        (self.0)()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SemiStr, SemiString};
+
+    #[test]
+    fn test_deny_alloc() {
+        let s = deny_alloc(|| {
+            let a = SemiStr::new("inline");
+            let (b, _) = a.split_at(2);
+            b
+        });
+        assert_eq!(s, "in");
+        let res = std::panic::catch_unwind(|| deny_alloc(|| SemiStr::new("a string longer than 12")));
+        assert!(res.is_err());
+        // state is restored after panic.
+        assert_eq!(SemiStr::new("a string longer than 12").len(), 23);
+    }
+
+    #[test]
+    fn test_count_allocs() {
+        let (_, n) = count_allocs(|| {
+            let a = SemiStr::new("a string longer than 12");
+            let mut b = SemiString::new();
+            b.push_str("abc");
+            let (_, n) = count_allocs(|| SemiStr::new("another long string"));
+            assert_eq!(n, 1);
+            a
+        });
+        assert_eq!(n, 3);
+    }
+
+    #[test]
+    fn test_single_block() {
+        // header and payload are in one block, shared by clones and views.
+        let (s, n) = count_allocs(|| SemiStr::new("a string longer than 12"));
+        assert_eq!(n, 1);
+        let v = deny_alloc(|| {
+            let (_, tail) = s.clone().split_at(2);
+            tail
+        });
+        assert_eq!(v, "string longer than 12");
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{alloc_block, block_header, inline_str, release_block, retain_block, Heap, SemiStr, View, INLINE_CAP, MAX_LEN, VIEW_TAG};
+use std::sync::atomic::Ordering as AtomicOrdering;
+
+/// Minimal capacity of block allocated when arena grows.
+const ARENA_MIN_CAP: usize = 1024;
+
+/// Arena is a shared heap block into which many strings are written
+/// back to back.
+///
+/// Long strings pushed into arena reference the block instead of
+/// owning separate allocations, short strings are stored inline and
+/// consume no arena space. The block is released when arena and all
+/// strings referencing it are dropped.
+pub(crate) struct Arena {
+    ptr: *mut u8,
+    cap: usize,
+    len: usize,
+}
+
+impl Arena {
+    /// Create an arena with given capacity in bytes.
+    /// Capacity is clamped to u32::MAX so that offsets fit in view format.
+    #[inline]
+    pub(crate) fn with_capacity(cap: usize) -> Self {
+        let cap = cap.min(u32::MAX as usize);
+        if cap == 0 {
+            return Arena{ptr: std::ptr::null_mut(), cap, len: 0}
+        }
+        // SAFETY
+        //
+        // cap is greater than 0.
+        let ptr = unsafe { alloc_block(cap) };
+        Arena{ptr, cap, len: 0}
+    }
+
+    /// Returns number of bytes which can be appended without allocation.
+    #[inline]
+    pub(crate) fn remaining(&self) -> usize {
+        self.cap - self.len
+    }
+
+    /// Discard all appended bytes.
+    ///
+    /// If no string references the block any more, it is rewound and
+    /// reused, otherwise it is left to the strings and a new block is
+    /// allocated on next push.
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        if self.ptr.is_null() {
+            return
+        }
+        // SAFETY
+        //
+        // arena holds one reference of the block.
+        let rc = unsafe { block_header(self.ptr).rc.load(AtomicOrdering::Acquire) };
+        if rc == 1 {
+            self.len = 0;
+        } else {
+            // next push sees no remaining space and allocates new block.
+            self.len = self.cap;
+        }
+    }
+
+    /// Append concatenation of all parts as a new string.
+    /// If remaining capacity is insufficient, a new block is allocated
+    /// with double capacity of current one, but at least ARENA_MIN_CAP
+    /// and the string length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if total length is greater than 4GB.
+    pub(crate) fn push(&mut self, parts: &[&str]) -> SemiStr {
+        let len: usize = parts.iter().map(|p| p.len()).sum();
+        assert!(len <= MAX_LEN);
+        if len <= INLINE_CAP {
+            let mut data = [0u8; INLINE_CAP];
+            let mut pos = 0;
+            for p in parts {
+                data[pos..pos + p.len()].copy_from_slice(p.as_bytes());
+                pos += p.len();
+            }
+            // SAFETY
+            //
+            // concatenation of str is valid utf-8, and length is checked.
+            return unsafe { inline_str(&data[..len]) }
+        }
+        if self.remaining() < len {
+            *self = Arena::with_capacity((self.cap * 2).max(ARENA_MIN_CAP).max(len));
+        }
+        // SAFETY
+        //
+        // Block has enough space after the used bytes, and offset and
+        // length are no more than u32::MAX.
+        unsafe {
+            let start = self.ptr.add(self.len);
+            let mut dst = start;
+            for p in parts {
+                std::ptr::copy_nonoverlapping(p.as_ptr(), dst, p.len());
+                dst = dst.add(p.len());
+            }
+            retain_block(self.ptr);
+            let offset = self.len;
+            self.len += len;
+            if offset == 0 {
+                let mut prefix = [0u8; 4];
+                prefix.copy_from_slice(std::slice::from_raw_parts(start, 4));
+                return SemiStr::from_heap(Heap{len: len as u32, prefix, ptr: self.ptr})
+            }
+            SemiStr::from_view(View{len: len as u32, offset: offset as u32, ptr: self.ptr.map_addr(|a| a | VIEW_TAG)})
+        }
+    }
+}
+
+// SAFETY
+//
+// Block is reference counted atomically, and used bytes are never
+// written again.
+unsafe impl Send for Arena {}
+unsafe impl Sync for Arena {}
+
+impl Default for Arena {
+    #[inline]
+    fn default() -> Self {
+        Arena::with_capacity(0)
+    }
+}
+
+impl Drop for Arena {
+    #[inline]
+    fn drop(&mut self) {
+        if !self.ptr.is_null() {
+            // SAFETY
+            //
+            // arena holds one reference of the block.
+            unsafe { release_block(self.ptr) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arena() {
+        let mut arena = Arena::with_capacity(40);
+        let s1 = arena.push(&["hello", ", ", "world!"]);
+        let s2 = arena.push(&["short"]);
+        let s3 = arena.push(&["another long string"]);
+        assert_eq!(arena.remaining(), 8);
+        let s4 = arena.push(&["does not fit in arena"]);
+        drop(arena);
+        assert_eq!(s1, "hello, world!");
+        assert_eq!(s2, "short");
+        assert_eq!(s3, "another long string");
+        assert_eq!(s4, "does not fit in arena");
+        assert_eq!(s1.payload_ptr().wrapping_add(13), s3.payload_ptr());
+    }
+
+    #[test]
+    fn test_arena_clear() {
+        let mut arena = Arena::with_capacity(40);
+        let s1 = arena.push(&["a string longer than 12"]);
+        let ptr = s1.payload_ptr();
+        drop(s1);
+        arena.clear();
+        assert_eq!(arena.remaining(), 40);
+        let s2 = arena.push(&["another long string"]);
+        assert_eq!(s2.payload_ptr(), ptr);
+        arena.clear();
+        assert_eq!(arena.remaining(), 0);
+        let s3 = arena.push(&["one more long string"]);
+        assert_ne!(s3.payload_ptr(), ptr);
+        assert_eq!(s2, "another long string");
+    }
+}
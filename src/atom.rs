@@ -0,0 +1,155 @@
+//! Pre-declared atoms compared by identity.
+//!
+//! Atoms are variants of an enum generated by [`semi_atoms!`], so
+//! comparing two atoms compares their discriminants and never touches
+//! string bytes. Text of each atom is static, and is resolved to
+//! SemiStr on demand without allocation.
+
+/// Declare a set of static atoms as a fieldless enum.
+///
+/// Text of an atom is its identifier, unless given explicitly with
+/// `= "text"`. The generated enum provides:
+///
+/// - `ALL`: all atoms in declaration order.
+/// - `as_str()`: static text of the atom.
+/// - `to_semistr()`: atom resolved to SemiStr referring to static text.
+/// - `lookup()`: find the atom of given text.
+///
+/// It also implements `Display`, `From<Atom> for SemiStr`, and
+/// comparison with SemiStr and str.
+///
+/// ```
+/// semistr::semi_atoms! {
+///     pub enum Keyword {
+///         SELECT,
+///         FROM,
+///         GROUP_BY = "GROUP BY",
+///     }
+/// }
+///
+/// assert_eq!(Keyword::lookup("GROUP BY"), Some(Keyword::GROUP_BY));
+/// assert_eq!(semistr::SemiStr::new("FROM"), Keyword::FROM);
+/// ```
+#[macro_export]
+macro_rules! semi_atoms {
+    (@text $atom:ident) => {
+        stringify!($atom)
+    };
+    (@text $atom:ident $text:literal) => {
+        $text
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$ameta:meta])*
+                $atom:ident $(= $text:literal)?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        $vis enum $name {
+            $(
+                $(#[$ameta])*
+                $atom,
+            )*
+        }
+
+        impl $name {
+            /// All atoms in declaration order.
+            pub const ALL: &'static [$name] = &[$($name::$atom),*];
+
+            /// Returns static text of the atom.
+            #[inline]
+            pub const fn as_str(self) -> &'static str {
+                match self {
+                    $($name::$atom => $crate::semi_atoms!(@text $atom $($text)?),)*
+                }
+            }
+
+            /// Returns the atom resolved to SemiStr.
+            #[inline]
+            pub fn to_semistr(self) -> $crate::SemiStr {
+                $crate::SemiStr::from_static(self.as_str())
+            }
+
+            /// Returns the atom of given text, or None if not declared.
+            #[inline]
+            pub fn lookup(s: &str) -> Option<Self> {
+                static INDEX: ::std::sync::OnceLock<::std::collections::HashMap<&'static str, $name>> = ::std::sync::OnceLock::new();
+                INDEX.get_or_init(|| {
+                    Self::ALL.iter().map(|a| (a.as_str(), *a)).collect()
+                }).get(s).copied()
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            #[inline]
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl From<$name> for $crate::SemiStr {
+            #[inline]
+            fn from(atom: $name) -> $crate::SemiStr {
+                atom.to_semistr()
+            }
+        }
+
+        impl PartialEq<$name> for $crate::SemiStr {
+            #[inline]
+            fn eq(&self, other: &$name) -> bool {
+                *self == other.as_str()
+            }
+        }
+
+        impl PartialEq<$crate::SemiStr> for $name {
+            #[inline]
+            fn eq(&self, other: &$crate::SemiStr) -> bool {
+                *other == self.as_str()
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            #[inline]
+            fn eq(&self, other: &str) -> bool {
+                self.as_str() == other
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SemiStr;
+
+    semi_atoms! {
+        /// Keywords for test.
+        enum Keyword {
+            SELECT,
+            FROM,
+            WHERE,
+            /// Text longer than inline capacity.
+            CURRENT_TIMESTAMP_LOCAL = "CURRENT TIMESTAMP LOCAL",
+        }
+    }
+
+    #[test]
+    fn test_atoms() {
+        assert_eq!(Keyword::ALL.len(), 4);
+        assert_eq!(Keyword::SELECT.as_str(), "SELECT");
+        assert_eq!(Keyword::CURRENT_TIMESTAMP_LOCAL.to_string(), "CURRENT TIMESTAMP LOCAL");
+        assert_eq!(Keyword::lookup("WHERE"), Some(Keyword::WHERE));
+        assert_eq!(Keyword::lookup("where"), None);
+        let s = SemiStr::new("FROM");
+        assert_eq!(s, Keyword::FROM);
+        assert_ne!(Keyword::SELECT, s);
+        assert_eq!(Keyword::WHERE.to_semistr(), "WHERE");
+        let long: SemiStr = Keyword::CURRENT_TIMESTAMP_LOCAL.into();
+        assert_eq!(long, "CURRENT TIMESTAMP LOCAL");
+        assert!(Keyword::SELECT < Keyword::WHERE);
+    }
+}
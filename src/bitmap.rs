@@ -0,0 +1,162 @@
+/// Bitmap is a fixed-length sequence of bits, packed into 64-bit words.
+///
+/// It is used by kernels to mark selected, invalid or null rows.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Bitmap {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bitmap {
+    /// Create a bitmap with all bits unset.
+    #[inline]
+    pub fn new(len: usize) -> Self {
+        Bitmap{words: vec![0; len.div_ceil(64)], len}
+    }
+
+    /// Create a bitmap with all bits set.
+    #[inline]
+    pub fn new_set(len: usize) -> Self {
+        let mut res = Bitmap{words: vec![u64::MAX; len.div_ceil(64)], len};
+        res.clear_trailing();
+        res
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns bit at given position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if idx is out of bounds.
+    #[inline]
+    pub fn get(&self, idx: usize) -> bool {
+        assert!(idx < self.len);
+        self.words[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    /// Set bit at given position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if idx is out of bounds.
+    #[inline]
+    pub fn set(&mut self, idx: usize, value: bool) {
+        assert!(idx < self.len);
+        let mask = 1 << (idx % 64);
+        if value {
+            self.words[idx / 64] |= mask;
+        } else {
+            self.words[idx / 64] &= !mask;
+        }
+    }
+
+    /// Append a bit at the end.
+    #[inline]
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, value);
+    }
+
+    /// Returns number of set bits.
+    #[inline]
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns true if any bit is set.
+    #[inline]
+    pub fn any(&self) -> bool {
+        self.words.iter().any(|w| *w != 0)
+    }
+
+    /// Iterate over all bits.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    /// Iterate over positions of set bits.
+    #[inline]
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, w)| {
+            let mut w = *w;
+            std::iter::from_fn(move || {
+                if w == 0 {
+                    return None
+                }
+                let bit = w.trailing_zeros() as usize;
+                w &= w - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    /// Returns the packed words, bits beyond length are always unset.
+    #[inline]
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    #[inline]
+    fn clear_trailing(&mut self) {
+        if !self.len.is_multiple_of(64) {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1 << (self.len % 64)) - 1;
+        }
+    }
+}
+
+impl FromIterator<bool> for Bitmap {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut res = Bitmap::default();
+        for b in iter {
+            res.push(b);
+        }
+        res
+    }
+}
+
+impl From<&[bool]> for Bitmap {
+    #[inline]
+    fn from(bits: &[bool]) -> Self {
+        bits.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitmap() {
+        let mut bm = Bitmap::new(130);
+        assert_eq!(bm.count_ones(), 0);
+        assert!(!bm.any());
+        bm.set(0, true);
+        bm.set(64, true);
+        bm.set(129, true);
+        assert!(bm.get(64) && !bm.get(65));
+        assert_eq!(bm.iter_ones().collect::<Vec<_>>(), vec![0, 64, 129]);
+        bm.set(64, false);
+        assert_eq!(bm.count_ones(), 2);
+        let all = Bitmap::new_set(70);
+        assert_eq!(all.count_ones(), 70);
+        let bm: Bitmap = [true, false, true].into_iter().collect();
+        assert_eq!(bm.len(), 3);
+        assert_eq!(bm.iter().collect::<Vec<_>>(), vec![true, false, true]);
+        assert_eq!(Bitmap::from(&[false, true][..]).iter_ones().collect::<Vec<_>>(), vec![1]);
+    }
+}
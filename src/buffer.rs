@@ -0,0 +1,126 @@
+use crate::{block_header, block_payload, release_block, retain_block, SemiStr};
+use std::fmt;
+use std::ops::Deref;
+use std::sync::atomic::Ordering as AtomicOrdering;
+
+/// SharedBuffer is a handle of the heap block shared by SemiStrs,
+/// see [`SemiStr::as_buffer`].
+///
+/// It keeps the block alive like a SemiStr does, and derefs to the
+/// bytes of the string it is obtained from.
+pub struct SharedBuffer {
+    // payload pointer of the heap block.
+    block: *const u8,
+    offset: usize,
+    len: usize,
+}
+
+impl SharedBuffer {
+    /// Returns offset of the bytes in the heap block.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns number of strings and handles sharing the heap block.
+    #[inline]
+    pub fn ref_count(&self) -> usize {
+        // SAFETY
+        //
+        // block is kept alive by self.
+        unsafe { block_header(self.block).rc.load(AtomicOrdering::Relaxed) }
+    }
+
+    /// Returns whether two handles refer to the same heap block.
+    #[inline]
+    pub fn ptr_eq(this: &SharedBuffer, other: &SharedBuffer) -> bool {
+        this.block == other.block
+    }
+}
+
+impl SemiStr {
+    /// Returns handle of the heap block storing the string, or None if
+    /// the string is inline or static.
+    #[inline]
+    pub fn as_buffer(&self) -> Option<SharedBuffer> {
+        let (block, offset) = self.block_ref()?;
+        // SAFETY
+        //
+        // block is kept alive by self.
+        unsafe { retain_block(block) };
+        Some(SharedBuffer{block, offset, len: self.len()})
+    }
+}
+
+// SAFETY
+//
+// the block is immutable and managed with atomic reference count,
+// same as SemiStr.
+unsafe impl Send for SharedBuffer {}
+unsafe impl Sync for SharedBuffer {}
+
+impl Deref for SharedBuffer {
+    type Target = [u8];
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        // SAFETY
+        //
+        // the range is initialized bytes of a string in the block.
+        unsafe { std::slice::from_raw_parts(block_payload(self.block).add(self.offset), self.len) }
+    }
+}
+
+impl AsRef<[u8]> for SharedBuffer {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Clone for SharedBuffer {
+    #[inline]
+    fn clone(&self) -> Self {
+        unsafe { retain_block(self.block) };
+        SharedBuffer{block: self.block, offset: self.offset, len: self.len}
+    }
+}
+
+impl Drop for SharedBuffer {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { release_block(self.block) }
+    }
+}
+
+impl fmt::Debug for SharedBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedBuffer")
+            .field("offset", &self.offset)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_buffer() {
+        let s = SemiStr::new("a string longer than 12");
+        let buf = s.as_buffer().unwrap();
+        assert_eq!(&*buf, s.as_bytes());
+        assert_eq!(buf.ref_count(), 2);
+        let v = s.slice(2..20);
+        let vbuf = v.as_buffer().unwrap();
+        assert_eq!(vbuf.offset(), 2);
+        assert!(SharedBuffer::ptr_eq(&buf, &vbuf));
+        drop(s);
+        drop(v);
+        // handles keep the block alive.
+        assert_eq!(buf.ref_count(), 2);
+        assert_eq!(&*vbuf, b"string longer than");
+        assert!(SemiStr::new("short").as_buffer().is_none());
+        assert!(SemiStr::from_static("a static string longer than 12").as_buffer().is_none());
+    }
+}
@@ -0,0 +1,301 @@
+use crate::{Error, Result, SemiStr, SemiString, INLINE_CAP, MAX_LEN};
+use std::fmt;
+
+/// SemiStrBuilder accumulates text and finishes into SemiStr.
+///
+/// Text is kept in an inline buffer until it exceeds 12 bytes, then
+/// spills to a heap buffer with the same layout as SemiStr, so short
+/// results take no allocation, and long results are frozen without
+/// copying.
+///
+/// It implements `Extend` and `fmt::Write`, so it can be used by
+/// generic code appending via `extend()` or `write!`.
+#[derive(Clone, Default)]
+pub struct SemiStrBuilder {
+    inline: [u8; INLINE_CAP],
+    inline_len: usize,
+    // heap buffer, text is spilled to it if allocated.
+    heap: SemiString,
+    // length of text appended beyond 4GB, which is discarded.
+    overflow: usize,
+}
+
+impl SemiStrBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a builder, which allocates heap buffer upfront if
+    /// capacity is greater than 12 bytes.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut res = Self::new();
+        if capacity > INLINE_CAP {
+            res.heap.reserve(capacity);
+        }
+        res
+    }
+
+    /// Create a builder with capacity, same as
+    /// [`SemiStrBuilder::with_capacity`], but returns error instead of
+    /// panicking or aborting if heap buffer cannot be allocated.
+    #[inline]
+    pub fn try_with_capacity(capacity: usize) -> Result<Self> {
+        let mut res = Self::new();
+        if capacity > INLINE_CAP {
+            res.heap.try_reserve(capacity)?;
+        }
+        Ok(res)
+    }
+
+    #[inline]
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0u8; 4]))
+    }
+
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        if self.overflow > 0 || self.len() + s.len() > MAX_LEN {
+            self.overflow = self.overflow.saturating_add(s.len());
+            return
+        }
+        if !self.spilled() {
+            if self.inline_len + s.len() <= INLINE_CAP {
+                self.inline[self.inline_len..self.inline_len + s.len()].copy_from_slice(s.as_bytes());
+                self.inline_len += s.len();
+                return
+            }
+            self.heap.reserve(self.inline_len + s.len());
+            // SAFETY
+            //
+            // inline buffer holds valid utf-8 string.
+            self.heap.push_str(unsafe { std::str::from_utf8_unchecked(&self.inline[..self.inline_len]) });
+        }
+        self.heap.push_str(s)
+    }
+
+    /// Append a string, returns error instead of discarding text
+    /// beyond 4GB or aborting if heap buffer cannot grow.
+    /// The builder is unchanged on error.
+    #[inline]
+    pub fn try_push_str(&mut self, s: &str) -> Result<()> {
+        if self.overflow > 0 {
+            return Err(Error::StringTooLong(self.len().saturating_add(self.overflow)))
+        }
+        if self.spilled() {
+            self.heap.try_reserve(s.len())?;
+        } else if self.inline_len + s.len() > INLINE_CAP {
+            self.heap.try_reserve(self.inline_len.saturating_add(s.len()))?;
+            // SAFETY
+            //
+            // inline buffer holds valid utf-8 string.
+            self.heap.push_str(unsafe { std::str::from_utf8_unchecked(&self.inline[..self.inline_len]) });
+        }
+        self.push_str(s);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        if self.spilled() {
+            self.heap.len()
+        } else {
+            self.inline_len
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns accumulated text, excluding text beyond 4GB.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        if self.spilled() {
+            return self.heap.as_str()
+        }
+        // SAFETY
+        //
+        // inline buffer holds valid utf-8 string.
+        unsafe { std::str::from_utf8_unchecked(&self.inline[..self.inline_len]) }
+    }
+
+    /// Finish building, returns error if accumulated text
+    /// is longer than 4GB.
+    ///
+    /// Spare capacity of heap buffer is kept by the result,
+    /// see [`SemiStr::compact`].
+    #[inline]
+    pub fn finish(self) -> Result<SemiStr> {
+        if self.overflow > 0 {
+            return Err(Error::StringTooLong(self.len().saturating_add(self.overflow)))
+        }
+        if self.spilled() {
+            return Ok(self.heap.freeze())
+        }
+        SemiStr::try_from(self.as_str())
+    }
+
+    #[inline]
+    fn spilled(&self) -> bool {
+        self.heap.capacity() > 0
+    }
+}
+
+impl SemiStr {
+    /// Create a string from formatted arguments, which are written
+    /// into inline buffer if short, and into a single heap buffer
+    /// otherwise. See also [`format_semistr!`](crate::format_semistr).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result is longer than 4GB, or a formatting trait
+    /// implementation returns an error.
+    #[inline]
+    pub fn from_fmt(args: fmt::Arguments<'_>) -> SemiStr {
+        if let Some(s) = args.as_str() {
+            return SemiStr::from_static(s)
+        }
+        let mut builder = SemiStrBuilder::new();
+        fmt::Write::write_fmt(&mut builder, args)
+            .expect("a formatting trait implementation returned an error");
+        builder.finish().unwrap()
+    }
+}
+
+/// Create a SemiStr using interpolation of runtime expressions,
+/// like `format!`, without intermediate String.
+///
+/// ```
+/// let id = 42;
+/// let s = semistr::format_semistr!("user-{}", id);
+/// assert_eq!(s, "user-42");
+/// ```
+#[macro_export]
+macro_rules! format_semistr {
+    ($($arg:tt)*) => {
+        $crate::SemiStr::from_fmt(::std::format_args!($($arg)*))
+    };
+}
+
+impl fmt::Debug for SemiStrBuilder {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Extend<char> for SemiStrBuilder {
+    #[inline]
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|c| self.push(c))
+    }
+}
+
+impl<'a> Extend<&'a char> for SemiStrBuilder {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a char>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|c| self.push(*c))
+    }
+}
+
+impl<'a> Extend<&'a str> for SemiStrBuilder {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|s| self.push_str(s))
+    }
+}
+
+impl<'a> Extend<&'a SemiStr> for SemiStrBuilder {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a SemiStr>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|s| self.push_str(s))
+    }
+}
+
+impl fmt::Write for SemiStrBuilder {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push(c);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn test_builder() {
+        let mut b = SemiStrBuilder::new();
+        b.extend(['a', 'b']);
+        b.extend(["c", "d"]);
+        let x = "x";
+        write!(b, "-{}:{}", 1, x).unwrap();
+        assert_eq!(b.as_str(), "abcd-1:x");
+        let s = b.finish().unwrap();
+        assert_eq!(s, "abcd-1:x");
+
+        fn serialize<W: Write + Extend<char>>(out: &mut W) {
+            out.extend("key".chars());
+            write!(out, "={}", 42).unwrap();
+        }
+        let mut b = SemiStrBuilder::with_capacity(16);
+        serialize(&mut b);
+        b.extend(&[SemiStr::new(", and a long tail")]);
+        assert_eq!(b.finish().unwrap(), "key=42, and a long tail");
+    }
+
+    #[test]
+    fn test_builder_spill() {
+        let mut b = SemiStrBuilder::new();
+        let (host, port) = ("host", 8080);
+        write!(b, "{}:{}", host, port).unwrap();
+        assert_eq!(b.len(), 9);
+        let s = b.clone().finish().unwrap();
+        assert_eq!(s, "host:8080");
+        // spills to heap buffer, which is frozen without copying.
+        let path = "some/long/path";
+        write!(b, "/{}", path).unwrap();
+        assert_eq!(b.as_str(), "host:8080/some/long/path");
+        let ptr = b.as_str().as_ptr();
+        let s = b.finish().unwrap();
+        assert_eq!(s, "host:8080/some/long/path");
+        assert_eq!(s.payload_ptr(), ptr);
+        let mut b = SemiStrBuilder::with_capacity(64);
+        b.push('x');
+        assert_eq!(b.finish().unwrap(), "x");
+    }
+
+    #[test]
+    fn test_builder_try_push() {
+        let mut b = SemiStrBuilder::try_with_capacity(4).unwrap();
+        b.try_push_str("short").unwrap();
+        assert!(!b.spilled());
+        b.try_push_str(", then spilled").unwrap();
+        assert_eq!(b.as_str(), "short, then spilled");
+        b.try_push_str("x").unwrap();
+        assert!(matches!(SemiStrBuilder::try_with_capacity(usize::MAX), Err(Error::StringTooLong(_))));
+        assert_eq!(b.finish().unwrap(), "short, then spilledx");
+    }
+
+    #[test]
+    fn test_from_fmt() {
+        let (table, id) = ("orders", 7);
+        let s = format_semistr!("{}#{}", table, id);
+        assert_eq!(s, "orders#7");
+        let s = format_semistr!("SELECT * FROM {table} WHERE id = {id}");
+        assert_eq!(s, "SELECT * FROM orders WHERE id = 7");
+        let s = SemiStr::from_fmt(format_args!("a static format string"));
+        assert_eq!(s, "a static format string");
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{heap_str, inline_str, range_bounds, validate_utf8, Error, Result, SemiStr, INLINE_CAP, MAX_LEN};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, RangeBounds};
+
+/// SemiBytes is an immutable byte string with length no more than
+/// 4GB, which has the same 16-byte layout as SemiStr, including
+/// inline format of short values and stored prefix of long values,
+/// but does not require utf-8.
+///
+/// Conversions between SemiStr and SemiBytes never copy the payload.
+#[derive(Clone, Default)]
+pub struct SemiBytes(
+    // bytes may be invalid utf-8, so only byte-level methods of
+    // SemiStr are called.
+    SemiStr,
+);
+
+impl SemiBytes {
+    /// # Panics
+    ///
+    /// Panics if the bytes are longer than 4GB.
+    #[inline]
+    pub fn new(bytes: &[u8]) -> Self {
+        Self::try_from(bytes).unwrap()
+    }
+
+    /// Create a byte string referring to static memory without copying,
+    /// see [`SemiStr::from_static`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bytes are longer than 4GB.
+    #[inline]
+    pub fn from_static(bytes: &'static [u8]) -> Self {
+        // SAFETY
+        //
+        // result is wrapped as SemiBytes.
+        SemiBytes(unsafe { SemiStr::from_static_bytes(bytes) })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    /// Returns the stored 4-byte prefix, padded with zeros.
+    #[inline]
+    pub fn prefix(&self) -> [u8; 4] {
+        self.0.prefix_bytes()
+    }
+
+    /// Returns whether the bytes are stored inline.
+    #[inline]
+    pub fn is_inline(&self) -> bool {
+        self.0.is_inline()
+    }
+
+    /// Returns number of values sharing the heap buffer, or None if
+    /// the bytes are inline or static.
+    #[inline]
+    pub fn ref_count(&self) -> Option<usize> {
+        self.0.ref_count()
+    }
+
+    /// Returns a slice sharing the buffer of self, or stored inline
+    /// if short.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    #[inline]
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> SemiBytes {
+        let (start, end) = range_bounds(range, self.len());
+        assert!(start <= end && end <= self.len(), "failed to slice bytes at {}..{}", start, end);
+        // SAFETY
+        //
+        // range is checked above, and result is wrapped as SemiBytes.
+        SemiBytes(unsafe { self.0.slice_shared(start, end) })
+    }
+}
+
+impl<'b> TryFrom<&'b [u8]> for SemiBytes {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: &'b [u8]) -> Result<Self> {
+        // SAFETY
+        //
+        // length is checked, and result is wrapped as SemiBytes.
+        if value.len() <= INLINE_CAP {
+            Ok(SemiBytes(unsafe { inline_str(value) }))
+        } else if value.len() <= MAX_LEN {
+            Ok(SemiBytes(unsafe { heap_str(value) }))
+        } else {
+            Err(Error::StringTooLong(value.len()))
+        }
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8; N]> for SemiBytes {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: &[u8; N]) -> Result<Self> {
+        Self::try_from(&value[..])
+    }
+}
+
+impl TryFrom<Vec<u8>> for SemiBytes {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: Vec<u8>) -> Result<Self> {
+        Self::try_from(&value[..])
+    }
+}
+
+impl From<SemiStr> for SemiBytes {
+    #[inline]
+    fn from(value: SemiStr) -> Self {
+        SemiBytes(value)
+    }
+}
+
+/// Bytes are validated, and the payload is not copied.
+impl TryFrom<SemiBytes> for SemiStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: SemiBytes) -> Result<Self> {
+        validate_utf8(value.as_bytes())?;
+        Ok(value.0)
+    }
+}
+
+impl Deref for SemiBytes {
+    type Target = [u8];
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsRef<[u8]> for SemiBytes {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl Borrow<[u8]> for SemiBytes {
+    #[inline]
+    fn borrow(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl PartialEq for SemiBytes {
+    /// Lengths and stored prefixes are compared first.
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.prefix() == other.prefix() && self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for SemiBytes {}
+
+impl PartialEq<[u8]> for SemiBytes {
+    #[inline]
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_bytes() == other
+    }
+}
+
+impl PartialEq<&[u8]> for SemiBytes {
+    #[inline]
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.as_bytes() == *other
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for SemiBytes {
+    #[inline]
+    fn eq(&self, other: &[u8; N]) -> bool {
+        self.as_bytes() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&[u8; N]> for SemiBytes {
+    #[inline]
+    fn eq(&self, other: &&[u8; N]) -> bool {
+        self.as_bytes() == *other
+    }
+}
+
+impl PartialOrd for SemiBytes {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SemiBytes {
+    /// Stored prefixes are compared first, same as SemiStr.
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp_bytes(&other.0)
+    }
+}
+
+impl Hash for SemiBytes {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state)
+    }
+}
+
+impl fmt::Debug for SemiBytes {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "b\"{}\"", self.as_bytes().escape_ascii())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semi_bytes() {
+        let hash = SemiBytes::new(&[0xff, 0xfe, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]);
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash.prefix(), [0xff, 0xfe, 0, 1]);
+        assert_eq!(hash[..2], [0xff, 0xfe]);
+        let tail = hash.slice(2..);
+        assert_eq!(tail.ref_count(), Some(2));
+        assert_eq!(tail, &[0u8, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]);
+        assert!(hash.slice(..4).is_inline());
+        assert!(SemiStr::try_from(hash.clone()).is_err());
+        assert_eq!(format!("{:?}", SemiBytes::new(b"a\xff")), "b\"a\\xff\"");
+
+        let s = SemiStr::new("a string longer than 12");
+        let ptr = s.payload_ptr();
+        let b = SemiBytes::from(s);
+        let s = SemiStr::try_from(b).unwrap();
+        assert_eq!(s.payload_ptr(), ptr);
+
+        let mut keys = vec![SemiBytes::new(b"b"), SemiBytes::new(b"a\0"), SemiBytes::new(b"a"), SemiBytes::from_static(b"\xff static bytes")];
+        keys.sort();
+        assert_eq!(keys, [&b"a"[..], b"a\0", b"b", b"\xff static bytes"]);
+        assert_eq!(SemiBytes::default(), b"");
+    }
+
+    #[test]
+    #[should_panic(expected = "from after maximum usize")]
+    fn test_slice_overflow() {
+        use std::ops::Bound;
+        SemiBytes::new(b"hello").slice((Bound::Excluded(usize::MAX), Bound::Unbounded));
+    }
+}
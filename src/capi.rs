@@ -0,0 +1,167 @@
+//! C API of SemiStr.
+//!
+//! Strings are passed as [`SemiStrHandle`], an opaque 16-byte struct
+//! aligned to 8 bytes on every target, with the same layout as SemiStr,
+//! which can be stored by value in C arrays and structs:
+//!
+//! ```c
+//! typedef struct { _Alignas(8) uint32_t opaque[4]; } semistr_t;
+//!
+//! int semistr_new(const char *data, size_t len, semistr_t *out);
+//! size_t semistr_len(const semistr_t *s);
+//! const char *semistr_data(const semistr_t *s);
+//! semistr_t semistr_clone(const semistr_t *s);
+//! void semistr_free(semistr_t *s);
+//! ```
+//!
+//! A handle must be initialized by `semistr_new` or `semistr_clone`,
+//! and released by `semistr_free` exactly once, which resets it to
+//! the empty string. Data of short strings is stored in the handle
+//! itself, so the pointer returned by `semistr_data` is valid as long
+//! as the handle is neither moved nor released.
+
+use crate::{Error, SemiStr};
+use std::ffi::{c_char, c_int, c_void};
+use std::mem::{align_of, size_of, ManuallyDrop};
+
+/// Error code of invalid utf-8 input.
+pub const SEMISTR_ERR_UTF8: c_int = 1;
+/// Error code of input longer than 4GB.
+pub const SEMISTR_ERR_TOO_LONG: c_int = 2;
+
+/// Handle of SemiStr passed across C API, with the same layout as
+/// SemiStr. Fields are opaque to callers.
+#[repr(C, align(8))]
+pub struct SemiStrHandle {
+    head: [u32; 2],
+    ptr: *const c_void,
+}
+
+// C declaration of the handle is 16 bytes aligned to 8 bytes, also
+// on 32-bit targets, where the pointer is followed by padding.
+const _: () = assert!(size_of::<SemiStrHandle>() == size_of::<SemiStr>()
+    && align_of::<SemiStrHandle>() == align_of::<SemiStr>()
+    && size_of::<SemiStrHandle>() == 16 && align_of::<SemiStrHandle>() == 8);
+
+impl From<SemiStr> for SemiStrHandle {
+    #[inline]
+    fn from(value: SemiStr) -> Self {
+        let value = ManuallyDrop::new(value);
+        // SAFETY
+        //
+        // layouts are identical, and ownership is transferred.
+        unsafe { std::ptr::read(&*value as *const SemiStr as *const SemiStrHandle) }
+    }
+}
+
+impl SemiStrHandle {
+    /// # Safety
+    ///
+    /// Handle must be initialized by C API and not yet released.
+    #[inline]
+    unsafe fn as_semistr(&self) -> &SemiStr {
+        &*(self as *const SemiStrHandle as *const SemiStr)
+    }
+}
+
+/// Create a string by copying len bytes of data into out.
+/// Returns 0 on success, or error code on failure, in which case out
+/// is not written.
+///
+/// # Safety
+///
+/// data must be valid for reading len bytes, and out must be valid
+/// for writing a handle.
+#[no_mangle]
+pub unsafe extern "C" fn semistr_new(data: *const c_char, len: usize, out: *mut SemiStrHandle) -> c_int {
+    let bytes = if len == 0 { &[][..] } else { std::slice::from_raw_parts(data as *const u8, len) };
+    match SemiStr::try_from(bytes) {
+        Ok(s) => {
+            out.write(SemiStrHandle::from(s));
+            0
+        }
+        Err(Error::StringTooLong(_)) => SEMISTR_ERR_TOO_LONG,
+        Err(_) => SEMISTR_ERR_UTF8,
+    }
+}
+
+/// Returns length of the string in bytes.
+///
+/// # Safety
+///
+/// s must point to an initialized handle.
+#[no_mangle]
+pub unsafe extern "C" fn semistr_len(s: *const SemiStrHandle) -> usize {
+    (*s).as_semistr().len()
+}
+
+/// Returns pointer to utf-8 bytes of the string, which is not
+/// null-terminated.
+///
+/// # Safety
+///
+/// s must point to an initialized handle.
+#[no_mangle]
+pub unsafe extern "C" fn semistr_data(s: *const SemiStrHandle) -> *const c_char {
+    (*s).as_semistr().as_ptr() as *const c_char
+}
+
+/// Returns a handle sharing heap buffer of the string.
+///
+/// # Safety
+///
+/// s must point to an initialized handle.
+#[no_mangle]
+pub unsafe extern "C" fn semistr_clone(s: *const SemiStrHandle) -> SemiStrHandle {
+    SemiStrHandle::from((*s).as_semistr().clone())
+}
+
+/// Release the string, and reset the handle to the empty string.
+///
+/// # Safety
+///
+/// s must point to an initialized handle.
+#[no_mangle]
+pub unsafe extern "C" fn semistr_free(s: *mut SemiStrHandle) {
+    let handle = s.replace(SemiStrHandle::from(SemiStr::default()));
+    std::ptr::drop_in_place(&handle as *const SemiStrHandle as *mut SemiStr);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::MaybeUninit;
+
+    unsafe fn to_str(s: &SemiStrHandle) -> &str {
+        let bytes = std::slice::from_raw_parts(semistr_data(s) as *const u8, semistr_len(s));
+        std::str::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_capi() {
+        unsafe {
+            let text = "a string shared with C callers";
+            let mut out = MaybeUninit::uninit();
+            assert_eq!(semistr_new(text.as_ptr() as *const c_char, text.len(), out.as_mut_ptr()), 0);
+            let mut s = out.assume_init();
+            assert_eq!(to_str(&s), text);
+            let mut c = semistr_clone(&s);
+            assert_eq!(semistr_data(&c), semistr_data(&s));
+            assert_eq!(s.as_semistr().ref_count(), Some(2));
+            semistr_free(&mut s);
+            assert_eq!(semistr_len(&s), 0);
+            assert_eq!(to_str(&c), text);
+            semistr_free(&mut c);
+
+            let mut out = MaybeUninit::uninit();
+            assert_eq!(semistr_new(b"short".as_ptr() as *const c_char, 5, out.as_mut_ptr()), 0);
+            let mut s = out.assume_init();
+            assert_eq!(to_str(&s), "short");
+            semistr_free(&mut s);
+            let mut out = MaybeUninit::uninit();
+            assert_eq!(semistr_new(std::ptr::null(), 0, out.as_mut_ptr()), 0);
+            assert_eq!(semistr_len(out.as_ptr()), 0);
+            assert_eq!(semistr_new(b"\xff".as_ptr() as *const c_char, 1, out.as_mut_ptr()), SEMISTR_ERR_UTF8);
+        }
+    }
+}
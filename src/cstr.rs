@@ -0,0 +1,255 @@
+use crate::{build_str, validate_utf8, Error, Result, SemiStr, MAX_LEN};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::ffi::{c_char, CStr, CString};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+/// SemiCStr is an immutable nul-terminated string with the same
+/// 16-byte layout as SemiStr.
+///
+/// The payload holds content without interior nul, followed by a
+/// trailing nul, so [`SemiCStr::as_ptr`] can be passed to C APIs
+/// without conversion. Content no longer than 11 bytes is stored
+/// inline.
+#[derive(Clone)]
+pub struct SemiCStr(
+    // bytes including trailing nul, may be invalid utf-8, so only
+    // byte-level methods of SemiStr are called.
+    SemiStr,
+);
+
+impl SemiCStr {
+    /// Create a nul-terminated string from content bytes, which must
+    /// not contain nul.
+    #[inline]
+    pub fn new<T: AsRef<[u8]> + ?Sized>(bytes: &T) -> Result<Self> {
+        let bytes = bytes.as_ref();
+        if let Some(pos) = bytes.iter().position(|b| *b == 0) {
+            return Err(Error::InteriorNul(pos))
+        }
+        // SAFETY
+        //
+        // content is nul-free as checked above.
+        unsafe { Self::from_bytes_unchecked(bytes) }
+    }
+
+    /// Create a nul-terminated string referring to static memory
+    /// without copying, see [`SemiStr::from_static`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is longer than 4GB.
+    #[inline]
+    pub fn from_static(s: &'static CStr) -> Self {
+        // SAFETY
+        //
+        // bytes are nul-terminated without interior nul, and result is
+        // wrapped as SemiCStr.
+        SemiCStr(unsafe { SemiStr::from_static_bytes(s.to_bytes_with_nul()) })
+    }
+
+    /// # Safety
+    ///
+    /// bytes must not contain nul.
+    #[inline]
+    unsafe fn from_bytes_unchecked(bytes: &[u8]) -> Result<Self> {
+        let len = bytes.len() + 1;
+        if len > MAX_LEN {
+            return Err(Error::StringTooLong(len))
+        }
+        Ok(SemiCStr(build_str(len, |buf| {
+            buf[..bytes.len()].copy_from_slice(bytes);
+            buf[bytes.len()] = 0;
+        })))
+    }
+
+    /// Returns length of content, excluding trailing nul.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len() - 1
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn as_c_str(&self) -> &CStr {
+        // SAFETY
+        //
+        // payload is nul-terminated without interior nul.
+        unsafe { CStr::from_bytes_with_nul_unchecked(self.0.as_bytes()) }
+    }
+
+    /// Returns pointer to the nul-terminated payload, which is valid
+    /// as long as self is not dropped or moved.
+    /// Note that short string is stored inline, so the pointer refers
+    /// to self rather than a shared buffer.
+    #[inline]
+    pub fn as_ptr(&self) -> *const c_char {
+        self.0.as_bytes().as_ptr() as *const c_char
+    }
+
+    /// Returns content bytes, excluding trailing nul.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.as_c_str().to_bytes()
+    }
+
+    #[inline]
+    pub fn as_bytes_with_nul(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    /// Returns the str if the content is valid utf-8.
+    #[inline]
+    pub fn to_str(&self) -> Result<&str> {
+        validate_utf8(self.as_bytes())
+    }
+}
+
+impl Default for SemiCStr {
+    #[inline]
+    fn default() -> Self {
+        SemiCStr::from_static(c"")
+    }
+}
+
+impl<'s> TryFrom<&'s str> for SemiCStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: &'s str) -> Result<Self> {
+        SemiCStr::new(value)
+    }
+}
+
+impl<'s> TryFrom<&'s CStr> for SemiCStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: &'s CStr) -> Result<Self> {
+        // SAFETY
+        //
+        // CStr never contains interior nul.
+        unsafe { SemiCStr::from_bytes_unchecked(value.to_bytes()) }
+    }
+}
+
+impl TryFrom<CString> for SemiCStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: CString) -> Result<Self> {
+        SemiCStr::try_from(value.as_c_str())
+    }
+}
+
+impl From<SemiCStr> for CString {
+    #[inline]
+    fn from(value: SemiCStr) -> Self {
+        value.as_c_str().to_owned()
+    }
+}
+
+impl Deref for SemiCStr {
+    type Target = CStr;
+    #[inline]
+    fn deref(&self) -> &CStr {
+        self.as_c_str()
+    }
+}
+
+impl AsRef<CStr> for SemiCStr {
+    #[inline]
+    fn as_ref(&self) -> &CStr {
+        self.as_c_str()
+    }
+}
+
+impl Borrow<CStr> for SemiCStr {
+    #[inline]
+    fn borrow(&self) -> &CStr {
+        self.as_c_str()
+    }
+}
+
+impl PartialEq for SemiCStr {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes_with_nul() == other.as_bytes_with_nul()
+    }
+}
+
+impl Eq for SemiCStr {}
+
+impl PartialEq<CStr> for SemiCStr {
+    #[inline]
+    fn eq(&self, other: &CStr) -> bool {
+        self.as_c_str() == other
+    }
+}
+
+impl PartialEq<&CStr> for SemiCStr {
+    #[inline]
+    fn eq(&self, other: &&CStr) -> bool {
+        self.as_c_str() == *other
+    }
+}
+
+impl PartialOrd for SemiCStr {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SemiCStr {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_c_str().cmp(other.as_c_str())
+    }
+}
+
+impl Hash for SemiCStr {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_c_str().hash(state)
+    }
+}
+
+impl fmt::Debug for SemiCStr {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_c_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semi_cstr() {
+        let s = SemiCStr::new("short").unwrap();
+        assert!(s.0.is_inline());
+        assert_eq!(s, c"short");
+        assert_eq!(s.len(), 5);
+        let s = SemiCStr::new("eleven byte").unwrap();
+        assert!(s.0.is_inline());
+        let s = SemiCStr::new("twelve bytes").unwrap();
+        assert!(!s.0.is_inline());
+        assert_eq!(s.as_bytes_with_nul(), b"twelve bytes\0");
+        let c = unsafe { CStr::from_ptr(s.as_ptr()) };
+        assert_eq!(c, c"twelve bytes");
+        assert_eq!(s.to_str().unwrap(), "twelve bytes");
+        let copy = s.clone();
+        assert_eq!(copy.as_ptr(), s.as_ptr());
+        assert!(matches!(SemiCStr::new(b"interior\0nul"), Err(Error::InteriorNul(8))));
+        assert!(SemiCStr::new(b"\xff").unwrap().to_str().is_err());
+        let s = SemiCStr::from_static(c"a static c string");
+        assert_eq!(CString::from(s), c"a static c string".to_owned());
+        assert!(SemiCStr::default().is_empty());
+        assert!(SemiCStr::try_from(c"abc").unwrap() < SemiCStr::try_from("abd").unwrap());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{build_str, write_chars, Error, Result, SemiStr, MAX_LEN};
+
+/// Encoded length of the replacement char U+FFFD.
+const REPLACEMENT_LEN: usize = char::REPLACEMENT_CHARACTER.len_utf8();
+
+impl SemiStr {
+    /// Create a string from bytes without checking that they are
+    /// valid utf-8, e.g. read from a trusted source which is already
+    /// validated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is longer than 4GB.
+    ///
+    /// # Safety
+    ///
+    /// The bytes must be valid utf-8, same as [`std::str::from_utf8_unchecked`].
+    #[inline]
+    pub unsafe fn from_utf8_unchecked(bytes: &[u8]) -> SemiStr {
+        Self::new(std::str::from_utf8_unchecked(bytes))
+    }
+
+    /// Create a string from bytes, replacing invalid utf-8 sequences
+    /// with U+FFFD, same as [`String::from_utf8_lossy`].
+    ///
+    /// The result is built in a single allocation, or stored inline
+    /// if short.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result is longer than 4GB.
+    #[inline]
+    pub fn from_utf8_lossy(bytes: &[u8]) -> SemiStr {
+        let len = bytes.utf8_chunks().fold(0usize, |n, chunk| {
+            let invalid = if chunk.invalid().is_empty() { 0 } else { REPLACEMENT_LEN };
+            n.saturating_add(chunk.valid().len() + invalid)
+        });
+        assert!(len <= MAX_LEN, "string too long: {}", len);
+        // SAFETY
+        //
+        // buffer is filled with valid chunks and replacement chars.
+        unsafe {
+            build_str(len, |buf| {
+                let mut pos = 0;
+                for chunk in bytes.utf8_chunks() {
+                    let valid = chunk.valid().as_bytes();
+                    buf[pos..pos + valid.len()].copy_from_slice(valid);
+                    pos += valid.len();
+                    if !chunk.invalid().is_empty() {
+                        pos += char::REPLACEMENT_CHARACTER.encode_utf8(&mut buf[pos..]).len();
+                    }
+                }
+            })
+        }
+    }
+
+    /// Create a string from utf-16 code units, same as
+    /// [`String::from_utf16`].
+    ///
+    /// The result is sized once and built in a single allocation, or
+    /// stored inline if short.
+    /// Returns error if input contains unpaired surrogates or the
+    /// result is longer than 4GB.
+    #[inline]
+    pub fn from_utf16(units: &[u16]) -> Result<SemiStr> {
+        let mut len = 0usize;
+        for ch in char::decode_utf16(units.iter().copied()) {
+            len += ch.map_err(|_| Error::InvalidUtf16String)?.len_utf8();
+        }
+        if len > MAX_LEN {
+            return Err(Error::StringTooLong(len))
+        }
+        // SAFETY
+        //
+        // buffer is filled with decoded chars, which are all valid.
+        Ok(unsafe {
+            build_str(len, |buf| {
+                write_chars(buf, char::decode_utf16(units.iter().copied()).map(|ch| ch.unwrap()))
+            })
+        })
+    }
+
+    /// Create a string from utf-16 code units, replacing unpaired
+    /// surrogates with U+FFFD, same as [`String::from_utf16_lossy`].
+    ///
+    /// The result is sized once and built in a single allocation, or
+    /// stored inline if short.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result is longer than 4GB.
+    #[inline]
+    pub fn from_utf16_lossy(units: &[u16]) -> SemiStr {
+        let chars = || {
+            char::decode_utf16(units.iter().copied())
+                .map(|ch| ch.unwrap_or(char::REPLACEMENT_CHARACTER))
+        };
+        let len = chars().map(char::len_utf8).sum();
+        // SAFETY
+        //
+        // buffer is filled with decoded chars and replacement chars.
+        unsafe { build_str(len, |buf| write_chars(buf, chars())) }
+    }
+
+    /// Create a string from ISO-8859-1 bytes, each of which is
+    /// widened to the char of same code point.
+    ///
+    /// The result is built in a single pass with exact-size
+    /// allocation, or stored inline if short.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result is longer than 4GB.
+    #[inline]
+    pub fn from_latin1(bytes: &[u8]) -> SemiStr {
+        // bytes above 0x7F are encoded in 2 bytes.
+        let len = bytes.len() + bytes.iter().filter(|b| !b.is_ascii()).count();
+        // SAFETY
+        //
+        // buffer is filled with utf-8 encoding of latin-1 code points.
+        unsafe {
+            build_str(len, |buf| {
+                if len == bytes.len() {
+                    buf.copy_from_slice(bytes);
+                    return
+                }
+                let mut pos = 0;
+                for &b in bytes {
+                    if b.is_ascii() {
+                        buf[pos] = b;
+                        pos += 1;
+                    } else {
+                        buf[pos] = 0xC0 | (b >> 6);
+                        buf[pos + 1] = 0x80 | (b & 0x3F);
+                        pos += 2;
+                    }
+                }
+            })
+        }
+    }
+
+    /// Create a string from ASCII bytes.
+    ///
+    /// Bytes are checked by [`slice::is_ascii`], which processes a
+    /// word at a time and is cheaper than full utf-8 validation.
+    /// Returns error with position of the first non-ASCII byte, or
+    /// if the string is longer than 4GB.
+    #[inline]
+    pub fn from_ascii(bytes: &[u8]) -> Result<SemiStr> {
+        if !bytes.is_ascii() {
+            let pos = bytes.iter().position(|b| !b.is_ascii()).unwrap();
+            return Err(Error::NonAsciiByte(pos))
+        }
+        // SAFETY
+        //
+        // ASCII is valid utf-8.
+        Self::try_from(unsafe { std::str::from_utf8_unchecked(bytes) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_utf8_lossy() {
+        let s = SemiStr::from_utf8_lossy(b"ab\xffc");
+        assert_eq!(s, "ab\u{FFFD}c");
+        assert!(s.is_inline());
+        let bytes = b"a long feed with \xf0\x90\x80 bad bytes \xc3";
+        let s = SemiStr::from_utf8_lossy(bytes);
+        assert_eq!(s.as_str(), String::from_utf8_lossy(bytes));
+        assert_eq!(SemiStr::from_utf8_lossy(b"valid and longer than 12"), "valid and longer than 12");
+        assert_eq!(SemiStr::from_utf8_lossy(b""), "");
+    }
+
+    #[test]
+    fn test_from_utf16() {
+        let units: Vec<u16> = "𝄞 music, a long utf-16 string".encode_utf16().collect();
+        assert_eq!(SemiStr::from_utf16(&units).unwrap(), "𝄞 music, a long utf-16 string");
+        let s = SemiStr::from_utf16(&[0x68, 0xE9]).unwrap();
+        assert_eq!(s, "hé");
+        assert!(s.is_inline());
+        let bad = [0xD834, 0x61, 0xDD1E];
+        assert!(matches!(SemiStr::from_utf16(&bad), Err(Error::InvalidUtf16String)));
+        assert_eq!(SemiStr::from_utf16_lossy(&bad), String::from_utf16_lossy(&bad));
+        assert_eq!(SemiStr::from_utf16_lossy(&units), "𝄞 music, a long utf-16 string");
+    }
+
+    #[test]
+    fn test_from_latin1() {
+        let s = SemiStr::from_latin1(b"caf\xe9");
+        assert_eq!(s, "café");
+        assert!(s.is_inline());
+        let bytes: Vec<u8> = (0u8..=255).collect();
+        let expected: String = bytes.iter().map(|&b| b as char).collect();
+        assert_eq!(SemiStr::from_latin1(&bytes).as_str(), expected);
+        assert_eq!(SemiStr::from_latin1(b"plain ascii, longer than 12"), "plain ascii, longer than 12");
+    }
+
+    #[test]
+    fn test_from_ascii() {
+        assert_eq!(SemiStr::from_ascii(b"customer_id").unwrap(), "customer_id");
+        assert_eq!(SemiStr::from_ascii(b"an ascii identifier longer than 12").unwrap(), "an ascii identifier longer than 12");
+        assert!(matches!(SemiStr::from_ascii("café".as_bytes()), Err(Error::NonAsciiByte(3))));
+        assert!(matches!(SemiStr::from_ascii(b"ok\x80"), Err(Error::NonAsciiByte(2))));
+    }
+
+    #[test]
+    fn test_from_utf8_unchecked() {
+        let s = unsafe { SemiStr::from_utf8_unchecked("héllo".as_bytes()) };
+        assert!(s.is_inline());
+        assert_eq!(s, "héllo");
+        let s = unsafe { SemiStr::from_utf8_unchecked(b"a trusted column value") };
+        assert_eq!(s, "a trusted column value");
+    }
+}
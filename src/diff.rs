@@ -0,0 +1,293 @@
+//! Diff between two SemiStrs.
+//!
+//! The edit script is computed by Myers' algorithm, and consecutive
+//! edits of the same kind are merged into chunks, which share buffers
+//! of the input strings without copying.
+
+use crate::SemiStr;
+use std::ops::Range;
+
+/// A chunk of diff from old string to new string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DiffOp<T> {
+    /// Text in both strings.
+    Equal(T),
+    /// Text only in new string.
+    Insert(T),
+    /// Text only in old string.
+    Delete(T),
+}
+
+impl<T> DiffOp<T> {
+    /// Returns text of the chunk.
+    #[inline]
+    pub fn text(&self) -> &T {
+        match self {
+            DiffOp::Equal(t) | DiffOp::Insert(t) | DiffOp::Delete(t) => t,
+        }
+    }
+}
+
+impl SemiStr {
+    /// Returns char-level diff from self to other.
+    pub fn diff(&self, other: &SemiStr) -> Vec<DiffOp<SemiStr>> {
+        diff_tokens(self, other, char_ranges)
+    }
+
+    /// Returns line-level diff from self to other.
+    /// Lines include their terminating newline.
+    pub fn diff_lines(&self, other: &SemiStr) -> Vec<DiffOp<SemiStr>> {
+        diff_tokens(self, other, line_ranges)
+    }
+}
+
+#[inline]
+fn char_ranges(s: &str) -> Vec<Range<usize>> {
+    s.char_indices().map(|(i, c)| i..i + c.len_utf8()).collect()
+}
+
+#[inline]
+fn line_ranges(s: &str) -> Vec<Range<usize>> {
+    let mut pos = 0;
+    s.split_inclusive('\n').map(|l| {
+        pos += l.len();
+        pos - l.len()..pos
+    }).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Equal,
+    Insert,
+    Delete,
+}
+
+fn diff_tokens<F>(old: &SemiStr, new: &SemiStr, tokenize: F) -> Vec<DiffOp<SemiStr>>
+where
+    F: Fn(&str) -> Vec<Range<usize>>,
+{
+    let a = tokenize(old);
+    let b = tokenize(new);
+    let eq = |i: usize, j: usize| old[a[i].clone()] == new[b[j].clone()];
+    let mut res: Vec<(Edit, Range<usize>)> = vec![];
+    for (edit, i, j) in myers(a.len(), b.len(), eq) {
+        let range = match edit {
+            Edit::Equal | Edit::Delete => a[i].clone(),
+            Edit::Insert => b[j].clone(),
+        };
+        match res.last_mut() {
+            Some((e, r)) if *e == edit && r.end == range.start => r.end = range.end,
+            _ => res.push((edit, range)),
+        }
+    }
+    res.into_iter().map(|(edit, r)| {
+        // SAFETY
+        //
+        // token ranges are on char boundary.
+        match edit {
+            Edit::Equal => DiffOp::Equal(unsafe { old.slice_shared(r.start, r.end) }),
+            Edit::Delete => DiffOp::Delete(unsafe { old.slice_shared(r.start, r.end) }),
+            Edit::Insert => DiffOp::Insert(unsafe { new.slice_shared(r.start, r.end) }),
+        }
+    }).collect()
+}
+
+/// Compute shortest edit script between sequences of length n and m.
+/// Returns edits in order, with token indexes in old and new sequence,
+/// index of the other sequence is meaningless for insert and delete.
+///
+/// Uses the linear space variant of Myers' algorithm: the middle snake
+/// of each range is found by searching from both ends, and the two
+/// halves are solved recursively, so memory is O(n + m).
+fn myers<F: Fn(usize, usize) -> bool>(n: usize, m: usize, eq: F) -> Vec<(Edit, usize, usize)> {
+    let mut res = Vec::with_capacity(n.max(m));
+    diff_range(&eq, 0..n, 0..m, &mut res);
+    // Halves may end and start with changes of different kind,
+    // keep deletes before inserts within each run of changes.
+    for run in res.split_mut(|e| e.0 == Edit::Equal) {
+        run.sort_by_key(|e| e.0 == Edit::Insert);
+    }
+    res
+}
+
+fn diff_range<F: Fn(usize, usize) -> bool>(
+    eq: &F,
+    mut a: Range<usize>,
+    mut b: Range<usize>,
+    res: &mut Vec<(Edit, usize, usize)>,
+) {
+    while a.start < a.end && b.start < b.end && eq(a.start, b.start) {
+        res.push((Edit::Equal, a.start, b.start));
+        a.start += 1;
+        b.start += 1;
+    }
+    let mut suffix = 0;
+    while a.start < a.end && b.start < b.end && eq(a.end - 1, b.end - 1) {
+        a.end -= 1;
+        b.end -= 1;
+        suffix += 1;
+    }
+    match middle_snake(eq, a.clone(), b.clone()) {
+        Some((x, y)) => {
+            diff_range(eq, a.start..x, b.start..y, res);
+            diff_range(eq, x..a.end, y..b.end, res);
+        }
+        None => {
+            res.extend(a.clone().map(|i| (Edit::Delete, i, b.start)));
+            res.extend(b.clone().map(|j| (Edit::Insert, a.end, j)));
+        }
+    }
+    res.extend((0..suffix).map(|i| (Edit::Equal, a.end + i, b.end + i)));
+}
+
+/// Find a split point on a shortest edit path of given ranges, by
+/// running forward and backward searches until they overlap.
+/// Returns None if ranges have nothing in common.
+fn middle_snake<F: Fn(usize, usize) -> bool>(
+    eq: &F,
+    a: Range<usize>,
+    b: Range<usize>,
+) -> Option<(usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    if n == 0 || m == 0 {
+        return None
+    }
+    let max_d = (n + m + 1) / 2;
+    let offset = max_d;
+    let len = 2 * max_d as usize + 2;
+    let mut vf = vec![-1isize; len];
+    let mut vb = vec![-1isize; len];
+    vf[offset as usize + 1] = 0;
+    vb[offset as usize + 1] = 0;
+    let delta = n - m;
+    // Overlap is checked on forward pass if delta is odd, otherwise on backward pass.
+    let front = delta % 2 != 0;
+    let (mut f_start, mut f_end, mut b_start, mut b_end) = (0, 0, 0, 0);
+    for d in 0..max_d {
+        for k in (-d + f_start..=d - f_end).step_by(2) {
+            let ki = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && vf[ki - 1] < vf[ki + 1]) { vf[ki + 1] } else { vf[ki - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && eq(a.start + x as usize, b.start + y as usize) {
+                x += 1;
+                y += 1;
+            }
+            vf[ki] = x;
+            if x > n {
+                f_end += 2;
+            } else if y > m {
+                f_start += 2;
+            } else if front {
+                let bi = offset + delta - k;
+                if bi >= 0 && (bi as usize) < len && vb[bi as usize] != -1 && x >= n - vb[bi as usize] {
+                    return Some((a.start + x as usize, b.start + y as usize))
+                }
+            }
+        }
+        for k in (-d + b_start..=d - b_end).step_by(2) {
+            let ki = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && vb[ki - 1] < vb[ki + 1]) { vb[ki + 1] } else { vb[ki - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && eq(a.end - 1 - x as usize, b.end - 1 - y as usize) {
+                x += 1;
+                y += 1;
+            }
+            vb[ki] = x;
+            if x > n {
+                b_end += 2;
+            } else if y > m {
+                b_start += 2;
+            } else if !front {
+                let fi = offset + delta - k;
+                if fi >= 0 && (fi as usize) < len && vf[fi as usize] != -1 {
+                    let fx = vf[fi as usize];
+                    let fy = offset + fx - fi;
+                    if fx >= n - x {
+                        return Some((a.start + fx as usize, b.start + fy as usize))
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(ops: &[DiffOp<SemiStr>]) -> String {
+        ops.iter().map(|op| match op {
+            DiffOp::Equal(t) => t.to_string(),
+            DiffOp::Insert(t) => format!("[+{}]", t),
+            DiffOp::Delete(t) => format!("[-{}]", t),
+        }).collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = SemiStr::new("the quick brown fox");
+        let b = SemiStr::new("the quack brown dog");
+        let ops = a.diff(&b);
+        assert_eq!(render(&ops), "the qu[-i][+a]ck brown [-f][+d]o[-x][+g]");
+        assert_eq!(SemiStr::default().diff(&SemiStr::new("ab")), vec![DiffOp::Insert(SemiStr::new("ab"))]);
+        let same = a.diff(&a);
+        assert_eq!(same, vec![DiffOp::Equal(a.clone())]);
+        assert_eq!(same[0].text().payload_ptr(), a.payload_ptr());
+        assert!(SemiStr::default().diff(&SemiStr::default()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let a = SemiStr::new("host = a\nport = 80\ndebug = false\n");
+        let b = SemiStr::new("host = a\nport = 8080\ndebug = false\nlog = on\n");
+        let ops = a.diff_lines(&b);
+        assert_eq!(ops, vec![
+            DiffOp::Equal(SemiStr::new("host = a\n")),
+            DiffOp::Delete(SemiStr::new("port = 80\n")),
+            DiffOp::Insert(SemiStr::new("port = 8080\n")),
+            DiffOp::Equal(SemiStr::new("debug = false\n")),
+            DiffOp::Insert(SemiStr::new("log = on\n")),
+        ]);
+    }
+
+    #[test]
+    fn test_myers_minimal() {
+        // Compare edit distance with LCS by dynamic programming.
+        let mut seed = 7u32;
+        let mut gen = |len: usize| -> Vec<u8> {
+            (0..len).map(|_| {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                b'a' + (seed >> 16) as u8 % 3
+            }).collect()
+        };
+        for round in 0..200 {
+            let a = gen(round % 17);
+            let b = gen(round % 13);
+            let edits = myers(a.len(), b.len(), |i, j| a[i] == b[j]);
+            let mut old = vec![];
+            let mut new = vec![];
+            for &(edit, i, j) in &edits {
+                match edit {
+                    Edit::Equal => {
+                        assert_eq!(a[i], b[j]);
+                        old.push(a[i]);
+                        new.push(b[j]);
+                    }
+                    Edit::Delete => old.push(a[i]),
+                    Edit::Insert => new.push(b[j]),
+                }
+            }
+            assert_eq!(old, a);
+            assert_eq!(new, b);
+            let mut lcs = vec![vec![0; b.len() + 1]; a.len() + 1];
+            for i in 0..a.len() {
+                for j in 0..b.len() {
+                    lcs[i + 1][j + 1] = if a[i] == b[j] { lcs[i][j] + 1 } else { lcs[i][j + 1].max(lcs[i + 1][j]) };
+                }
+            }
+            let equal = edits.iter().filter(|e| e.0 == Edit::Equal).count();
+            assert_eq!(equal, lcs[a.len()][b.len()]);
+        }
+    }
+}
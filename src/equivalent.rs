@@ -0,0 +1,72 @@
+//! Implementations of `equivalent` traits.
+//!
+//! `str` is already equivalent and comparable to SemiStr, by the
+//! blanket implementations over `Borrow<str>`. This module covers the
+//! other directions, so maps keyed by str or String can be queried by
+//! SemiStr, and maps keyed by SemiStr can be queried by String.
+
+use crate::SemiStr;
+use ::equivalent::{Comparable, Equivalent};
+use std::cmp::Ordering;
+
+impl Equivalent<str> for SemiStr {
+    #[inline]
+    fn equivalent(&self, key: &str) -> bool {
+        self == key
+    }
+}
+
+impl Comparable<str> for SemiStr {
+    #[inline]
+    fn compare(&self, key: &str) -> Ordering {
+        self.as_str().cmp(key)
+    }
+}
+
+impl Equivalent<String> for SemiStr {
+    #[inline]
+    fn equivalent(&self, key: &String) -> bool {
+        self == key
+    }
+}
+
+impl Comparable<String> for SemiStr {
+    #[inline]
+    fn compare(&self, key: &String) -> Ordering {
+        self.as_str().cmp(key.as_str())
+    }
+}
+
+impl Equivalent<SemiStr> for String {
+    #[inline]
+    fn equivalent(&self, key: &SemiStr) -> bool {
+        key == self
+    }
+}
+
+impl Comparable<SemiStr> for String {
+    #[inline]
+    fn compare(&self, key: &SemiStr) -> Ordering {
+        self.as_str().cmp(key.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hashbrown::HashMap;
+
+    #[test]
+    fn test_equivalent_lookup() {
+        let mut map: HashMap<SemiStr, u32> = HashMap::new();
+        map.insert(SemiStr::new("a key longer than 12 bytes"), 1);
+        map.insert(SemiStr::new("short"), 2);
+        assert_eq!(map.get("short"), Some(&2));
+        assert_eq!(map.get(&String::from("a key longer than 12 bytes")), Some(&1));
+        let mut map: HashMap<String, u32> = HashMap::new();
+        map.insert("owned".to_string(), 3);
+        assert_eq!(map.get(&SemiStr::new("owned")), Some(&3));
+        assert_eq!(SemiStr::new("b").compare("a"), Ordering::Greater);
+        assert_eq!(String::from("a").compare(&SemiStr::new("b")), Ordering::Less);
+    }
+}
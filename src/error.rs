@@ -1,11 +1,101 @@
-use thiserror::Error;
+use std::fmt;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-#[derive(Debug, Clone, Error)]
+/// Error of this crate.
+///
+/// Variants of optional features are always declared, so enabling a
+/// feature never changes the enum, and new variants may be added.
+/// Variants carrying a message are only declared with the `std`
+/// feature, as they allocate.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum Error {
-    #[error("string too long, length is {0}")]
     StringTooLong(usize),
-    #[error("invalid utf-8 string")]
+    AllocFailed(usize),
     InvalidUtf8String,
-}
\ No newline at end of file
+    InvalidUtf16String,
+    NonAsciiByte(usize),
+    InteriorNul(usize),
+    #[cfg(feature = "std")]
+    Io(String),
+    #[cfg(feature = "std")]
+    UnsupportedArrowFormat(String),
+    #[cfg(feature = "std")]
+    Fst(String),
+    #[cfg(feature = "std")]
+    InvalidRegex(String),
+    #[cfg(feature = "std")]
+    Zstd(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::StringTooLong(len) => write!(f, "string too long, length is {}", len),
+            Error::AllocFailed(size) => write!(f, "allocation of {} bytes failed", size),
+            Error::InvalidUtf8String => f.write_str("invalid utf-8 string"),
+            Error::InvalidUtf16String => f.write_str("invalid utf-16 string"),
+            Error::NonAsciiByte(idx) => write!(f, "non-ascii byte at {}", idx),
+            Error::InteriorNul(idx) => write!(f, "interior nul byte at {}", idx),
+            #[cfg(feature = "std")]
+            Error::Io(msg) => write!(f, "io error: {}", msg),
+            #[cfg(feature = "std")]
+            Error::UnsupportedArrowFormat(format) => write!(f, "unsupported arrow format {}", format),
+            #[cfg(feature = "std")]
+            Error::Fst(msg) => write!(f, "fst error: {}", msg),
+            #[cfg(feature = "std")]
+            Error::InvalidRegex(msg) => write!(f, "invalid regex: {}", msg),
+            #[cfg(feature = "std")]
+            Error::Zstd(msg) => write!(f, "zstd error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Error of converting owned bytes into SemiStr, which gives the
+/// bytes back, like [`std::string::FromUtf8Error`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct FromBytesError {
+    bytes: Vec<u8>,
+    error: Error,
+}
+
+#[cfg(feature = "std")]
+impl FromBytesError {
+    #[inline]
+    pub(crate) fn new(bytes: Vec<u8>, error: Error) -> Self {
+        FromBytesError{bytes, error}
+    }
+
+    /// Returns the bytes failed to convert.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the bytes failed to convert, without copying.
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Returns the cause of failure.
+    #[inline]
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for FromBytesError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FromBytesError {}
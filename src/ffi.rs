@@ -0,0 +1,313 @@
+//! Arrow C Data Interface for SemiStrVec.
+//!
+//! Columns are exported as Arrow string view arrays, whose 16-byte
+//! views resemble SemiStr headers. Heap buffers are handed out as
+//! variadic data buffers without copying, and are kept alive until
+//! the consumer releases the array.
+
+use crate::{block_payload, validate_utf8, Error, Result, SemiStr, SemiStrVec, SemiStrVecBuilder, INLINE_CAP};
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr};
+use std::ptr;
+
+const ARROW_FLAG_NULLABLE: i64 = 2;
+
+/// ArrowSchema as defined by Arrow C Data Interface.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ArrowSchema {
+    pub format: *const c_char,
+    pub name: *const c_char,
+    pub metadata: *const c_char,
+    pub flags: i64,
+    pub n_children: i64,
+    pub children: *mut *mut ArrowSchema,
+    pub dictionary: *mut ArrowSchema,
+    pub release: Option<unsafe extern "C" fn(*mut ArrowSchema)>,
+    pub private_data: *mut c_void,
+}
+
+/// ArrowArray as defined by Arrow C Data Interface.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ArrowArray {
+    pub length: i64,
+    pub null_count: i64,
+    pub offset: i64,
+    pub n_buffers: i64,
+    pub n_children: i64,
+    pub buffers: *mut *const c_void,
+    pub children: *mut *mut ArrowArray,
+    pub dictionary: *mut ArrowArray,
+    pub release: Option<unsafe extern "C" fn(*mut ArrowArray)>,
+    pub private_data: *mut c_void,
+}
+
+impl Drop for ArrowSchema {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(release) = self.release {
+            unsafe { release(self) }
+        }
+    }
+}
+
+impl Drop for ArrowArray {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(release) = self.release {
+            unsafe { release(self) }
+        }
+    }
+}
+
+/// Buffers owned by exported array.
+struct Exported {
+    validity: Option<Vec<u8>>,
+    views: Vec<u128>,
+    sizes: Vec<i64>,
+    buffers: Vec<*const c_void>,
+    // shared strings keeping data buffers alive.
+    _owners: Vec<SemiStr>,
+}
+
+unsafe extern "C" fn release_array(array: *mut ArrowArray) {
+    let array = &mut *array;
+    drop(Box::from_raw(array.private_data as *mut Exported));
+    array.release = None;
+}
+
+unsafe extern "C" fn release_schema(schema: *mut ArrowSchema) {
+    (*schema).release = None;
+}
+
+impl SemiStrVec {
+    /// Export the column as Arrow string view array.
+    ///
+    /// Inline values are copied into views, long values refer to
+    /// heap buffers of SemiStr, which are shared without copying.
+    pub fn to_ffi(&self) -> (ArrowArray, ArrowSchema) {
+        let mut views = Vec::with_capacity(self.len());
+        let mut data: Vec<*const c_void> = vec![];
+        let mut sizes = vec![];
+        let mut owners = vec![];
+        let mut buf_idx: HashMap<*const u8, usize> = HashMap::new();
+        for s in self.iter() {
+            let mut view = [0u8; 16];
+            view[..4].copy_from_slice(&(s.len() as u32).to_ne_bytes());
+            if s.len() <= INLINE_CAP {
+                view[4..4 + s.len()].copy_from_slice(s.as_bytes());
+            } else {
+                view[4..8].copy_from_slice(&s.as_bytes()[..4]);
+                // static strings and offsets beyond i32 are exported as
+                // dedicated buffers keyed by data pointer, values with the
+                // same pointer may differ in length.
+                let (owner, offset) = match s.block_ref() {
+                    // SAFETY
+                    //
+                    // owner is pointer of heap block.
+                    Some((owner, offset)) if offset <= i32::MAX as usize => unsafe {
+                        (block_payload(owner), offset)
+                    },
+                    _ => (s.payload_ptr(), 0),
+                };
+                let idx = *buf_idx.entry(owner).or_insert_with(|| {
+                    data.push(owner as *const c_void);
+                    sizes.push(0);
+                    owners.push(s.share());
+                    data.len() - 1
+                });
+                // buffer size is the largest end in use, so spare
+                // capacity of the block is never exposed.
+                sizes[idx] = sizes[idx].max((offset + s.len()) as i64);
+                view[8..12].copy_from_slice(&(idx as i32).to_ne_bytes());
+                view[12..].copy_from_slice(&(offset as i32).to_ne_bytes());
+            }
+            views.push(u128::from_ne_bytes(view));
+        }
+        let validity = self.validity().map(|v| v.as_words().iter().flat_map(|w| w.to_le_bytes()).collect::<Vec<u8>>());
+        let mut exported = Box::new(Exported{validity, views, sizes, buffers: vec![], _owners: owners});
+        exported.buffers.push(exported.validity.as_ref().map_or(ptr::null(), |v| v.as_ptr() as *const c_void));
+        exported.buffers.push(exported.views.as_ptr() as *const c_void);
+        exported.buffers.extend(data);
+        exported.buffers.push(exported.sizes.as_ptr() as *const c_void);
+        let array = ArrowArray{
+            length: self.len() as i64,
+            null_count: self.null_count() as i64,
+            offset: 0,
+            n_buffers: exported.buffers.len() as i64,
+            n_children: 0,
+            buffers: exported.buffers.as_mut_ptr(),
+            children: ptr::null_mut(),
+            dictionary: ptr::null_mut(),
+            release: Some(release_array),
+            private_data: Box::into_raw(exported) as *mut c_void,
+        };
+        let schema = ArrowSchema{
+            format: c"vu".as_ptr(),
+            name: ptr::null(),
+            metadata: ptr::null(),
+            flags: if self.null_count() > 0 { ARROW_FLAG_NULLABLE } else { 0 },
+            n_children: 0,
+            children: ptr::null_mut(),
+            dictionary: ptr::null_mut(),
+            release: Some(release_schema),
+            private_data: ptr::null_mut(),
+        };
+        (array, schema)
+    }
+
+    /// Import Arrow array of format utf8, large utf8 or string view.
+    ///
+    /// Values are copied into shared buffers of SemiStr, and the array
+    /// is released after import.
+    ///
+    /// # Safety
+    ///
+    /// array and schema must be valid according to Arrow C Data Interface.
+    pub unsafe fn from_ffi(array: ArrowArray, schema: &ArrowSchema) -> Result<SemiStrVec> {
+        let format = CStr::from_ptr(schema.format).to_bytes();
+        let len = array.length as usize;
+        let offset = array.offset as usize;
+        let buffers = std::slice::from_raw_parts(array.buffers, array.n_buffers as usize);
+        let validity = buffers[0] as *const u8;
+        let is_valid = |i: usize| {
+            let i = offset + i;
+            validity.is_null() || *validity.add(i / 8) & (1 << (i % 8)) != 0
+        };
+        match format {
+            b"u" => {
+                let offsets = buffers[1] as *const i32;
+                let data = buffers[2] as *const u8;
+                import(len, is_valid, |i| {
+                    let start = *offsets.add(offset + i) as usize;
+                    let end = *offsets.add(offset + i + 1) as usize;
+                    std::slice::from_raw_parts(data.add(start), end - start)
+                })
+            }
+            b"U" => {
+                let offsets = buffers[1] as *const i64;
+                let data = buffers[2] as *const u8;
+                import(len, is_valid, |i| {
+                    let start = *offsets.add(offset + i) as usize;
+                    let end = *offsets.add(offset + i + 1) as usize;
+                    std::slice::from_raw_parts(data.add(start), end - start)
+                })
+            }
+            b"vu" => {
+                let views = buffers[1] as *const u8;
+                let data = &buffers[2..buffers.len() - 1];
+                import(len, is_valid, |i| {
+                    let view = std::slice::from_raw_parts(views.add((offset + i) * 16), 16);
+                    let n = i32::from_ne_bytes(view[..4].try_into().unwrap()) as usize;
+                    if n <= INLINE_CAP {
+                        return &view[4..4 + n]
+                    }
+                    let idx = i32::from_ne_bytes(view[8..12].try_into().unwrap()) as usize;
+                    let start = i32::from_ne_bytes(view[12..].try_into().unwrap()) as usize;
+                    std::slice::from_raw_parts((data[idx] as *const u8).add(start), n)
+                })
+            }
+            _ => Err(Error::UnsupportedArrowFormat(String::from_utf8_lossy(format).into_owned())),
+        }
+    }
+}
+
+#[inline]
+fn import<'a, V, F>(len: usize, is_valid: V, value: F) -> Result<SemiStrVec>
+where
+    V: Fn(usize) -> bool,
+    F: Fn(usize) -> &'a [u8],
+{
+    let bytes = (0..len).filter(|i| is_valid(*i)).map(|i| value(i).len()).filter(|n| *n > INLINE_CAP).sum();
+    let mut builder = SemiStrVecBuilder::with_capacity(len, bytes);
+    for i in 0..len {
+        if is_valid(i) {
+            let s = validate_utf8(value(i))?;
+            builder.append(s);
+        } else {
+            builder.append_null();
+        }
+    }
+    Ok(builder.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SemiString;
+
+    #[test]
+    fn test_ffi_roundtrip() {
+        let long = SemiStr::new("a long string shared by views");
+        let (_, tail) = long.split_at(2);
+        let mut col = SemiStrVec::new();
+        col.push(SemiStr::new("short"));
+        col.push_null();
+        col.push(long.clone());
+        col.push(tail);
+        col.push(SemiStr::from_static("a static string exported as is"));
+        let (array, schema) = col.to_ffi();
+        assert_eq!(array.length, 5);
+        assert_eq!(array.null_count, 1);
+        // validity, views, the heap buffer shared by clone and view,
+        // the static string, and sizes.
+        assert_eq!(array.n_buffers, 5);
+        let buffers = unsafe { std::slice::from_raw_parts(array.buffers, 5) };
+        assert_eq!(buffers[2] as *const u8, col[2].payload_ptr());
+        assert_eq!(buffers[3] as *const u8, col[4].payload_ptr());
+        let res = unsafe { SemiStrVec::from_ffi(array, &schema) }.unwrap();
+        assert_eq!(res, col);
+    }
+
+    #[test]
+    fn test_ffi_buffer_sizes() {
+        let mut s = SemiString::with_capacity(256);
+        s.push_str("a frozen string with spare capacity");
+        let frozen = s.freeze();
+        let mut col = SemiStrVec::new();
+        col.push(frozen.slice(2..20));
+        col.push(frozen.slice(..30));
+        let (array, schema) = col.to_ffi();
+        let buffers = unsafe { std::slice::from_raw_parts(array.buffers, 4) };
+        let sizes = unsafe { std::slice::from_raw_parts(buffers[3] as *const i64, 1) };
+        assert_eq!(sizes, &[30]);
+        let res = unsafe { SemiStrVec::from_ffi(array, &schema) }.unwrap();
+        assert_eq!(res, col);
+    }
+
+    #[test]
+    fn test_ffi_static_slices() {
+        let full = SemiStr::from_static("a static string of 31 bytes ...");
+        let mut col = SemiStrVec::new();
+        col.push(full.slice(0..20));
+        col.push(full.clone());
+        let (array, schema) = col.to_ffi();
+        assert_eq!(array.n_buffers, 4);
+        let buffers = unsafe { std::slice::from_raw_parts(array.buffers, 4) };
+        let sizes = unsafe { std::slice::from_raw_parts(buffers[3] as *const i64, 1) };
+        assert_eq!(sizes, &[31]);
+        let res = unsafe { SemiStrVec::from_ffi(array, &schema) }.unwrap();
+        assert_eq!(res, col);
+    }
+
+    #[test]
+    fn test_ffi_import_utf8() {
+        let offsets: [i32; 4] = [0, 3, 3, 21];
+        let data = b"abcthis is a long one";
+        let validity = [0b101u8];
+        let mut buffers = [validity.as_ptr() as *const c_void, offsets.as_ptr() as *const c_void, data.as_ptr() as *const c_void];
+        let array = ArrowArray{
+            length: 2, null_count: 1, offset: 1, n_buffers: 3, n_children: 0, buffers: buffers.as_mut_ptr(),
+            children: ptr::null_mut(), dictionary: ptr::null_mut(), release: None, private_data: ptr::null_mut(),
+        };
+        let mut schema = SemiStrVec::new().to_ffi().1;
+        schema.format = c"u".as_ptr();
+        let res = unsafe { SemiStrVec::from_ffi(array, &schema) }.unwrap();
+        assert!(res.is_null(0));
+        assert_eq!(res.get_opt(1).unwrap(), "this is a long one");
+        schema.format = c"i".as_ptr();
+        let array = SemiStrVec::new().to_ffi().0;
+        assert!(matches!(unsafe { SemiStrVec::from_ffi(array, &schema) }, Err(Error::UnsupportedArrowFormat(_))));
+    }
+}
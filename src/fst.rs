@@ -0,0 +1,162 @@
+//! Finite-state dictionaries built from sorted SemiStrs.
+//!
+//! Keys must be given in lexicographic byte order without duplicates,
+//! as `fst` requires. Query results are converted back to SemiStrs,
+//! so they can be pushed into columns like any other value.
+
+use crate::{Error, Result, SemiStr};
+use ::fst::automaton::{Automaton, Levenshtein, Str};
+use ::fst::{IntoStreamer, Map, MapBuilder, Set, SetBuilder, Streamer};
+use std::ops::{Bound, RangeBounds};
+
+/// Build an in-memory set from sorted strings.
+pub fn build_set<'a, I>(strs: I) -> Result<Set<Vec<u8>>>
+where
+    I: IntoIterator<Item = &'a SemiStr>,
+{
+    let mut builder = SetBuilder::memory();
+    for s in strs {
+        builder.insert(s.as_bytes()).map_err(fst_err)?;
+    }
+    let bytes = builder.into_inner().map_err(fst_err)?;
+    Set::new(bytes).map_err(fst_err)
+}
+
+/// Build an in-memory map from sorted strings and their values.
+pub fn build_map<'a, I>(entries: I) -> Result<Map<Vec<u8>>>
+where
+    I: IntoIterator<Item = (&'a SemiStr, u64)>,
+{
+    let mut builder = MapBuilder::memory();
+    for (s, v) in entries {
+        builder.insert(s.as_bytes(), v).map_err(fst_err)?;
+    }
+    let bytes = builder.into_inner().map_err(fst_err)?;
+    Map::new(bytes).map_err(fst_err)
+}
+
+/// Returns keys of the set within given range, in order.
+pub fn set_range<'r, D, R>(set: &Set<D>, range: R) -> Result<Vec<SemiStr>>
+where
+    D: AsRef<[u8]>,
+    R: RangeBounds<&'r str>,
+{
+    let mut builder = set.range();
+    builder = match range.start_bound() {
+        Bound::Included(lo) => builder.ge(lo),
+        Bound::Excluded(lo) => builder.gt(lo),
+        Bound::Unbounded => builder,
+    };
+    builder = match range.end_bound() {
+        Bound::Included(hi) => builder.le(hi),
+        Bound::Excluded(hi) => builder.lt(hi),
+        Bound::Unbounded => builder,
+    };
+    collect_keys(builder.into_stream())
+}
+
+/// Returns keys of the set starting with given prefix, in order.
+pub fn set_prefix<D: AsRef<[u8]>>(set: &Set<D>, prefix: &str) -> Result<Vec<SemiStr>> {
+    collect_keys(set.search(Str::new(prefix).starts_with()).into_stream())
+}
+
+/// Returns keys of the set within given Levenshtein distance to the
+/// query, in order.
+pub fn set_fuzzy<D: AsRef<[u8]>>(set: &Set<D>, query: &str, distance: u32) -> Result<Vec<SemiStr>> {
+    let lev = Levenshtein::new(query, distance).map_err(fst_err)?;
+    collect_keys(set.search(lev).into_stream())
+}
+
+/// Returns entries of the map within given range, in order.
+pub fn map_range<'r, D, R>(map: &Map<D>, range: R) -> Result<Vec<(SemiStr, u64)>>
+where
+    D: AsRef<[u8]>,
+    R: RangeBounds<&'r str>,
+{
+    let mut builder = map.range();
+    builder = match range.start_bound() {
+        Bound::Included(lo) => builder.ge(lo),
+        Bound::Excluded(lo) => builder.gt(lo),
+        Bound::Unbounded => builder,
+    };
+    builder = match range.end_bound() {
+        Bound::Included(hi) => builder.le(hi),
+        Bound::Excluded(hi) => builder.lt(hi),
+        Bound::Unbounded => builder,
+    };
+    collect_entries(builder.into_stream())
+}
+
+/// Returns entries of the map whose keys start with given prefix, in order.
+pub fn map_prefix<D: AsRef<[u8]>>(map: &Map<D>, prefix: &str) -> Result<Vec<(SemiStr, u64)>> {
+    collect_entries(map.search(Str::new(prefix).starts_with()).into_stream())
+}
+
+/// Returns entries of the map whose keys are within given Levenshtein
+/// distance to the query, in order.
+pub fn map_fuzzy<D: AsRef<[u8]>>(map: &Map<D>, query: &str, distance: u32) -> Result<Vec<(SemiStr, u64)>> {
+    let lev = Levenshtein::new(query, distance).map_err(fst_err)?;
+    collect_entries(map.search(lev).into_stream())
+}
+
+#[inline]
+fn collect_keys<S>(mut stream: S) -> Result<Vec<SemiStr>>
+where
+    S: for<'s> Streamer<'s, Item = &'s [u8]>,
+{
+    let mut res = vec![];
+    while let Some(k) = stream.next() {
+        res.push(SemiStr::try_from(k)?);
+    }
+    Ok(res)
+}
+
+#[inline]
+fn collect_entries<S>(mut stream: S) -> Result<Vec<(SemiStr, u64)>>
+where
+    S: for<'s> Streamer<'s, Item = (&'s [u8], u64)>,
+{
+    let mut res = vec![];
+    while let Some((k, v)) = stream.next() {
+        res.push((SemiStr::try_from(k)?, v));
+    }
+    Ok(res)
+}
+
+#[inline]
+fn fst_err<E: ToString>(e: E) -> Error {
+    Error::Fst(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_queries() {
+        let strs: Vec<SemiStr> = ["apple", "apply", "banana", "band", "a string longer than 12"]
+            .into_iter()
+            .map(SemiStr::new)
+            .collect();
+        let mut sorted = strs.clone();
+        sorted.sort();
+        let set = build_set(&sorted).unwrap();
+        assert_eq!(set.len(), 5);
+        assert_eq!(set_prefix(&set, "ban").unwrap(), vec!["banana", "band"]);
+        assert_eq!(set_range(&set, "apple".."b").unwrap(), vec!["apple", "apply"]);
+        assert_eq!(set_range(&set, "b"..).unwrap(), vec!["banana", "band"]);
+        assert_eq!(set_fuzzy(&set, "appla", 1).unwrap(), vec!["apple", "apply"]);
+        // unsorted input is rejected.
+        assert!(build_set(&strs).is_err());
+    }
+
+    #[test]
+    fn test_map_queries() {
+        let keys = [SemiStr::new("from"), SemiStr::new("select"), SemiStr::new("where")];
+        let map = build_map(keys.iter().zip(0..)).unwrap();
+        assert_eq!(map.get("where"), Some(2));
+        assert_eq!(map_prefix(&map, "se").unwrap(), vec![(SemiStr::new("select"), 1)]);
+        assert_eq!(map_range(&map, ..="select").unwrap().len(), 2);
+        assert_eq!(map_fuzzy(&map, "selct", 1).unwrap(), vec![(SemiStr::new("select"), 1)]);
+    }
+}
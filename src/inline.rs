@@ -0,0 +1,319 @@
+use crate::{validate_utf8, Error, Result};
+#[cfg(feature = "std")]
+use crate::SemiStr;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+/// InlineStr is a fixed-capacity string stored entirely inline, which
+/// never allocates.
+///
+/// It is the short-string half of SemiStr with configurable capacity.
+/// All growing methods are fallible and leave the string unchanged
+/// when capacity is exceeded.
+///
+/// No method of InlineStr touches the allocator, so it is available
+/// without the `std` feature. Conversions from and to SemiStr require
+/// `std`.
+#[derive(Clone, Copy)]
+pub struct InlineStr<const N: usize> {
+    len: u32,
+    // bytes beyond len are always zero, so equality compares the
+    // whole array.
+    data: [u8; N],
+}
+
+impl<const N: usize> InlineStr<N> {
+    #[inline]
+    pub const fn new() -> Self {
+        InlineStr{len: 0, data: [0u8; N]}
+    }
+
+    /// Create a string from utf-8 bytes.
+    #[inline]
+    pub fn from_utf8(bytes: &[u8]) -> Result<Self> {
+        validate_utf8(bytes).and_then(Self::try_from)
+    }
+
+    /// Create a string from formatted arguments, returns error if
+    /// result exceeds capacity. See also
+    /// [`format_inline!`](crate::format_inline).
+    #[inline]
+    pub fn try_from_fmt(args: fmt::Arguments<'_>) -> Result<Self> {
+        let mut w = Writer{s: Self::new(), overflow: 0};
+        match fmt::Write::write_fmt(&mut w, args) {
+            Ok(()) => Ok(w.s),
+            Err(_) if w.overflow > 0 => Err(Error::StringTooLong(w.overflow)),
+            Err(_) => panic!("a formatting trait implementation returned an error"),
+        }
+    }
+
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len()]
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // SAFETY
+        //
+        // buffer holds valid utf-8 string.
+        unsafe { std::str::from_utf8_unchecked(self.as_bytes()) }
+    }
+
+    #[inline]
+    pub fn try_push(&mut self, ch: char) -> Result<()> {
+        self.try_push_str(ch.encode_utf8(&mut [0u8; 4]))
+    }
+
+    #[inline]
+    pub fn try_push_str(&mut self, s: &str) -> Result<()> {
+        let len = self.len() + s.len();
+        if len > N {
+            return Err(Error::StringTooLong(len))
+        }
+        self.data[self.len as usize..len].copy_from_slice(s.as_bytes());
+        self.len = len as u32;
+        Ok(())
+    }
+
+    /// Remove the last char and return it.
+    #[inline]
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = self.as_str().chars().next_back()?;
+        self.truncate(self.len() - ch.len_utf8());
+        Some(ch)
+    }
+
+    /// Shorten the string to given byte length.
+    /// No effect if new_len is greater than current length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new_len is not on char boundary.
+    #[inline]
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.len() {
+            assert!(self.as_str().is_char_boundary(new_len), "new length {} is not a char boundary", new_len);
+            self.data[new_len..self.len as usize].fill(0);
+            self.len = new_len as u32;
+        }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+}
+
+/// Writer of formatted arguments, which records required length on
+/// overflow.
+struct Writer<const N: usize> {
+    s: InlineStr<N>,
+    overflow: usize,
+}
+
+impl<const N: usize> fmt::Write for Writer<N> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.s.try_push_str(s).map_err(|_| {
+            self.overflow = self.s.len() + s.len();
+            fmt::Error
+        })
+    }
+}
+
+/// Create an InlineStr using interpolation of runtime expressions,
+/// like `format!`, returns error if result exceeds capacity.
+///
+/// ```
+/// let id = 42;
+/// let s: semistr::InlineStr<16> = semistr::format_inline!("user-{}", id).unwrap();
+/// assert_eq!(s, "user-42");
+/// ```
+#[macro_export]
+macro_rules! format_inline {
+    ($($arg:tt)*) => {
+        $crate::InlineStr::try_from_fmt(::core::format_args!($($arg)*))
+    };
+}
+
+impl<const N: usize> Default for InlineStr<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'s, const N: usize> TryFrom<&'s str> for InlineStr<N> {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: &'s str) -> Result<Self> {
+        let mut res = Self::new();
+        res.try_push_str(value)?;
+        Ok(res)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'s, const N: usize> TryFrom<&'s SemiStr> for InlineStr<N> {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: &'s SemiStr) -> Result<Self> {
+        Self::try_from(value.as_str())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> From<InlineStr<N>> for SemiStr {
+    #[inline]
+    fn from(value: InlineStr<N>) -> Self {
+        SemiStr::new(value.as_str())
+    }
+}
+
+impl<const N: usize> fmt::Write for InlineStr<N> {
+    /// Returns error if capacity is exceeded, and the string is
+    /// unchanged.
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.try_push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N: usize> Deref for InlineStr<N> {
+    type Target = str;
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for InlineStr<N> {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> Borrow<str> for InlineStr<N> {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> PartialEq for InlineStr<N> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.data == other.data
+    }
+}
+
+impl<const N: usize> Eq for InlineStr<N> {}
+
+impl<const N: usize> PartialEq<str> for InlineStr<N> {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for InlineStr<N> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> PartialEq<SemiStr> for InlineStr<N> {
+    #[inline]
+    fn eq(&self, other: &SemiStr) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> PartialOrd for InlineStr<N> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for InlineStr<N> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const N: usize> Hash for InlineStr<N> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl<const N: usize> fmt::Debug for InlineStr<N> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Display for InlineStr<N> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn test_inline_str() {
+        let mut s = InlineStr::<8>::new();
+        s.try_push_str("abc").unwrap();
+        s.try_push('é').unwrap();
+        assert_eq!(s, "abcé");
+        assert!(matches!(s.try_push_str("defg"), Err(Error::StringTooLong(9))));
+        assert_eq!(s, "abcé");
+        assert!(write!(s, "{}", 12345).is_err());
+        assert_eq!(s.pop(), Some('é'));
+        assert_eq!(s, InlineStr::try_from("abc").unwrap());
+        s.clear();
+        assert!(s.is_empty());
+        assert_eq!(s, InlineStr::default());
+        assert!(InlineStr::<4>::from_utf8(b"\xff").is_err());
+        assert!(InlineStr::<4>::try_from("abc").unwrap() < InlineStr::try_from("abd").unwrap());
+
+        let (table, id) = ("orders", 7);
+        let s: InlineStr<16> = format_inline!("{}#{}", table, id).unwrap();
+        assert_eq!(s, "orders#7");
+        #[cfg(feature = "std")]
+        assert_eq!(SemiStr::from(s), "orders#7");
+        let res: Result<InlineStr<4>> = format_inline!("{}#{}", table, id);
+        assert!(matches!(res, Err(Error::StringTooLong(6))));
+    }
+}
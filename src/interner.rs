@@ -0,0 +1,175 @@
+use crate::arena::Arena;
+use crate::SemiStr;
+use std::collections::HashMap;
+
+/// Default capacity in bytes of the arena of ScopedInterner.
+const DEFAULT_ARENA_CAP: usize = 4096;
+
+/// ScopedInterner deduplicates strings within an epoch, e.g. the
+/// lifetime of a query.
+///
+/// Long strings are written into a shared arena, so interning takes
+/// no allocation per entry. [`ScopedInterner::reset`] discards all
+/// entries at once and starts a new epoch, reusing the arena if no
+/// string handed out still references it.
+///
+/// Symbols are only valid within the epoch they are created in.
+/// This is checked in debug builds.
+pub struct ScopedInterner {
+    arena: Arena,
+    map: HashMap<SemiStr, u32>,
+    strs: Vec<SemiStr>,
+    epoch: u32,
+}
+
+/// Symbol is a handle of string interned in [`ScopedInterner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol {
+    idx: u32,
+    #[cfg(debug_assertions)]
+    epoch: u32,
+}
+
+impl Symbol {
+    /// Returns index of the symbol, which is dense in its epoch.
+    #[inline]
+    pub fn index(self) -> usize {
+        self.idx as usize
+    }
+}
+
+impl Default for ScopedInterner {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScopedInterner {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_ARENA_CAP)
+    }
+
+    /// Create an interner whose arena has given capacity in bytes.
+    #[inline]
+    pub fn with_capacity(cap: usize) -> Self {
+        ScopedInterner{arena: Arena::with_capacity(cap), map: HashMap::new(), strs: vec![], epoch: 0}
+    }
+
+    /// Returns number of distinct strings in current epoch.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.strs.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.strs.is_empty()
+    }
+
+    /// Returns current epoch, which starts from 0 and is increased
+    /// by every reset.
+    #[inline]
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    /// Intern the string, returns symbol of existing entry if equal
+    /// string is already interned in current epoch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is longer than 4GB.
+    #[inline]
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(idx) = self.map.get(s) {
+            return self.symbol(*idx)
+        }
+        let idx = self.strs.len() as u32;
+        let value = self.arena.push(&[s]);
+        self.map.insert(value.share(), idx);
+        self.strs.push(value);
+        self.symbol(idx)
+    }
+
+    /// Returns symbol of the string if it is interned in current epoch.
+    #[inline]
+    pub fn lookup(&self, s: &str) -> Option<Symbol> {
+        self.map.get(s).map(|idx| self.symbol(*idx))
+    }
+
+    /// Returns the string of given symbol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the symbol is created in another epoch, in debug builds.
+    #[inline]
+    pub fn resolve(&self, sym: Symbol) -> &SemiStr {
+        #[cfg(debug_assertions)]
+        assert_eq!(sym.epoch, self.epoch, "symbol of epoch {} used in epoch {}", sym.epoch, self.epoch);
+        &self.strs[sym.idx as usize]
+    }
+
+    /// Discard all interned strings and start a new epoch.
+    ///
+    /// Strings resolved and cloned before reset remain valid, but
+    /// symbols do not.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.map.clear();
+        self.strs.clear();
+        self.arena.clear();
+        self.epoch = self.epoch.wrapping_add(1);
+    }
+
+    #[inline]
+    fn symbol(&self, idx: u32) -> Symbol {
+        Symbol{
+            idx,
+            #[cfg(debug_assertions)]
+            epoch: self.epoch,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoped_interner() {
+        let mut interner = ScopedInterner::with_capacity(64);
+        let a = interner.intern("customer_id");
+        let b = interner.intern("a column name longer than 12");
+        assert_eq!(interner.intern("customer_id"), a);
+        assert_eq!(interner.lookup("a column name longer than 12"), Some(b));
+        assert_eq!(interner.lookup("missing"), None);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(b.index(), 1);
+        assert_eq!(interner.resolve(a), "customer_id");
+        let ptr = interner.resolve(b).payload_ptr();
+        interner.reset();
+        assert!(interner.is_empty());
+        assert_eq!(interner.epoch(), 1);
+        // arena is reused as no string references it.
+        let c = interner.intern("another long string, epoch 1");
+        assert_eq!(c.index(), 0);
+        assert_eq!(interner.resolve(c).payload_ptr(), ptr);
+        let kept = interner.resolve(c).clone();
+        interner.reset();
+        interner.intern("a long string of epoch 2");
+        assert_eq!(kept, "another long string, epoch 1");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_scoped_interner_stale_symbol() {
+        let mut interner = ScopedInterner::new();
+        let sym = interner.intern("stale");
+        interner.reset();
+        interner.intern("fresh");
+        interner.resolve(sym);
+    }
+}
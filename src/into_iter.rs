@@ -0,0 +1,143 @@
+use crate::SemiStr;
+use std::iter::FusedIterator;
+
+/// Owned iterator over chars of a SemiStr, see [`SemiStr::into_chars`].
+#[derive(Debug, Clone)]
+pub struct IntoChars {
+    s: SemiStr,
+    // byte range of remaining chars.
+    start: usize,
+    end: usize,
+}
+
+/// Owned iterator over bytes of a SemiStr, see [`SemiStr::into_bytes_iter`].
+#[derive(Debug, Clone)]
+pub struct IntoBytes {
+    s: SemiStr,
+    // range of remaining bytes.
+    start: usize,
+    end: usize,
+}
+
+impl SemiStr {
+    /// Convert into an iterator over chars, which owns the string,
+    /// so it can be returned without borrowing.
+    #[inline]
+    pub fn into_chars(self) -> IntoChars {
+        let end = self.len();
+        IntoChars{s: self, start: 0, end}
+    }
+
+    /// Convert into an iterator over bytes, which owns the string,
+    /// so it can be returned without borrowing.
+    #[inline]
+    pub fn into_bytes_iter(self) -> IntoBytes {
+        let end = self.len();
+        IntoBytes{s: self, start: 0, end}
+    }
+}
+
+impl IntoChars {
+    /// Returns the remaining string.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.s.as_str()[self.start..self.end]
+    }
+}
+
+impl Iterator for IntoChars {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        let ch = self.as_str().chars().next()?;
+        self.start += ch.len_utf8();
+        Some(ch)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len.div_ceil(4), Some(len))
+    }
+}
+
+impl DoubleEndedIterator for IntoChars {
+    #[inline]
+    fn next_back(&mut self) -> Option<char> {
+        let ch = self.as_str().chars().next_back()?;
+        self.end -= ch.len_utf8();
+        Some(ch)
+    }
+}
+
+impl FusedIterator for IntoChars {}
+
+impl IntoBytes {
+    /// Returns the remaining bytes.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.s.as_bytes()[self.start..self.end]
+    }
+}
+
+impl Iterator for IntoBytes {
+    type Item = u8;
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        let b = *self.as_bytes().first()?;
+        self.start += 1;
+        Some(b)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for IntoBytes {
+    #[inline]
+    fn next_back(&mut self) -> Option<u8> {
+        let b = *self.as_bytes().last()?;
+        self.end -= 1;
+        Some(b)
+    }
+}
+
+impl ExactSizeIterator for IntoBytes {}
+
+impl FusedIterator for IntoBytes {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tail_chars(s: &str) -> IntoChars {
+        SemiStr::new(s).into_chars()
+    }
+
+    #[test]
+    fn test_into_chars() {
+        let mut chars = tail_chars("héllo wörld, a long string");
+        assert_eq!(chars.next(), Some('h'));
+        assert_eq!(chars.next(), Some('é'));
+        assert_eq!(chars.next_back(), Some('g'));
+        assert_eq!(chars.as_str(), "llo wörld, a long strin");
+        assert_eq!(chars.collect::<String>(), "llo wörld, a long strin");
+        assert_eq!(tail_chars("").next(), None);
+        assert_eq!(tail_chars("ab𝄞").rev().collect::<String>(), "𝄞ba");
+    }
+
+    #[test]
+    fn test_into_bytes_iter() {
+        let mut bytes = SemiStr::new("héllo").into_bytes_iter();
+        assert_eq!(bytes.len(), 6);
+        assert_eq!(bytes.next(), Some(b'h'));
+        assert_eq!(bytes.next_back(), Some(b'o'));
+        assert_eq!(bytes.as_bytes(), "éll".as_bytes());
+        assert_eq!(bytes.collect::<Vec<u8>>(), "éll".as_bytes());
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{Error, Result, SemiStr, SemiString, MAX_LEN};
+use std::io::{ErrorKind, Read};
+
+/// Initial capacity of buffer reading a string.
+const INIT_READ_CAP: usize = 64;
+
+impl SemiStr {
+    /// Read all bytes of the reader into a string no longer than
+    /// max_len bytes.
+    ///
+    /// Bytes are read directly into a heap block and validated
+    /// incrementally, so no intermediate String is built.
+    /// Returns error if the reader fails, the bytes are not valid
+    /// utf-8, or the string is longer than max_len or 4GB.
+    #[inline]
+    pub fn from_reader<R: Read>(mut r: R, max_len: usize) -> Result<SemiStr> {
+        let mut buf = ReadBuf::new(max_len);
+        loop {
+            match r.read(buf.spare()) {
+                Ok(0) => return buf.finish(),
+                Ok(n) => buf.commit(n)?,
+                Err(e) if e.kind() == ErrorKind::Interrupted => (),
+                Err(e) => return Err(Error::Io(e.to_string())),
+            }
+        }
+    }
+
+    /// Read all bytes of the async reader into a string no longer than
+    /// max_len bytes, same as [`SemiStr::from_reader`].
+    #[cfg(feature = "tokio")]
+    #[inline]
+    pub async fn from_async_read<R: tokio::io::AsyncRead + Unpin>(mut r: R, max_len: usize) -> Result<SemiStr> {
+        use tokio::io::AsyncReadExt;
+        let mut buf = ReadBuf::new(max_len);
+        loop {
+            match r.read(buf.spare()).await {
+                Ok(0) => return buf.finish(),
+                Ok(n) => buf.commit(n)?,
+                Err(e) if e.kind() == ErrorKind::Interrupted => (),
+                Err(e) => return Err(Error::Io(e.to_string())),
+            }
+        }
+    }
+}
+
+/// Buffer of a string being read, which validates utf-8 incrementally.
+pub(crate) struct ReadBuf {
+    // validated part is the string, pending bytes are kept in spare
+    // capacity.
+    s: SemiString,
+    filled: usize,
+    max_len: usize,
+    // one-byte buffer to detect more input once max_len is reached.
+    probe: [u8; 1],
+}
+
+impl ReadBuf {
+    #[inline]
+    pub(crate) fn new(max_len: usize) -> Self {
+        ReadBuf{s: SemiString::new(), filled: 0, max_len: max_len.min(MAX_LEN), probe: [0u8; 1]}
+    }
+
+    /// Returns zeroed buffer to read into.
+    #[inline]
+    pub(crate) fn spare(&mut self) -> &mut [u8] {
+        if self.filled == self.max_len {
+            return &mut self.probe
+        }
+        let cap = self.s.capacity();
+        if self.filled == cap {
+            let want = (self.filled - self.s.len() + 1).max(INIT_READ_CAP.min(self.max_len));
+            self.s.reserve(want);
+            let new_cap = self.s.capacity();
+            // SAFETY
+            //
+            // spare capacity is zeroed so it can be passed to reader.
+            unsafe { self.s.as_mut_ptr().add(cap).write_bytes(0, new_cap - cap) };
+        }
+        let end = self.s.capacity().min(self.max_len);
+        // SAFETY
+        //
+        // bytes up to capacity are initialized above.
+        unsafe { std::slice::from_raw_parts_mut(self.s.as_mut_ptr().add(self.filled), end - self.filled) }
+    }
+
+    /// Commit n bytes read into the spare buffer, and validate them.
+    #[inline]
+    pub(crate) fn commit(&mut self, n: usize) -> Result<()> {
+        if n == 0 {
+            return Ok(())
+        }
+        if self.filled == self.max_len {
+            return Err(Error::StringTooLong(self.max_len + 1))
+        }
+        self.filled += n;
+        let len = self.s.len();
+        // SAFETY
+        //
+        // bytes up to filled are read from the reader.
+        let pending = unsafe { std::slice::from_raw_parts(self.s.as_mut_ptr().add(len), self.filled - len) };
+        let valid = match std::str::from_utf8(pending) {
+            Ok(_) => pending.len(),
+            // incomplete char at the end may be completed by next read.
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => return Err(Error::InvalidUtf8String),
+        };
+        // SAFETY
+        //
+        // bytes up to new length are validated.
+        unsafe { self.s.set_len(len + valid) };
+        Ok(())
+    }
+
+    /// Finish reading and convert into SemiStr.
+    #[inline]
+    pub(crate) fn finish(mut self) -> Result<SemiStr> {
+        if self.s.len() != self.filled {
+            return Err(Error::InvalidUtf8String)
+        }
+        self.s.shrink_to_fit();
+        Ok(self.s.freeze())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reader returning at most n bytes per read.
+    struct Chunked<'a>(&'a [u8], usize);
+
+    impl Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.0.len().min(self.1).min(buf.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let text = "héllo wörld 𝄞, ".repeat(20);
+        let s = SemiStr::from_reader(text.as_bytes(), 1024).unwrap();
+        assert_eq!(s.as_str(), text);
+        assert_eq!(s.heap_size_bytes(), crate::BLOCK_HEADER + text.len());
+        // chars split across reads.
+        let s = SemiStr::from_reader(Chunked(text.as_bytes(), 3), 1024).unwrap();
+        assert_eq!(s.as_str(), text);
+        let s = SemiStr::from_reader(&b"short"[..], 5).unwrap();
+        assert!(s.is_inline());
+        assert_eq!(SemiStr::from_reader(&b""[..], 0).unwrap(), "");
+        assert!(matches!(SemiStr::from_reader(text.as_bytes(), text.len() - 1), Err(Error::StringTooLong(_))));
+        assert!(matches!(SemiStr::from_reader(&b"ok\xc3"[..], 10), Err(Error::InvalidUtf8String)));
+        assert!(matches!(SemiStr::from_reader(Chunked(b"ok\xc3(", 3), 10), Err(Error::InvalidUtf8String)));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_from_async_read() {
+        let text = "héllo wörld 𝄞, ".repeat(20);
+        let s = SemiStr::from_async_read(text.as_bytes(), 1024).await.unwrap();
+        assert_eq!(s.as_str(), text);
+        let (mut tx, rx) = tokio::io::duplex(7);
+        let body = text.clone();
+        let writer = tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            tx.write_all(body.as_bytes()).await.unwrap();
+        });
+        let s = SemiStr::from_async_read(rx, 1024).await.unwrap();
+        writer.await.unwrap();
+        assert_eq!(s.as_str(), text);
+        let res = SemiStr::from_async_read(text.as_bytes(), 10).await;
+        assert!(matches!(res, Err(Error::StringTooLong(_))));
+    }
+}
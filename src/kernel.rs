@@ -0,0 +1,47 @@
+//! Batch kernels operating on columns of SemiStr.
+//!
+//! Kernels work on `&[SemiStr]` so that they apply to any container
+//! which can expose its values as a contiguous slice of headers.
+
+mod case;
+mod cast;
+mod cmp;
+mod concat;
+mod gather;
+mod join;
+mod length;
+mod prefix;
+#[cfg(feature = "regex")]
+mod regexp;
+mod substr;
+mod topk;
+
+pub use case::{eq_ignore_case, like_ignore_case};
+pub use cast::{parse_bool, parse_date, parse_f64, parse_i64};
+pub use cmp::{compare, compare_scalar, is_distinct_from, is_not_distinct_from, CmpOp};
+pub use concat::concat_columns;
+pub use gather::{gather_iter, gather_strs, GatherIter};
+pub use join::{merge_join, MergeJoin};
+pub use length::{byte_len, char_len};
+pub use prefix::{extract_prefixes, extract_prefixes_into};
+#[cfg(feature = "regex")]
+pub use regexp::{regexp_captures, regexp_match};
+pub use substr::substr;
+pub use topk::top_k;
+
+use crate::{Bitmap, SemiStr, SemiStrVec};
+
+/// Evaluate predicate on non-null rows, returns result bitmap and
+/// null bitmap.
+#[inline]
+pub(crate) fn eval_nullable<F: FnMut(&SemiStr) -> bool>(col: &SemiStrVec, mut f: F) -> (Bitmap, Bitmap) {
+    let mut res = Bitmap::new(col.len());
+    let mut nulls = Bitmap::new(col.len());
+    for i in 0..col.len() {
+        match col.get_opt(i) {
+            Some(s) => res.set(i, f(s)),
+            None => nulls.set(i, true),
+        }
+    }
+    (res, nulls)
+}
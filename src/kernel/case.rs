@@ -0,0 +1,157 @@
+use super::eval_nullable;
+use crate::{Bitmap, SemiStr, SemiStrVec};
+
+/// Compare every string with the pattern, ignoring case, with SQL
+/// three-valued logic.
+///
+/// Strings are compared after Unicode lowercase folding. Leading ASCII
+/// bytes of the stored prefix are folded first, so most mismatches are
+/// rejected without touching heap payloads.
+///
+/// Returns result bitmap and null bitmap, the same as
+/// [`compare_scalar`](super::compare_scalar).
+pub fn eq_ignore_case(col: &SemiStrVec, pattern: &str) -> (Bitmap, Bitmap) {
+    let folded: String = fold(pattern).collect();
+    eval_nullable(col, |s| prefix_matches(s, folded.as_bytes()) && fold(s).eq(folded.chars()))
+}
+
+/// Match every string against the SQL LIKE pattern, ignoring case,
+/// with SQL three-valued logic.
+///
+/// `%` matches any sequence of chars, `_` matches a single char, and
+/// `\` escapes the next char. Strings and pattern are compared after
+/// Unicode lowercase folding, and the literal prefix of the pattern is
+/// checked against the stored prefix first, see [`eq_ignore_case`].
+pub fn like_ignore_case(col: &SemiStrVec, pattern: &str) -> (Bitmap, Bitmap) {
+    let tokens = parse_like(pattern);
+    let literal: String = tokens.iter().map_while(|t| match t {
+        Token::Char(c) => Some(*c),
+        _ => None,
+    }).collect();
+    let mut buf: Vec<char> = vec![];
+    eval_nullable(col, |s| {
+        if !prefix_matches(s, literal.as_bytes()) {
+            return false
+        }
+        buf.clear();
+        buf.extend(fold(s));
+        like_match(&buf, &tokens)
+    })
+}
+
+#[inline]
+fn fold(s: &str) -> impl Iterator<Item = char> + '_ {
+    s.chars().flat_map(char::to_lowercase)
+}
+
+/// Check whether folded string may start with the folded prefix,
+/// by the leading ASCII bytes of the stored prefix.
+#[inline]
+fn prefix_matches(s: &SemiStr, folded: &[u8]) -> bool {
+    let prefix = s.prefix_bytes();
+    let n = s.len().min(4);
+    prefix[..n].iter()
+        .take_while(|b| b.is_ascii())
+        .zip(folded)
+        .all(|(a, b)| a.to_ascii_lowercase() == *b)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Char(char),
+    Any,
+    Many,
+}
+
+/// Parse folded pattern into tokens.
+fn parse_like(pattern: &str) -> Vec<Token> {
+    let mut res = vec![];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => if res.last() != Some(&Token::Many) { res.push(Token::Many) },
+            '_' => res.push(Token::Any),
+            '\\' => {
+                let escaped = chars.next().unwrap_or('\\');
+                res.extend(escaped.to_lowercase().map(Token::Char));
+            }
+            c => res.extend(c.to_lowercase().map(Token::Char)),
+        }
+    }
+    res
+}
+
+/// Wildcard matching with backtracking to the last `%`.
+fn like_match(s: &[char], tokens: &[Token]) -> bool {
+    let (mut i, mut j) = (0, 0);
+    // position after last `%` in tokens, and position in s it is retried at.
+    let mut star: Option<(usize, usize)> = None;
+    while i < s.len() {
+        match tokens.get(j) {
+            Some(Token::Many) => {
+                star = Some((j + 1, i));
+                j += 1;
+            }
+            Some(Token::Any) => {
+                i += 1;
+                j += 1;
+            }
+            Some(Token::Char(c)) if *c == s[i] => {
+                i += 1;
+                j += 1;
+            }
+            _ => match star {
+                Some((sj, si)) => {
+                    star = Some((sj, si + 1));
+                    i = si + 1;
+                    j = sj;
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[j..].iter().all(|t| *t == Token::Many)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eq_ignore_case() {
+        let col: SemiStrVec = ["Hello", "HELLO", "hellO!", "Straße Überweg", "STRASSE überweg", "other"].into_iter().collect();
+        let (res, _) = eq_ignore_case(&col, "hello");
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![true, true, false, false, false, false]);
+        let (res, _) = eq_ignore_case(&col, "straße überweg");
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![false, false, false, true, false, false]);
+    }
+
+    #[test]
+    fn test_like_ignore_case() {
+        let col: SemiStrVec = ["SELECT * FROM t", "select 1", "Deselect", "sel_ct", "Ünicode Text", ""].into_iter().collect();
+        let (res, _) = like_ignore_case(&col, "select%");
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![true, true, false, false, false, false]);
+        let (res, _) = like_ignore_case(&col, "%SELECT%");
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![true, true, true, false, false, false]);
+        let (res, _) = like_ignore_case(&col, "sel\\_c_");
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![false, false, false, true, false, false]);
+        let (res, _) = like_ignore_case(&col, "ü%t");
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![false, false, false, false, true, false]);
+        let (res, _) = like_ignore_case(&col, "%");
+        assert_eq!(res.count_ones(), 6);
+    }
+
+    #[test]
+    fn test_ignore_case_nulls() {
+        let mut col = SemiStrVec::new();
+        col.push(SemiStr::new(""));
+        col.push_null();
+        col.push(SemiStr::new("Hello"));
+        let (res, nulls) = eq_ignore_case(&col, "");
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![true, false, false]);
+        assert_eq!(nulls.iter().collect::<Vec<_>>(), vec![false, true, false]);
+        let (res, nulls) = like_ignore_case(&col, "%");
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![true, false, true]);
+        assert_eq!(nulls.iter().collect::<Vec<_>>(), vec![false, true, false]);
+    }
+}
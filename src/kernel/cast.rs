@@ -0,0 +1,169 @@
+use crate::{Bitmap, SemiStr};
+
+/// Parse all strings as i64.
+///
+/// Returns parsed values and a bitmap marking invalid rows, whose
+/// values are set to 0. Leading and trailing ASCII whitespaces are
+/// ignored.
+pub fn parse_i64(strs: &[SemiStr]) -> (Vec<i64>, Bitmap) {
+    cast(strs, parse_i64_bytes)
+}
+
+/// Parse all strings as f64.
+///
+/// Returns parsed values and a bitmap marking invalid rows, whose
+/// values are set to 0.0. Leading and trailing ASCII whitespaces are
+/// ignored.
+pub fn parse_f64(strs: &[SemiStr]) -> (Vec<f64>, Bitmap) {
+    cast(strs, |b| std::str::from_utf8(b).ok()?.parse().ok())
+}
+
+/// Parse all strings as bool.
+///
+/// Accepted values are `true`/`false`, `t`/`f`, `yes`/`no`, `y`/`n`,
+/// `on`/`off` and `1`/`0`, case insensitive.
+/// Returns parsed values and a bitmap marking invalid rows, whose
+/// values are set to false. Leading and trailing ASCII whitespaces are
+/// ignored.
+pub fn parse_bool(strs: &[SemiStr]) -> (Vec<bool>, Bitmap) {
+    cast(strs, parse_bool_bytes)
+}
+
+/// Parse all strings as date in `YYYY-MM-DD` format.
+///
+/// Values are number of days since 1970-01-01.
+/// Returns parsed values and a bitmap marking invalid rows, whose
+/// values are set to 0. Leading and trailing ASCII whitespaces are
+/// ignored.
+pub fn parse_date(strs: &[SemiStr]) -> (Vec<i32>, Bitmap) {
+    cast(strs, parse_date_bytes)
+}
+
+#[inline]
+fn cast<T: Default, F: Fn(&[u8]) -> Option<T>>(strs: &[SemiStr], f: F) -> (Vec<T>, Bitmap) {
+    let mut values = Vec::with_capacity(strs.len());
+    let mut invalid = Bitmap::new(strs.len());
+    for (i, s) in strs.iter().enumerate() {
+        // short values are read from inline buffer without indirection.
+        match f(s.as_bytes().trim_ascii()) {
+            Some(v) => values.push(v),
+            None => {
+                values.push(T::default());
+                invalid.set(i, true);
+            }
+        }
+    }
+    (values, invalid)
+}
+
+#[inline]
+fn parse_i64_bytes(b: &[u8]) -> Option<i64> {
+    let (neg, digits) = match b.split_first()? {
+        (b'-', rest) => (true, rest),
+        (b'+', rest) => (false, rest),
+        _ => (false, b),
+    };
+    if digits.is_empty() {
+        return None
+    }
+    // accumulate negatively to cover i64::MIN.
+    let mut res: i64 = 0;
+    for d in digits {
+        let d = d.wrapping_sub(b'0');
+        if d > 9 {
+            return None
+        }
+        res = res.checked_mul(10)?.checked_sub(d as i64)?;
+    }
+    if neg { Some(res) } else { res.checked_neg() }
+}
+
+#[inline]
+fn parse_bool_bytes(b: &[u8]) -> Option<bool> {
+    const TRUE: [&[u8]; 6] = [b"true", b"t", b"yes", b"y", b"on", b"1"];
+    const FALSE: [&[u8]; 6] = [b"false", b"f", b"no", b"n", b"off", b"0"];
+    if TRUE.iter().any(|t| t.eq_ignore_ascii_case(b)) {
+        Some(true)
+    } else if FALSE.iter().any(|f| f.eq_ignore_ascii_case(b)) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+#[inline]
+fn parse_date_bytes(b: &[u8]) -> Option<i32> {
+    if b.len() != 10 || b[4] != b'-' || b[7] != b'-' {
+        return None
+    }
+    let num = |b: &[u8]| -> Option<u32> {
+        b.iter().try_fold(0u32, |acc, d| {
+            let d = d.wrapping_sub(b'0');
+            if d > 9 { None } else { Some(acc * 10 + d as u32) }
+        })
+    };
+    let (y, m, d) = (num(&b[..4])? as i32, num(&b[5..7])?, num(&b[8..])?);
+    if !(1..=12).contains(&m) || d == 0 || d > days_in_month(y, m) {
+        return None
+    }
+    Some(days_from_civil(y, m, d))
+}
+
+#[inline]
+fn days_in_month(y: i32, m: u32) -> u32 {
+    match m {
+        2 if (y % 4 == 0 && y % 100 != 0) || y % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns number of days since 1970-01-01 of a proleptic Gregorian date.
+#[inline]
+fn days_from_civil(y: i32, m: u32, d: u32) -> i32 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy as i32;
+    era * 146097 + doe - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn col(strs: &[&str]) -> Vec<SemiStr> {
+        strs.iter().map(|s| SemiStr::new(s)).collect()
+    }
+
+    #[test]
+    fn test_parse_i64() {
+        let (values, invalid) = parse_i64(&col(&["1", " -42 ", "+7", "abc", "", "-9223372036854775808", "9223372036854775808", "12345678901234"]));
+        assert_eq!(values, vec![1, -42, 7, 0, 0, i64::MIN, 0, 12345678901234]);
+        assert_eq!(invalid.iter_ones().collect::<Vec<_>>(), vec![3, 4, 6]);
+    }
+
+    #[test]
+    fn test_parse_f64() {
+        let (values, invalid) = parse_f64(&col(&["1.5", "-2e3", "nan?", " 0.25"]));
+        assert_eq!(values, vec![1.5, -2000.0, 0.0, 0.25]);
+        assert_eq!(invalid.iter_ones().collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_parse_bool() {
+        let (values, invalid) = parse_bool(&col(&["TRUE", "f", "yes", "0", "maybe"]));
+        assert_eq!(values, vec![true, false, true, false, false]);
+        assert_eq!(invalid.iter_ones().collect::<Vec<_>>(), vec![4]);
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let (values, invalid) = parse_date(&col(&["1970-01-01", "2000-03-01", "1969-12-31", "2023-02-29", "2024-02-29", "20240229"]));
+        assert_eq!(values, vec![0, 11017, -1, 0, 19782, 0]);
+        assert_eq!(invalid.iter_ones().collect::<Vec<_>>(), vec![3, 5]);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{Bitmap, SemiStr, SemiStrVec};
+use std::cmp::Ordering;
+
+/// Comparison operator of kernels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CmpOp {
+    #[inline]
+    fn eval(self, a: &SemiStr, b: &SemiStr) -> bool {
+        match self {
+            CmpOp::Eq => a == b,
+            CmpOp::Ne => a != b,
+            CmpOp::Lt => a.cmp(b) == Ordering::Less,
+            CmpOp::Le => a.cmp(b) != Ordering::Greater,
+            CmpOp::Gt => a.cmp(b) == Ordering::Greater,
+            CmpOp::Ge => a.cmp(b) != Ordering::Less,
+        }
+    }
+}
+
+/// Compare two columns row by row, with SQL three-valued logic.
+///
+/// Returns result bits and a bitmap marking null results, which are
+/// produced if either side is null. Result bits of null rows are unset.
+///
+/// # Panics
+///
+/// Panics if two columns have different lengths.
+pub fn compare(a: &SemiStrVec, b: &SemiStrVec, op: CmpOp) -> (Bitmap, Bitmap) {
+    assert_eq!(a.len(), b.len(), "columns to compare have different lengths");
+    let mut res = Bitmap::new(a.len());
+    let mut nulls = Bitmap::new(a.len());
+    for i in 0..a.len() {
+        match (a.get_opt(i), b.get_opt(i)) {
+            (Some(x), Some(y)) => res.set(i, op.eval(x, y)),
+            _ => nulls.set(i, true),
+        }
+    }
+    (res, nulls)
+}
+
+/// Compare a column with a scalar, with SQL three-valued logic.
+///
+/// A null scalar makes all results null, see [`compare`].
+pub fn compare_scalar(a: &SemiStrVec, b: Option<&SemiStr>, op: CmpOp) -> (Bitmap, Bitmap) {
+    let mut res = Bitmap::new(a.len());
+    let Some(b) = b else {
+        return (res, Bitmap::new_set(a.len()))
+    };
+    let mut nulls = Bitmap::new(a.len());
+    for i in 0..a.len() {
+        match a.get_opt(i) {
+            Some(x) => res.set(i, op.eval(x, b)),
+            None => nulls.set(i, true),
+        }
+    }
+    (res, nulls)
+}
+
+/// Evaluate `a IS DISTINCT FROM b` row by row.
+///
+/// Null is not distinct from null, and distinct from any value,
+/// so results are never null.
+///
+/// # Panics
+///
+/// Panics if two columns have different lengths.
+pub fn is_distinct_from(a: &SemiStrVec, b: &SemiStrVec) -> Bitmap {
+    assert_eq!(a.len(), b.len(), "columns to compare have different lengths");
+    (0..a.len()).map(|i| a.get_opt(i) != b.get_opt(i)).collect()
+}
+
+/// Evaluate `a IS NOT DISTINCT FROM b` row by row, see [`is_distinct_from`].
+///
+/// # Panics
+///
+/// Panics if two columns have different lengths.
+pub fn is_not_distinct_from(a: &SemiStrVec, b: &SemiStrVec) -> Bitmap {
+    assert_eq!(a.len(), b.len(), "columns to compare have different lengths");
+    (0..a.len()).map(|i| a.get_opt(i) == b.get_opt(i)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SemiStrVecBuilder;
+
+    fn column(values: &[Option<&str>]) -> SemiStrVec {
+        let mut b = SemiStrVecBuilder::new();
+        for v in values {
+            match v {
+                Some(v) => b.append(v),
+                None => b.append_null(),
+            }
+        }
+        b.finish()
+    }
+
+    #[test]
+    fn test_compare() {
+        let a = column(&[Some("apple"), None, Some("a long string value"), None, Some("")]);
+        let b = column(&[Some("banana"), Some("x"), Some("a long string value"), None, None]);
+        let (res, nulls) = compare(&a, &b, CmpOp::Lt);
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![true, false, false, false, false]);
+        assert_eq!(nulls.iter().collect::<Vec<_>>(), vec![false, true, false, true, true]);
+        let (res, _) = compare(&a, &b, CmpOp::Ge);
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![false, false, true, false, false]);
+        // empty string is not null.
+        let (res, nulls) = compare_scalar(&a, Some(&SemiStr::new("")), CmpOp::Eq);
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![false, false, false, false, true]);
+        assert_eq!(nulls.count_ones(), 2);
+        let (res, nulls) = compare_scalar(&a, None, CmpOp::Ne);
+        assert!(!res.any());
+        assert_eq!(nulls.count_ones(), 5);
+    }
+
+    #[test]
+    fn test_is_distinct_from() {
+        let a = column(&[Some("x"), None, None, Some("y")]);
+        let b = column(&[Some("x"), None, Some(""), Some("z")]);
+        assert_eq!(is_distinct_from(&a, &b).iter().collect::<Vec<_>>(), vec![false, false, true, true]);
+        assert_eq!(is_not_distinct_from(&a, &b).iter().collect::<Vec<_>>(), vec![true, true, false, false]);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::arena::Arena;
+use crate::{SemiStr, SemiStrVec, INLINE_CAP};
+
+/// Concatenate strings of two columns row by row, with separator
+/// between them.
+///
+/// Total size of long outputs is computed in a first pass, so that all
+/// of them are written into a single shared buffer instead of
+/// allocating per row. Outputs no longer than 12 bytes are stored inline.
+///
+/// # Panics
+///
+/// Panics if two columns have different lengths, or any output is longer
+/// than 4GB.
+pub fn concat_columns(a: &[SemiStr], b: &[SemiStr], separator: &str) -> SemiStrVec {
+    assert_eq!(a.len(), b.len(), "columns to concat have different lengths");
+    let total: usize = a.iter().zip(b).map(|(x, y)| {
+        let len = x.len() + separator.len() + y.len();
+        if len > INLINE_CAP { len } else { 0 }
+    }).sum();
+    let mut arena = Arena::with_capacity(total);
+    a.iter().zip(b).map(|(x, y)| arena.push(&[x, separator, y])).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concat_columns() {
+        let a: SemiStrVec = ["a", "hello", "", "long string on the left"].into_iter().collect();
+        let b: SemiStrVec = ["b", "world", "", "right"].into_iter().collect();
+        let res = concat_columns(&a, &b, ", ");
+        assert_eq!(res.iter().map(|s| s.as_str()).collect::<Vec<_>>(), vec!["a, b", "hello, world", ", ", "long string on the left, right"]);
+        let res = concat_columns(&a, &b, "");
+        assert_eq!(res[1], "helloworld");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_concat_columns_mismatch() {
+        let a: SemiStrVec = ["a"].into_iter().collect();
+        concat_columns(&a, &[], "");
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{SemiStr, INLINE_CAP};
+
+/// Distance in rows, at which payloads are prefetched ahead of the
+/// current row. Headers are prefetched at twice the distance, so
+/// that they are in cache when the payload pointers are read.
+const PREFETCH_DISTANCE: usize = 8;
+
+/// Call f on strings at given positions, in order.
+///
+/// Random access into a large column is bound by memory latency, as
+/// both header and heap payload miss the cache. Headers and payloads
+/// of upcoming rows are prefetched while processing the current one.
+///
+/// # Panics
+///
+/// Panics if any index is out of bounds.
+#[inline]
+pub fn gather_strs<F: FnMut(&str)>(strs: &[SemiStr], indices: &[usize], mut f: F) {
+    gather_iter(strs, indices).for_each(|s| f(s))
+}
+
+/// Returns iterator of strings at given positions, which prefetches
+/// upcoming rows, see [`gather_strs`].
+#[inline]
+pub fn gather_iter<'a>(strs: &'a [SemiStr], indices: &'a [usize]) -> GatherIter<'a> {
+    GatherIter{strs, indices, pos: 0}
+}
+
+/// Iterator returned by [`gather_iter`].
+pub struct GatherIter<'a> {
+    strs: &'a [SemiStr],
+    indices: &'a [usize],
+    pos: usize,
+}
+
+impl<'a> Iterator for GatherIter<'a> {
+    type Item = &'a SemiStr;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a SemiStr> {
+        let idx = *self.indices.get(self.pos)?;
+        if let Some(s) = self.indices.get(self.pos + PREFETCH_DISTANCE * 2).and_then(|i| self.strs.get(*i)) {
+            prefetch(s.header_ptr());
+        }
+        if let Some(s) = self.indices.get(self.pos + PREFETCH_DISTANCE).and_then(|i| self.strs.get(*i)) {
+            if s.len() > INLINE_CAP {
+                prefetch(s.payload_ptr());
+            }
+        }
+        self.pos += 1;
+        Some(&self.strs[idx])
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.indices.len() - self.pos;
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for GatherIter<'_> {}
+
+#[inline(always)]
+fn prefetch(ptr: *const u8) {
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    // SAFETY
+    //
+    // prefetch never faults, and sse is always available on x86_64.
+    unsafe {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch::<_MM_HINT_T0>(ptr as *const i8);
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(miri))))]
+    let _ = ptr;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gather() {
+        let strs: Vec<SemiStr> = (0..100).map(|i| SemiStr::new(&format!("value number {:04}", i))).collect();
+        let indices: Vec<usize> = (0..100).map(|i| i * 37 % 100).collect();
+        let mut res = vec![];
+        gather_strs(&strs, &indices, |s| res.push(s.to_string()));
+        assert_eq!(res.len(), 100);
+        assert_eq!(res[1], "value number 0037");
+        let iter = gather_iter(&strs, &indices[..3]);
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.map(|s| s.as_str()).collect::<Vec<_>>(), vec!["value number 0000", "value number 0037", "value number 0074"]);
+    }
+}
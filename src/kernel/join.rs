@@ -0,0 +1,103 @@
+use crate::SemiStr;
+use std::cmp::Ordering;
+
+/// Join two sorted columns, returning index pairs of all equal values.
+///
+/// Both inputs must be sorted in ascending order.
+/// Pairs are returned in order of left index, then right index.
+#[inline]
+pub fn merge_join(left: &[SemiStr], right: &[SemiStr]) -> Vec<(usize, usize)> {
+    MergeJoin::new(left, right).collect()
+}
+
+/// Iterator over matching index pairs of two sorted columns.
+///
+/// Values are compared by their stored prefixes first, so runs of
+/// non-matching values are mostly skipped without touching heap payloads.
+pub struct MergeJoin<'a> {
+    left: &'a [SemiStr],
+    right: &'a [SemiStr],
+    i: usize,
+    j: usize,
+    run: Option<Run>,
+}
+
+/// Cartesian product of two runs of equal values.
+struct Run {
+    l: usize,
+    l_end: usize,
+    r: usize,
+    r_start: usize,
+    r_end: usize,
+}
+
+impl<'a> MergeJoin<'a> {
+    #[inline]
+    pub fn new(left: &'a [SemiStr], right: &'a [SemiStr]) -> Self {
+        MergeJoin{left, right, i: 0, j: 0, run: None}
+    }
+}
+
+impl Iterator for MergeJoin<'_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(run) = self.run.as_mut() {
+                if run.l < run.l_end {
+                    let res = (run.l, run.r);
+                    run.r += 1;
+                    if run.r == run.r_end {
+                        run.r = run.r_start;
+                        run.l += 1;
+                    }
+                    return Some(res)
+                }
+                self.run = None;
+            }
+            if self.i >= self.left.len() || self.j >= self.right.len() {
+                return None
+            }
+            let (l, r) = (&self.left[self.i], &self.right[self.j]);
+            match l.cmp(r) {
+                Ordering::Less => self.i += 1,
+                Ordering::Greater => self.j += 1,
+                Ordering::Equal => {
+                    let l_end = run_end(self.left, self.i);
+                    let r_end = run_end(self.right, self.j);
+                    self.run = Some(Run{l: self.i, l_end, r: self.j, r_start: self.j, r_end});
+                    self.i = l_end;
+                    self.j = r_end;
+                }
+            }
+        }
+    }
+}
+
+/// Returns end index of the run of values equal to `strs[start]`.
+#[inline]
+fn run_end(strs: &[SemiStr], start: usize) -> usize {
+    let v = &strs[start];
+    start + 1 + strs[start+1..].iter().take_while(|s| *s == v).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn col(strs: &[&str]) -> Vec<SemiStr> {
+        strs.iter().map(|s| SemiStr::new(s)).collect()
+    }
+
+    #[test]
+    fn test_merge_join() {
+        let left = col(&["a", "b", "b", "long string value 1", "long string value 2", "z"]);
+        let right = col(&["b", "b", "c", "long string value 2", "long string value 2", "y"]);
+        let res = merge_join(&left, &right);
+        assert_eq!(res, vec![(1, 0), (1, 1), (2, 0), (2, 1), (4, 3), (4, 4)]);
+        assert!(merge_join(&left, &[]).is_empty());
+        let mut iter = MergeJoin::new(&left, &left);
+        assert_eq!(iter.next(), Some((0, 0)));
+        assert_eq!(iter.count(), 5 + 2);
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{SemiStr, INLINE_CAP};
+
+/// Returns byte length of every string in the column.
+/// Only the headers are read.
+#[inline]
+pub fn byte_len(col: &[SemiStr]) -> Vec<u32> {
+    col.iter().map(|s| s.len() as u32).collect()
+}
+
+/// Returns char length of every string in the column.
+///
+/// There is no cached flag of ASCII strings, so ASCII-ness is checked
+/// word by word, on the inline buffer for short strings, and chars
+/// are counted only for non-ASCII strings.
+pub fn char_len(col: &[SemiStr]) -> Vec<u32> {
+    col.iter().map(|s| {
+        if s.len() <= INLINE_CAP {
+            if inline_is_ascii(s) {
+                return s.len() as u32
+            }
+        } else if s.is_ascii() {
+            return s.len() as u32
+        }
+        s.chars().count() as u32
+    }).collect()
+}
+
+/// Check whether the inline string is ASCII with two word loads.
+/// Padding bytes of inline buffer are always zero.
+#[inline]
+fn inline_is_ascii(s: &SemiStr) -> bool {
+    let data = &s.as_inline().data;
+    let lo = u64::from_ne_bytes(data[..8].try_into().unwrap());
+    let hi = u32::from_ne_bytes(data[8..].try_into().unwrap());
+    (lo & 0x8080_8080_8080_8080) == 0 && (hi & 0x8080_8080) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SemiStrVec;
+
+    #[test]
+    fn test_length() {
+        let col: SemiStrVec = ["", "abc", "héllo", "a long ascii string", "a long ünicode string ✓"].into_iter().collect();
+        assert_eq!(byte_len(&col), vec![0, 3, 6, 19, 26]);
+        assert_eq!(char_len(&col), vec![0, 3, 5, 19, 23]);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{Kind, SemiStr};
+
+/// Extract the 4-byte prefixes of all strings into a dense u32 column.
+///
+/// Each prefix is read as big-endian integer, so comparing two extracted
+/// values gives the same ordering as comparing the first four bytes of
+/// the strings (shorter strings are padded with zeros).
+/// Only the headers are read, heap payloads are never touched except
+/// for views, whose prefix is not stored.
+#[inline]
+pub fn extract_prefixes(strs: &[SemiStr]) -> Vec<u32> {
+    let mut res = Vec::with_capacity(strs.len());
+    extract_prefixes_into(strs, &mut res);
+    res
+}
+
+/// Extract the 4-byte prefixes of all strings and append them to `out`.
+pub fn extract_prefixes_into(strs: &[SemiStr], out: &mut Vec<u32>) {
+    out.reserve(strs.len());
+    let mut strs = strs;
+    #[cfg(all(target_arch = "x86_64", not(miri)))]
+    {
+        let n = strs.len() & !3;
+        let start = out.len();
+        // SAFETY
+        //
+        // capacity is reserved above, and sse2 is always available on x86_64.
+        unsafe {
+            sse2::extract_prefixes(&strs[..n], out.as_mut_ptr().add(start));
+            out.set_len(start + n);
+        }
+        // views, static and foreign strings store offset in place of
+        // prefix, patch them afterwards.
+        for (s, p) in strs[..n].iter().zip(&mut out[start..]) {
+            if matches!(s.kind(), Kind::View | Kind::Static | Kind::Foreign) {
+                *p = s.prefix_key();
+            }
+        }
+        strs = &strs[n..];
+    }
+    out.extend(strs.iter().map(SemiStr::prefix_key));
+}
+
+#[cfg(all(target_arch = "x86_64", not(miri)))]
+mod sse2 {
+    use crate::SemiStr;
+    use std::arch::x86_64::*;
+
+    /// Extract prefixes of 4 headers per iteration.
+    ///
+    /// # Safety
+    ///
+    /// length of input must be multiple of 4, and output must have
+    /// room for the same number of u32 values.
+    #[inline]
+    pub(super) unsafe fn extract_prefixes(strs: &[SemiStr], out: *mut u32) {
+        debug_assert!(strs.len().is_multiple_of(4));
+        for (i, chunk) in strs.chunks_exact(4).enumerate() {
+            // only the first 8 bytes (length and prefix) of each header are loaded.
+            let h0 = _mm_loadl_epi64(chunk[0].header_ptr() as *const __m128i);
+            let h1 = _mm_loadl_epi64(chunk[1].header_ptr() as *const __m128i);
+            let h2 = _mm_loadl_epi64(chunk[2].header_ptr() as *const __m128i);
+            let h3 = _mm_loadl_epi64(chunk[3].header_ptr() as *const __m128i);
+            // [len0, len1, p0, p1] and [len2, len3, p2, p3]
+            let lo = _mm_unpacklo_epi32(h0, h1);
+            let hi = _mm_unpacklo_epi32(h2, h3);
+            // [p0, p1, p2, p3]
+            let p = _mm_unpackhi_epi64(lo, hi);
+            // byte swap each 32-bit lane to get big-endian integers.
+            let p = _mm_shufflehi_epi16(_mm_shufflelo_epi16(p, 0b10_11_00_01), 0b10_11_00_01);
+            let p = _mm_or_si128(_mm_slli_epi16(p, 8), _mm_srli_epi16(p, 8));
+            _mm_storeu_si128(out.add(i * 4) as *mut __m128i, p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_prefixes() {
+        let mut strs: Vec<SemiStr> = ["", "a", "abc", "abcd", "abcdefghijklmnopq", "b", "zzzzzzzzzzzzzzzzzz"]
+            .iter()
+            .map(|s| SemiStr::new(s))
+            .collect();
+        strs.push(unsafe { strs[4].slice_shared(1, 15) });
+        let prefixes = extract_prefixes(&strs);
+        let expected: Vec<u32> = strs.iter().map(|s| {
+            let mut p = [0u8; 4];
+            let n = s.len().min(4);
+            p[..n].copy_from_slice(&s.as_bytes()[..n]);
+            u32::from_be_bytes(p)
+        }).collect();
+        assert_eq!(prefixes, expected);
+        assert!(prefixes[1] < prefixes[2] && prefixes[2] < prefixes[3]);
+        assert_eq!(prefixes[3], prefixes[4]);
+        let mut out = vec![42];
+        extract_prefixes_into(&strs[..3], &mut out);
+        assert_eq!(out, vec![42, 0, u32::from_be_bytes(*b"a\0\0\0"), u32::from_be_bytes(*b"abc\0")]);
+    }
+}
@@ -0,0 +1,140 @@
+use super::eval_nullable;
+use crate::{Bitmap, Error, Result, SemiStr, SemiStrVec};
+use regex::Regex;
+use regex_syntax::hir::literal::Extractor;
+use regex_syntax::hir::Look;
+
+/// Compiled pattern with the literal prefix required by all matches,
+/// if the pattern is anchored at start.
+struct Matcher {
+    regex: Regex,
+    prefix: Vec<u8>,
+}
+
+impl Matcher {
+    fn new(pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern).map_err(|e| Error::InvalidRegex(e.to_string()))?;
+        let hir = regex_syntax::parse(pattern).map_err(|e| Error::InvalidRegex(e.to_string()))?;
+        let prefix = if hir.properties().look_set_prefix().contains(Look::Start) {
+            Extractor::new().extract(&hir).longest_common_prefix().map(<[u8]>::to_vec).unwrap_or_default()
+        } else {
+            vec![]
+        };
+        Ok(Matcher{regex, prefix})
+    }
+
+    /// Check the required prefix, with its first 4 bytes compared with
+    /// the stored prefix, before running the regex.
+    #[inline]
+    fn may_match(&self, s: &SemiStr) -> bool {
+        let n = self.prefix.len();
+        if n == 0 {
+            return true
+        }
+        if s.len() < n {
+            return false
+        }
+        let k = n.min(4);
+        s.prefix_bytes()[..k] == self.prefix[..k] && (n <= 4 || s.as_bytes().starts_with(&self.prefix))
+    }
+}
+
+/// Match every string against the regex, with SQL three-valued logic.
+///
+/// The pattern is compiled once. If it is anchored at start, its
+/// required literal prefix is checked first, mostly on the stored
+/// prefix, so that rows are rejected without running the regex.
+///
+/// Returns result bitmap and null bitmap, the same as
+/// [`compare_scalar`](super::compare_scalar).
+pub fn regexp_match(col: &SemiStrVec, pattern: &str) -> Result<(Bitmap, Bitmap)> {
+    let m = Matcher::new(pattern)?;
+    Ok(eval_nullable(col, |s| m.may_match(s) && m.regex.is_match(s)))
+}
+
+/// Match every string against the regex, and extract capture groups.
+///
+/// Returns match bitmap, null bitmap, and one column per capture group,
+/// excluding the implicit whole match group. Captures of null rows,
+/// non-matching rows and groups not participating in the match are
+/// null. Long captures share buffers of input strings without copying.
+pub fn regexp_captures(col: &SemiStrVec, pattern: &str) -> Result<(Bitmap, Bitmap, Vec<SemiStrVec>)> {
+    let m = Matcher::new(pattern)?;
+    let groups = m.regex.captures_len() - 1;
+    let mut matched = Bitmap::new(col.len());
+    let mut nulls = Bitmap::new(col.len());
+    let mut res: Vec<SemiStrVec> = (0..groups).map(|_| SemiStrVec::with_capacity(col.len())).collect();
+    let mut locs = m.regex.capture_locations();
+    for i in 0..col.len() {
+        let Some(s) = col.get_opt(i) else {
+            nulls.set(i, true);
+            res.iter_mut().for_each(SemiStrVec::push_null);
+            continue
+        };
+        let found = m.may_match(s) && m.regex.captures_read(&mut locs, s).is_some();
+        matched.set(i, found);
+        for (g, out) in res.iter_mut().enumerate() {
+            match locs.get(g + 1).filter(|_| found) {
+                // SAFETY
+                //
+                // capture positions are on char boundary.
+                Some((start, end)) => out.push(unsafe { s.slice_shared(start, end) }),
+                None => out.push_null(),
+            }
+        }
+    }
+    Ok((matched, nulls, res))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matcher_prefix() {
+        assert_eq!(Matcher::new("^user_[0-9]+").unwrap().prefix, b"user_");
+        assert_eq!(Matcher::new("^(abcd|abxy)").unwrap().prefix, b"ab");
+        assert!(Matcher::new("user_[0-9]+").unwrap().prefix.is_empty());
+        assert!(Matcher::new("(?i)^user").unwrap().prefix.is_empty());
+        assert!(Matcher::new("(").is_err());
+    }
+
+    #[test]
+    fn test_regexp_match() {
+        let col: SemiStrVec = ["user_42", "user_x", "a user_7", "user_1234567890123", "us"].into_iter().collect();
+        let (res, _) = regexp_match(&col, "^user_[0-9]+$").unwrap();
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![true, false, false, true, false]);
+        let (res, _) = regexp_match(&col, "user_[0-9]").unwrap();
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![true, false, true, true, false]);
+    }
+
+    #[test]
+    fn test_regexp_captures() {
+        let col: SemiStrVec = ["key=value", "a very long key name=x", "no separator"].into_iter().collect();
+        let (matched, _, caps) = regexp_captures(&col, "^([^=]+)=(.*)$").unwrap();
+        assert_eq!(matched.iter().collect::<Vec<_>>(), vec![true, true, false]);
+        assert_eq!(caps.len(), 2);
+        assert_eq!(caps[0].get_opt(0).unwrap(), "key");
+        assert_eq!(caps[0].get_opt(1).unwrap(), "a very long key name");
+        assert_eq!(caps[0].get_opt(1).unwrap().payload_ptr(), col[1].payload_ptr());
+        assert_eq!(caps[1].get_opt(1).unwrap(), "x");
+        assert!(caps[0].is_null(2) && caps[1].is_null(2));
+    }
+
+    #[test]
+    fn test_regexp_nulls() {
+        let mut col = SemiStrVec::new();
+        col.push(SemiStr::new(""));
+        col.push_null();
+        col.push(SemiStr::new("k=v"));
+        let (res, nulls) = regexp_match(&col, "^$").unwrap();
+        assert_eq!(res.iter().collect::<Vec<_>>(), vec![true, false, false]);
+        assert_eq!(nulls.iter().collect::<Vec<_>>(), vec![false, true, false]);
+        let (matched, nulls, caps) = regexp_captures(&col, "(.*)").unwrap();
+        assert_eq!(matched.iter().collect::<Vec<_>>(), vec![true, false, true]);
+        assert_eq!(nulls.iter().collect::<Vec<_>>(), vec![false, true, false]);
+        assert_eq!(caps[0].get_opt(0).unwrap(), "");
+        assert!(caps[0].is_null(1));
+        assert_eq!(caps[0].get_opt(2).unwrap(), "k=v");
+    }
+}
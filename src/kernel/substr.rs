@@ -0,0 +1,42 @@
+use crate::{SemiStr, SemiStrVec};
+
+/// Returns substring of every string in the column, with SQL semantics.
+///
+/// start is 1-based char position, and may be zero or negative, in which
+/// case the window `[start, start + len)` is clipped to the string.
+/// If len is None, substring extends to the end of the string.
+///
+/// Outputs longer than 12 bytes share buffers of the input strings
+/// without copying.
+pub fn substr(col: &[SemiStr], start: i64, len: Option<usize>) -> SemiStrVec {
+    let from = start.saturating_sub(1).max(0) as usize;
+    let to = match len {
+        Some(len) => start.saturating_sub(1).saturating_add(len.min(i64::MAX as usize) as i64).max(0) as usize,
+        None => usize::MAX,
+    };
+    if to <= from {
+        return col.iter().map(|_| SemiStr::default()).collect()
+    }
+    col.iter().map(|s| s.substr(from, to - from)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substr() {
+        let col: SemiStrVec = ["hello", "", "héllo wörld, this is long", "abcdefghijklmnopqrstuvwxyz"].into_iter().collect();
+        let res = substr(&col, 2, Some(3));
+        assert_eq!(res.iter().map(|s| s.as_str()).collect::<Vec<_>>(), vec!["ell", "", "éll", "bcd"]);
+        let res = substr(&col, 0, Some(2));
+        assert_eq!(res.iter().map(|s| s.as_str()).collect::<Vec<_>>(), vec!["h", "", "h", "a"]);
+        let res = substr(&col, 3, None);
+        assert_eq!(res.iter().map(|s| s.as_str()).collect::<Vec<_>>(), vec!["llo", "", "llo wörld, this is long", "cdefghijklmnopqrstuvwxyz"]);
+        assert_eq!(res[3].kind(), crate::Kind::View);
+        let res = substr(&col, -5, Some(3));
+        assert!(res.iter().all(|s| s.is_empty()));
+        let res = substr(&col, 100, None);
+        assert!(res.iter().all(|s| s.is_empty()));
+    }
+}
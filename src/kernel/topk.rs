@@ -0,0 +1,84 @@
+use crate::SemiStr;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Select indices of the top k values of the column.
+///
+/// If `ascending` is true, the k smallest values are selected, otherwise
+/// the k largest. Returned indices are ordered by value in the requested
+/// direction, and equal values are ordered by index.
+///
+/// A bounded heap of size k is maintained, keyed by the 8-byte sort key
+/// of each value. Full string comparison is only performed on key ties.
+pub fn top_k(strs: &[SemiStr], k: usize, ascending: bool) -> Vec<usize> {
+    if k == 0 {
+        return vec![]
+    }
+    let mut heap = BinaryHeap::with_capacity(k.min(strs.len()) + 1);
+    for (idx, s) in strs.iter().enumerate() {
+        let entry = Entry{key: s.sort_key(), idx, s, ascending};
+        if heap.len() < k {
+            heap.push(entry);
+        } else if let Some(mut top) = heap.peek_mut() {
+            // replace the worst value kept so far.
+            if entry < *top {
+                *top = entry;
+            }
+        }
+    }
+    heap.into_sorted_vec().into_iter().map(|e| e.idx).collect()
+}
+
+/// Heap entry ordered by value in requested direction, then by index.
+/// The greatest entry is the worst candidate.
+struct Entry<'a> {
+    key: u64,
+    idx: usize,
+    s: &'a SemiStr,
+    ascending: bool,
+}
+
+impl PartialEq for Entry<'_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry<'_> {}
+
+impl PartialOrd for Entry<'_> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry<'_> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ord = match self.key.cmp(&other.key) {
+            Ordering::Equal => self.s.as_str().cmp(other.s.as_str()),
+            ord => ord,
+        };
+        let ord = if self.ascending { ord } else { ord.reverse() };
+        ord.then(self.idx.cmp(&other.idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_k() {
+        let strs: Vec<SemiStr> = ["pear", "apple", "banana split with cream", "banana split", "apple", "zucchini", "banana split with chocolate"]
+            .iter()
+            .map(|s| SemiStr::new(s))
+            .collect();
+        assert_eq!(top_k(&strs, 3, true), vec![1, 4, 3]);
+        assert_eq!(top_k(&strs, 3, false), vec![5, 0, 2]);
+        assert_eq!(top_k(&strs, 100, true), vec![1, 4, 3, 6, 2, 0, 5]);
+        assert!(top_k(&strs, 0, true).is_empty());
+    }
+}
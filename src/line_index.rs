@@ -0,0 +1,116 @@
+use crate::SemiStr;
+
+/// Position in text, both line and column are 0-based.
+/// Column is counted in chars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// LineIndex maps byte offsets of a text to line and column.
+///
+/// Start offsets of all lines are computed once on construction, so
+/// that each lookup is a binary search instead of scanning the text.
+/// Lines are returned as SemiStrs sharing the buffer of the text.
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    text: SemiStr,
+    // start offset of each line.
+    starts: Vec<u32>,
+}
+
+impl LineIndex {
+    /// Build line index of the text, which shares its buffer.
+    pub fn new(text: &SemiStr) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.bytes().enumerate().filter(|(_, b)| *b == b'\n').map(|(i, _)| i as u32 + 1))
+            .collect();
+        LineIndex{text: text.share(), starts}
+    }
+
+    /// Returns the indexed text.
+    #[inline]
+    pub fn text(&self) -> &SemiStr {
+        &self.text
+    }
+
+    /// Returns number of lines. Text ending with newline has an empty
+    /// last line.
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Returns line and column of given byte offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if offset is past the end, or not on char boundary.
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        assert!(self.text.is_char_boundary(offset), "invalid offset {}", offset);
+        let line = self.starts.partition_point(|s| *s as usize <= offset) - 1;
+        let start = self.starts[line] as usize;
+        LineCol{line, col: self.text[start..offset].chars().count()}
+    }
+
+    /// Returns byte offset of given line and column, or None if the
+    /// position is out of the text. Column may point to the end of line.
+    pub fn offset(&self, pos: LineCol) -> Option<usize> {
+        let (start, end) = self.line_range(pos.line)?;
+        let line = &self.text[start..end];
+        line.char_indices().map(|(i, _)| i).chain(std::iter::once(line.len()))
+            .nth(pos.col)
+            .map(|i| start + i)
+    }
+
+    /// Returns the line without line terminator, or None if out of range.
+    #[inline]
+    pub fn line(&self, line: usize) -> Option<SemiStr> {
+        let (start, end) = self.line_range(line)?;
+        // SAFETY
+        //
+        // line boundaries are next to ASCII newlines.
+        Some(unsafe { self.text.slice_shared(start, end) })
+    }
+
+    /// Returns byte range of the line, excluding `\n` or `\r\n`.
+    #[inline]
+    fn line_range(&self, line: usize) -> Option<(usize, usize)> {
+        let start = *self.starts.get(line)? as usize;
+        let mut end = match self.starts.get(line + 1) {
+            Some(next) => *next as usize - 1,
+            None => self.text.len(),
+        };
+        if end > start && self.text.as_bytes()[end - 1] == b'\r' {
+            end -= 1;
+        }
+        Some((start, end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_index() {
+        let text = SemiStr::new("SELECT a,\r\n  b FROM t\nWHERE x = 'héllo' AND y > 1\n");
+        let idx = LineIndex::new(&text);
+        assert_eq!(idx.line_count(), 4);
+        assert_eq!(idx.line_col(0), LineCol{line: 0, col: 0});
+        assert_eq!(idx.line_col(13), LineCol{line: 1, col: 2});
+        let off = text.find("AND").unwrap();
+        assert_eq!(idx.line_col(off), LineCol{line: 2, col: 18});
+        assert_eq!(idx.offset(LineCol{line: 2, col: 18}), Some(off));
+        assert_eq!(idx.offset(LineCol{line: 1, col: 100}), None);
+        assert_eq!(idx.line_col(text.len()), LineCol{line: 3, col: 0});
+        assert_eq!(idx.line(0).unwrap(), "SELECT a,");
+        assert_eq!(idx.line(1).unwrap(), "  b FROM t");
+        let line = idx.line(2).unwrap();
+        assert_eq!(line, "WHERE x = 'héllo' AND y > 1");
+        assert_eq!(line.payload_ptr(), text[22..].as_ptr());
+        assert_eq!(idx.line(3).unwrap(), "");
+        assert!(idx.line(4).is_none());
+    }
+}
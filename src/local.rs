@@ -0,0 +1,247 @@
+use crate::{block_header, release_block_local, retain_block_local, Result, SemiStr};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+use std::sync::atomic::Ordering as AtomicOrdering;
+
+/// SemiStrLocal is a single-threaded sibling of SemiStr, with the same
+/// layout, whose clone and drop update reference count of heap block
+/// without atomic read-modify-write.
+///
+/// It is neither `Send` nor `Sync`, and never shares heap block with
+/// SemiStr, so the reference count is only touched on one thread.
+/// Conversions between the two types transfer heap block if it is
+/// uniquely owned, and copy it otherwise.
+pub struct SemiStrLocal {
+    inner: ManuallyDrop<SemiStr>,
+    // not Send nor Sync, as reference count is not synchronized.
+    _marker: PhantomData<*const ()>,
+}
+
+impl SemiStrLocal {
+    /// # Panics
+    ///
+    /// Panics if the string is longer than 4GB.
+    #[inline]
+    pub fn new(s: &str) -> Self {
+        Self::from_unique(SemiStr::new(s))
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        self.inner.as_str()
+    }
+
+    /// Returns number of strings sharing the heap block, or None if
+    /// the string is inline or static.
+    #[inline]
+    pub fn ref_count(&self) -> Option<usize> {
+        self.inner.ref_count()
+    }
+
+    /// Convert into SemiStr, the heap block is transferred if not
+    /// shared, and copied otherwise.
+    #[inline]
+    pub fn into_shared(self) -> SemiStr {
+        if self.ref_count().is_some_and(|rc| rc > 1) {
+            return SemiStr::new(self.as_str())
+        }
+        let mut this = ManuallyDrop::new(self);
+        // SAFETY
+        //
+        // self is not dropped, and the block is uniquely owned.
+        unsafe { ManuallyDrop::take(&mut this.inner) }
+    }
+
+    /// Wrap a string whose heap block is not shared.
+    #[inline]
+    fn from_unique(s: SemiStr) -> Self {
+        SemiStrLocal{inner: ManuallyDrop::new(s), _marker: PhantomData}
+    }
+}
+
+/// Heap block is transferred if not shared, and copied otherwise.
+impl From<SemiStr> for SemiStrLocal {
+    #[inline]
+    fn from(value: SemiStr) -> Self {
+        let unique = match value.block_ref() {
+            // SAFETY
+            //
+            // owner is payload pointer of heap block kept alive by value,
+            // acquire pairs with release of other owners on drop.
+            Some((owner, _)) => unsafe { block_header(owner).rc.load(AtomicOrdering::Acquire) == 1 },
+            None => true,
+        };
+        if unique {
+            Self::from_unique(value)
+        } else {
+            Self::new(value.as_str())
+        }
+    }
+}
+
+impl From<SemiStrLocal> for SemiStr {
+    #[inline]
+    fn from(value: SemiStrLocal) -> Self {
+        value.into_shared()
+    }
+}
+
+impl<'s> TryFrom<&'s str> for SemiStrLocal {
+    type Error = crate::Error;
+    #[inline]
+    fn try_from(value: &'s str) -> Result<Self> {
+        SemiStr::try_from(value).map(Self::from_unique)
+    }
+}
+
+impl Clone for SemiStrLocal {
+    #[inline]
+    fn clone(&self) -> Self {
+        if let Some((owner, _)) = self.inner.block_ref() {
+            // SAFETY
+            //
+            // the block is only referenced by SemiStrLocal on current thread.
+            unsafe { retain_block_local(owner) }
+        }
+        // SAFETY
+        //
+        // reference count is increased above if the block is shared.
+        Self::from_unique(unsafe { std::ptr::read(&*self.inner) })
+    }
+}
+
+impl Drop for SemiStrLocal {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some((owner, _)) = self.inner.block_ref() {
+            // SAFETY
+            //
+            // the block is only referenced by SemiStrLocal on current thread.
+            unsafe { release_block_local(owner) }
+        }
+    }
+}
+
+impl Default for SemiStrLocal {
+    #[inline]
+    fn default() -> Self {
+        Self::from_unique(SemiStr::default())
+    }
+}
+
+impl Deref for SemiStrLocal {
+    type Target = str;
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SemiStrLocal {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for SemiStrLocal {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for SemiStrLocal {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        *self.inner == *other.inner
+    }
+}
+
+impl Eq for SemiStrLocal {}
+
+impl PartialEq<str> for SemiStrLocal {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        *self.inner == *other
+    }
+}
+
+impl PartialEq<&str> for SemiStrLocal {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        *self.inner == **other
+    }
+}
+
+impl PartialOrd for SemiStrLocal {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SemiStrLocal {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.inner.cmp(&other.inner)
+    }
+}
+
+impl Hash for SemiStrLocal {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl fmt::Debug for SemiStrLocal {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for SemiStrLocal {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semistr_local() {
+        let s = SemiStrLocal::new("an identifier longer than 12");
+        let s2 = s.clone();
+        assert_eq!(s.ref_count(), Some(2));
+        assert_eq!(s2.as_ptr(), s.as_ptr());
+        assert_eq!(s, s2);
+        assert_eq!(s, "an identifier longer than 12");
+        // shared block is copied.
+        let shared = s2.into_shared();
+        assert_ne!(shared.payload_ptr(), s.as_ptr());
+        assert_eq!(s.ref_count(), Some(1));
+        // unique block is transferred.
+        let ptr = s.as_ptr();
+        let shared = s.into_shared();
+        assert_eq!(shared.payload_ptr(), ptr);
+        let local = SemiStrLocal::from(shared);
+        assert_eq!(local.as_ptr(), ptr);
+        let shared = SemiStr::new("a shared string longer than 12");
+        let local = SemiStrLocal::from(shared.clone());
+        assert_ne!(local.as_ptr(), shared.payload_ptr());
+        assert_eq!(shared.ref_count(), Some(1));
+        assert_eq!(SemiStrLocal::new("short").ref_count(), None);
+        assert_eq!(SemiStrLocal::default(), "");
+        assert_eq!(format!("{:?}", SemiStrLocal::new("a")), "\"a\"");
+    }
+}
@@ -0,0 +1,48 @@
+//! Macros constructing SemiStr from literals.
+
+/// Create a SemiStr from a string literal.
+///
+/// Literal no longer than 12 bytes is built into the inline format at
+/// compile time, so it takes no runtime work. Longer literal refers to
+/// static memory, see [`SemiStr::from_static`](crate::SemiStr::from_static).
+///
+/// ```
+/// use semistr::{semistr, SemiStr};
+///
+/// fn column(idx: usize) -> SemiStr {
+///     match idx {
+///         0 => semistr!("id"),
+///         _ => semistr!("a column name longer than 12"),
+///     }
+/// }
+/// assert_eq!(column(0), "id");
+/// ```
+#[macro_export]
+macro_rules! semistr {
+    ($s:literal) => {{
+        const S: &str = $s;
+        const { ::std::assert!(S.len() < u32::MAX as usize, "string literal longer than 4GB") };
+        match const { $crate::SemiStr::__literal_inline(S) } {
+            ::std::option::Option::Some(s) => s,
+            ::std::option::Option::None => $crate::SemiStr::from_static(S),
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SemiStr;
+
+    #[test]
+    fn test_semistr_macro() {
+        let s = semistr!("key");
+        assert_eq!(s, "key");
+        assert_eq!(s.kind(), crate::Kind::Inline);
+        assert_eq!(semistr!(""), SemiStr::default());
+        let s = semistr!("twelve bytes");
+        assert_eq!(s.kind(), crate::Kind::Inline);
+        let s = semistr!("a literal longer than inline capacity");
+        assert_eq!(s, "a literal longer than inline capacity");
+        assert_eq!(s.kind(), crate::Kind::Static);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{Error, Result, SemiBytes, SemiStr};
+use std::borrow::Borrow;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::path::Path;
+
+/// SemiOsStr is an immutable OS string with the same 16-byte layout
+/// as SemiStr.
+///
+/// It stores the platform encoding of [`OsStr`], i.e. arbitrary bytes
+/// on Unix and WTF-8 on Windows, so it round-trips OsStr losslessly.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SemiOsStr(
+    // bytes returned by OsStr::as_encoded_bytes(), or a substring of
+    // them split by OsStr methods, or valid utf-8.
+    SemiBytes,
+);
+
+impl SemiOsStr {
+    /// # Panics
+    ///
+    /// Panics if the string is longer than 4GB.
+    #[inline]
+    pub fn new<S: AsRef<OsStr> + ?Sized>(s: &S) -> Self {
+        Self::try_from(s.as_ref()).unwrap()
+    }
+
+    #[inline]
+    pub fn as_os_str(&self) -> &OsStr {
+        // SAFETY
+        //
+        // bytes are encoded by OsStr, or are valid utf-8.
+        unsafe { OsStr::from_encoded_bytes_unchecked(self.0.as_bytes()) }
+    }
+
+    /// Returns the platform encoding of the string, same as
+    /// [`OsStr::as_encoded_bytes`].
+    #[inline]
+    pub fn as_encoded_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    /// Returns length of the encoded bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the str if the string is valid utf-8.
+    #[inline]
+    pub fn to_str(&self) -> Option<&str> {
+        self.as_os_str().to_str()
+    }
+
+    /// Returns a string sharing the buffer of self, given a substring
+    /// borrowed from self.
+    ///
+    /// # Panics
+    ///
+    /// Panics if sub is not borrowed from self.
+    #[inline]
+    pub(crate) fn shared_sub(&self, sub: &OsStr) -> SemiOsStr {
+        let base = self.as_encoded_bytes().as_ptr().addr();
+        let start = sub.as_encoded_bytes().as_ptr().addr().wrapping_sub(base);
+        let end = start.wrapping_add(sub.len());
+        assert!(start <= end && end <= self.len(), "substring is not borrowed from self");
+        SemiOsStr(self.0.slice(start..end))
+    }
+}
+
+impl<'s> TryFrom<&'s OsStr> for SemiOsStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: &'s OsStr) -> Result<Self> {
+        SemiBytes::try_from(value.as_encoded_bytes()).map(SemiOsStr)
+    }
+}
+
+impl TryFrom<OsString> for SemiOsStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: OsString) -> Result<Self> {
+        Self::try_from(value.as_os_str())
+    }
+}
+
+impl From<SemiOsStr> for OsString {
+    #[inline]
+    fn from(value: SemiOsStr) -> Self {
+        value.as_os_str().to_os_string()
+    }
+}
+
+/// The payload is not copied.
+impl From<SemiStr> for SemiOsStr {
+    #[inline]
+    fn from(value: SemiStr) -> Self {
+        SemiOsStr(SemiBytes::from(value))
+    }
+}
+
+/// The string is validated, and the payload is not copied.
+impl TryFrom<SemiOsStr> for SemiStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: SemiOsStr) -> Result<Self> {
+        SemiStr::try_from(value.0)
+    }
+}
+
+impl Deref for SemiOsStr {
+    type Target = OsStr;
+    #[inline]
+    fn deref(&self) -> &OsStr {
+        self.as_os_str()
+    }
+}
+
+impl AsRef<OsStr> for SemiOsStr {
+    #[inline]
+    fn as_ref(&self) -> &OsStr {
+        self.as_os_str()
+    }
+}
+
+impl AsRef<Path> for SemiOsStr {
+    #[inline]
+    fn as_ref(&self) -> &Path {
+        Path::new(self.as_os_str())
+    }
+}
+
+impl Borrow<OsStr> for SemiOsStr {
+    #[inline]
+    fn borrow(&self) -> &OsStr {
+        self.as_os_str()
+    }
+}
+
+impl PartialEq<OsStr> for SemiOsStr {
+    #[inline]
+    fn eq(&self, other: &OsStr) -> bool {
+        self.as_os_str() == other
+    }
+}
+
+impl PartialEq<str> for SemiOsStr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_os_str() == other
+    }
+}
+
+impl PartialEq<&str> for SemiOsStr {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_os_str() == *other
+    }
+}
+
+impl Hash for SemiOsStr {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_os_str().hash(state)
+    }
+}
+
+impl fmt::Debug for SemiOsStr {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_os_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semi_os_str() {
+        let s = SemiOsStr::new("a file name longer than 12");
+        assert_eq!(s, "a file name longer than 12");
+        assert_eq!(s.to_str(), Some("a file name longer than 12"));
+        let os: OsString = s.clone().into();
+        assert_eq!(os, "a file name longer than 12");
+        let name = SemiOsStr::new("name");
+        assert_eq!(SemiStr::try_from(name).unwrap(), "name");
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let raw = OsStr::from_bytes(b"non-utf8 \xff file name");
+            let s = SemiOsStr::new(raw);
+            assert_eq!(s.as_os_str(), raw);
+            assert_eq!(s.to_str(), None);
+            assert!(SemiStr::try_from(s.clone()).is_err());
+            let path: &Path = s.as_ref();
+            assert_eq!(path.as_os_str(), raw);
+        }
+    }
+}
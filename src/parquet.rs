@@ -0,0 +1,158 @@
+//! Encoders writing SemiStrVec as Parquet BYTE_ARRAY page data.
+//!
+//! Values are read directly from the headers, so columns need not be
+//! converted to owned strings before export. Encoded bytes are the
+//! page bodies following Parquet format spec, and page headers and
+//! compression are left to the file writer.
+
+use crate::SemiStrVec;
+use std::collections::HashMap;
+
+/// Write non-null values in PLAIN encoding, each value is prefixed by
+/// its 4-byte little-endian length.
+pub fn write_plain(col: &SemiStrVec, buf: &mut Vec<u8>) {
+    buf.reserve(col.iter().map(|s| 4 + s.len()).sum());
+    for i in 0..col.len() {
+        if let Some(s) = col.get_opt(i) {
+            buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+            buf.extend_from_slice(s.as_bytes());
+        }
+    }
+}
+
+/// Write definition levels of a nullable column with max definition
+/// level 1, in RLE/bit-packed hybrid encoding with 4-byte length
+/// prefix, as required by data page v1.
+pub fn write_def_levels(col: &SemiStrVec, buf: &mut Vec<u8>) {
+    let levels: Vec<u32> = (0..col.len()).map(|i| !col.is_null(i) as u32).collect();
+    let start = buf.len();
+    buf.extend_from_slice(&[0u8; 4]);
+    write_rle_hybrid(&levels, 1, buf);
+    let len = (buf.len() - start - 4) as u32;
+    buf[start..start + 4].copy_from_slice(&len.to_le_bytes());
+}
+
+/// Dictionary encoded column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictEncoded {
+    /// Body of dictionary page, distinct values in PLAIN encoding.
+    pub dict: Vec<u8>,
+    /// Number of distinct values.
+    pub num_dict_values: usize,
+    /// Indices of non-null values, in RLE_DICTIONARY encoding with
+    /// leading bit width byte.
+    pub indices: Vec<u8>,
+}
+
+/// Encode non-null values with dictionary encoding.
+/// Distinct values are ordered by first occurrence.
+pub fn write_dictionary(col: &SemiStrVec) -> DictEncoded {
+    let mut map: HashMap<&str, u32> = HashMap::new();
+    let mut dict = Vec::new();
+    let mut keys = Vec::with_capacity(col.len());
+    for i in 0..col.len() {
+        if let Some(s) = col.get_opt(i) {
+            let next = map.len() as u32;
+            let key = *map.entry(s.as_str()).or_insert_with(|| {
+                dict.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                dict.extend_from_slice(s.as_bytes());
+                next
+            });
+            keys.push(key);
+        }
+    }
+    let num_dict_values = map.len();
+    let bit_width = (u32::BITS - (num_dict_values.saturating_sub(1) as u32).leading_zeros()) as u8;
+    let mut indices = vec![bit_width];
+    write_rle_hybrid(&keys, bit_width, &mut indices);
+    DictEncoded{dict, num_dict_values, indices}
+}
+
+/// Write values in RLE/bit-packed hybrid encoding without length prefix.
+///
+/// Runs of at least 8 equal values are RLE encoded, others are
+/// bit-packed in groups of 8.
+fn write_rle_hybrid(values: &[u32], bit_width: u8, buf: &mut Vec<u8>) {
+    let run_len = |start: usize| values[start..].iter().take_while(|v| **v == values[start]).count();
+    let mut i = 0;
+    while i < values.len() {
+        let run = run_len(i);
+        if run >= 8 {
+            write_varint((run as u64) << 1, buf);
+            let bytes = (bit_width as usize).div_ceil(8);
+            buf.extend_from_slice(&values[i].to_le_bytes()[..bytes]);
+            i += run;
+            continue
+        }
+        // collect groups until next long run.
+        let mut end = i;
+        while end < values.len() && (end == i || run_len(end) < 8) {
+            end = (end + 8).min(values.len());
+        }
+        let groups = (end - i).div_ceil(8);
+        write_varint(((groups as u64) << 1) | 1, buf);
+        bit_pack(&values[i..end], groups * 8, bit_width, buf);
+        i = end;
+    }
+}
+
+/// Pack values LSB first, padded with zeros to given count.
+fn bit_pack(values: &[u32], count: usize, bit_width: u8, buf: &mut Vec<u8>) {
+    let mut acc = 0u64;
+    let mut bits = 0;
+    for i in 0..count {
+        let v = values.get(i).copied().unwrap_or(0) as u64;
+        acc |= v << bits;
+        bits += bit_width as u32;
+        while bits >= 8 {
+            buf.push(acc as u8);
+            acc >>= 8;
+            bits -= 8;
+        }
+    }
+    debug_assert_eq!(bits, 0);
+}
+
+#[inline]
+fn write_varint(mut v: u64, buf: &mut Vec<u8>) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SemiStr;
+
+    #[test]
+    fn test_plain_and_def_levels() {
+        let mut col = SemiStrVec::new();
+        col.push(SemiStr::new("ab"));
+        col.push_null();
+        col.push(SemiStr::new("a string longer than 12"));
+        let mut buf = vec![];
+        write_plain(&col, &mut buf);
+        assert_eq!(&buf[..6], &[2, 0, 0, 0, b'a', b'b']);
+        assert_eq!(&buf[6..10], &[23, 0, 0, 0]);
+        assert_eq!(&buf[10..], b"a string longer than 12");
+        let mut buf = vec![];
+        write_def_levels(&col, &mut buf);
+        // one bit-packed group: 0b101.
+        assert_eq!(buf, vec![2, 0, 0, 0, 0b11, 0b101]);
+    }
+
+    #[test]
+    fn test_dictionary() {
+        let mut strs = vec!["x"; 10];
+        strs.extend(["y", "z", "y"]);
+        let col: SemiStrVec = strs.into_iter().collect();
+        let res = write_dictionary(&col);
+        assert_eq!(res.num_dict_values, 3);
+        assert_eq!(res.dict, vec![1, 0, 0, 0, b'x', 1, 0, 0, 0, b'y', 1, 0, 0, 0, b'z']);
+        // bit width 2, rle run of 10 zeros, then bit-packed [1, 2, 1] padded to 8.
+        assert_eq!(res.indices, vec![2, 20, 0, 0b11, 0b011001, 0]);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{Error, Result, SemiOsStr, SemiStr};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::ffi::OsStr;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// SemiPath is an immutable path with the same 16-byte layout as
+/// SemiStr.
+///
+/// Clone is cheap, and [`SemiPath::parent`] and [`SemiPath::file_name`]
+/// share the buffer of self instead of copying.
+/// Comparison and hashing follow [`Path`], i.e. component-wise.
+#[derive(Clone, Default)]
+pub struct SemiPath(SemiOsStr);
+
+impl SemiPath {
+    /// # Panics
+    ///
+    /// Panics if the path is longer than 4GB.
+    #[inline]
+    pub fn new<P: AsRef<Path> + ?Sized>(p: &P) -> Self {
+        Self::try_from(p.as_ref()).unwrap()
+    }
+
+    #[inline]
+    pub fn as_path(&self) -> &Path {
+        Path::new(self.0.as_os_str())
+    }
+
+    #[inline]
+    pub fn as_os_str(&self) -> &OsStr {
+        self.0.as_os_str()
+    }
+
+    #[inline]
+    pub fn into_os_str(self) -> SemiOsStr {
+        self.0
+    }
+
+    /// Returns the path without its final component, sharing the
+    /// buffer of self. See [`Path::parent`].
+    #[inline]
+    pub fn parent(&self) -> Option<SemiPath> {
+        self.as_path().parent().map(|p| SemiPath(self.0.shared_sub(p.as_os_str())))
+    }
+
+    /// Returns the final component of the path, sharing the buffer of
+    /// self. See [`Path::file_name`].
+    #[inline]
+    pub fn file_name(&self) -> Option<SemiOsStr> {
+        self.as_path().file_name().map(|n| self.0.shared_sub(n))
+    }
+
+    /// Create a new path by joining path to self. See [`Path::join`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result is longer than 4GB.
+    #[inline]
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> SemiPath {
+        SemiPath::new(&self.as_path().join(path))
+    }
+}
+
+impl<'p> TryFrom<&'p Path> for SemiPath {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: &'p Path) -> Result<Self> {
+        SemiOsStr::try_from(value.as_os_str()).map(SemiPath)
+    }
+}
+
+impl TryFrom<PathBuf> for SemiPath {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: PathBuf) -> Result<Self> {
+        Self::try_from(value.as_path())
+    }
+}
+
+impl From<SemiPath> for PathBuf {
+    #[inline]
+    fn from(value: SemiPath) -> Self {
+        value.as_path().to_path_buf()
+    }
+}
+
+/// The payload is not copied.
+impl From<SemiOsStr> for SemiPath {
+    #[inline]
+    fn from(value: SemiOsStr) -> Self {
+        SemiPath(value)
+    }
+}
+
+/// The payload is not copied.
+impl From<SemiStr> for SemiPath {
+    #[inline]
+    fn from(value: SemiStr) -> Self {
+        SemiPath(SemiOsStr::from(value))
+    }
+}
+
+impl Deref for SemiPath {
+    type Target = Path;
+    #[inline]
+    fn deref(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl AsRef<Path> for SemiPath {
+    #[inline]
+    fn as_ref(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl AsRef<OsStr> for SemiPath {
+    #[inline]
+    fn as_ref(&self) -> &OsStr {
+        self.as_os_str()
+    }
+}
+
+impl Borrow<Path> for SemiPath {
+    #[inline]
+    fn borrow(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl PartialEq for SemiPath {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_path() == other.as_path()
+    }
+}
+
+impl Eq for SemiPath {}
+
+impl PartialEq<Path> for SemiPath {
+    #[inline]
+    fn eq(&self, other: &Path) -> bool {
+        self.as_path() == other
+    }
+}
+
+impl PartialEq<&Path> for SemiPath {
+    #[inline]
+    fn eq(&self, other: &&Path) -> bool {
+        self.as_path() == *other
+    }
+}
+
+impl PartialOrd for SemiPath {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SemiPath {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_path().cmp(other.as_path())
+    }
+}
+
+impl Hash for SemiPath {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_path().hash(state)
+    }
+}
+
+impl fmt::Debug for SemiPath {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_path(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_semi_path() {
+        let p = SemiPath::new("/var/lib/semistr/data.parquet");
+        let name = p.file_name().unwrap();
+        assert_eq!(name, "data.parquet");
+        let parent = p.parent().unwrap();
+        assert_eq!(parent, Path::new("/var/lib/semistr"));
+        assert_eq!(parent.as_os_str().as_encoded_bytes().as_ptr(), p.as_os_str().as_encoded_bytes().as_ptr());
+        assert_eq!(parent.parent().unwrap().file_name().unwrap(), "lib");
+        assert_eq!(SemiPath::new("/").parent(), None);
+        assert_eq!(SemiPath::new("a").parent().unwrap(), Path::new(""));
+        assert_eq!(SemiPath::new("a/..").file_name(), None);
+        assert_eq!(parent.join("index.fst"), Path::new("/var/lib/semistr/index.fst"));
+        // compared component-wise, same as Path.
+        assert_eq!(SemiPath::new("a//b/"), SemiPath::new("a/b"));
+        let set: HashSet<SemiPath> = [SemiPath::new("a/b")].into_iter().collect();
+        assert!(set.contains(Path::new("a//b")));
+        let buf: PathBuf = p.into();
+        assert_eq!(buf, Path::new("/var/lib/semistr/data.parquet"));
+    }
+}
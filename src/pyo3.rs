@@ -0,0 +1,62 @@
+//! Conversions between SemiStr and Python str.
+
+use crate::SemiStr;
+use ::pyo3::exceptions::PyValueError;
+use ::pyo3::prelude::*;
+use ::pyo3::types::PyString;
+use std::convert::Infallible;
+
+/// Short Python strings are copied into inline format, without any
+/// allocation.
+impl FromPyObject<'_> for SemiStr {
+    #[inline]
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let s = ob.downcast::<PyString>()?.to_cow()?;
+        SemiStr::try_from(&*s).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+impl<'py> IntoPyObject<'py> for SemiStr {
+    type Target = PyString;
+    type Output = Bound<'py, PyString>;
+    type Error = Infallible;
+
+    #[inline]
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(PyString::new(py, self.as_str()))
+    }
+}
+
+impl<'py> IntoPyObject<'py> for &SemiStr {
+    type Target = PyString;
+    type Output = Bound<'py, PyString>;
+    type Error = Infallible;
+
+    #[inline]
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(PyString::new(py, self.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::pyo3::types::PyInt;
+
+    #[test]
+    fn test_pyo3() {
+        ::pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let s: SemiStr = PyString::new(py, "short").extract().unwrap();
+            assert_eq!(s, "short");
+            assert!(s.is_inline());
+            let s: SemiStr = PyString::new(py, "a longer ünicode string").extract().unwrap();
+            assert_eq!(s, "a longer ünicode string");
+            let obj = s.clone().into_pyobject(py).unwrap();
+            assert_eq!(obj.to_str().unwrap(), "a longer ünicode string");
+            let obj = (&s).into_pyobject(py).unwrap();
+            assert_eq!(obj.len().unwrap(), 23);
+            assert!(PyInt::new(py, 1).extract::<SemiStr>().is_err());
+        });
+    }
+}
@@ -0,0 +1,319 @@
+use crate::{Bitmap, SemiStr, SemiStrVec};
+
+/// RleVec is a run-length encoded column of SemiStr values.
+///
+/// Consecutive equal values are stored once, together with the end
+/// position of the run. Sorted or low-cardinality columns are much
+/// smaller in this form, and kernels evaluate each run only once.
+/// Consecutive nulls form a null run, which holds an empty string and
+/// is tracked by a validity bitmap of runs, like [`SemiStrVec`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RleVec {
+    values: Vec<SemiStr>,
+    // exclusive end position of each run, strictly increasing.
+    run_ends: Vec<usize>,
+    // bit is set for non-null run, None if there is no null.
+    validity: Option<Bitmap>,
+}
+
+impl RleVec {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encode a flat column.
+    pub fn encode(strs: &[SemiStr]) -> Self {
+        let mut res = RleVec::new();
+        for s in strs {
+            res.push(s);
+        }
+        res
+    }
+
+    /// Append a value, extending the last run if equal.
+    #[inline]
+    pub fn push(&mut self, value: &SemiStr) {
+        self.push_run(value, 1)
+    }
+
+    /// Append a run of `count` equal values.
+    #[inline]
+    pub fn push_run(&mut self, value: &SemiStr, count: usize) {
+        self.push_run_opt(Some(value), count)
+    }
+
+    /// Append a null value, extending the last run if null.
+    #[inline]
+    pub fn push_null(&mut self) {
+        self.push_run_opt(None, 1)
+    }
+
+    /// Append a run of `count` nulls.
+    #[inline]
+    pub fn push_null_run(&mut self, count: usize) {
+        self.push_run_opt(None, count)
+    }
+
+    fn push_run_opt(&mut self, value: Option<&SemiStr>, count: usize) {
+        if count == 0 {
+            return
+        }
+        let end = self.len() + count;
+        if let Some(last) = self.values.len().checked_sub(1) {
+            let extend = match value {
+                Some(v) => !self.is_null_run(last) && &self.values[last] == v,
+                None => self.is_null_run(last),
+            };
+            if extend {
+                self.run_ends[last] = end;
+                return
+            }
+        }
+        match value {
+            Some(v) => {
+                if let Some(validity) = self.validity.as_mut() {
+                    validity.push(true);
+                }
+                self.values.push(v.clone());
+            }
+            None => {
+                let validity = self.validity.get_or_insert_with(|| Bitmap::new_set(self.values.len()));
+                validity.push(false);
+                self.values.push(SemiStr::default());
+            }
+        }
+        self.run_ends.push(end);
+    }
+
+    /// Returns number of logical values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.run_ends.last().copied().unwrap_or(0)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.run_ends.is_empty()
+    }
+
+    /// Returns number of runs.
+    #[inline]
+    pub fn num_runs(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns value at given logical position.
+    /// Null value is returned as empty string, see [`RleVec::get_opt`].
+    #[inline]
+    pub fn get(&self, idx: usize) -> Option<&SemiStr> {
+        if idx >= self.len() {
+            return None
+        }
+        Some(&self.values[self.run_of(idx)])
+    }
+
+    /// Returns value at given logical position, or None if it is null.
+    ///
+    /// # Panics
+    ///
+    /// Panics if idx is out of bounds.
+    #[inline]
+    pub fn get_opt(&self, idx: usize) -> Option<&SemiStr> {
+        if self.is_null(idx) { None } else { Some(&self.values[self.run_of(idx)]) }
+    }
+
+    /// Returns true if value at given logical position is null.
+    ///
+    /// # Panics
+    ///
+    /// Panics if idx is out of bounds.
+    #[inline]
+    pub fn is_null(&self, idx: usize) -> bool {
+        assert!(idx < self.len());
+        self.is_null_run(self.run_of(idx))
+    }
+
+    /// Returns true if given run is a null run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if run is out of bounds.
+    #[inline]
+    pub fn is_null_run(&self, run: usize) -> bool {
+        assert!(run < self.num_runs());
+        self.validity.as_ref().is_some_and(|v| !v.get(run))
+    }
+
+    /// Returns number of null values.
+    #[inline]
+    pub fn null_count(&self) -> usize {
+        if self.validity.is_none() {
+            return 0
+        }
+        self.runs().enumerate().filter(|(run, _)| self.is_null_run(*run)).map(|(_, (_, n))| n).sum()
+    }
+
+    #[inline]
+    fn run_of(&self, idx: usize) -> usize {
+        self.run_ends.partition_point(|end| *end <= idx)
+    }
+
+    /// Iterate over runs as pairs of value and run length.
+    /// Null runs hold empty string, see [`RleVec::is_null_run`].
+    #[inline]
+    pub fn runs(&self) -> Runs<'_> {
+        Runs{rle: self, run: 0}
+    }
+
+    /// Iterate over all logical values, nulls are empty strings.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &SemiStr> + '_ {
+        self.runs().flat_map(|(v, n)| std::iter::repeat_n(v, n))
+    }
+
+    /// Decode into a flat column.
+    pub fn decode(&self) -> SemiStrVec {
+        let mut res = SemiStrVec::with_capacity(self.len());
+        for (run, (v, n)) in self.runs().enumerate() {
+            if self.is_null_run(run) {
+                (0..n).for_each(|_| res.push_null());
+            } else {
+                res.extend(std::iter::repeat_n(v, n).cloned());
+            }
+        }
+        res
+    }
+
+    /// Compare all values with given string, with SQL three-valued
+    /// logic, each run is compared only once.
+    ///
+    /// Returns result bitmap and null bitmap, the same as
+    /// [`compare_scalar`](crate::kernel::compare_scalar).
+    pub fn eq_scalar(&self, value: &str) -> (Bitmap, Bitmap) {
+        let mut res = Bitmap::new(self.len());
+        let mut nulls = Bitmap::new(self.len());
+        let mut start = 0;
+        for (run, (v, n)) in self.runs().enumerate() {
+            if self.is_null_run(run) {
+                (start..start+n).for_each(|i| nulls.set(i, true));
+            } else if v == value {
+                (start..start+n).for_each(|i| res.set(i, true));
+            }
+            start += n;
+        }
+        (res, nulls)
+    }
+
+    /// Select values whose corresponding bit is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if length of bitmap does not match length of self.
+    pub fn filter(&self, bitmap: &Bitmap) -> RleVec {
+        assert_eq!(bitmap.len(), self.len());
+        let mut res = RleVec::new();
+        let mut start = 0;
+        for (run, (v, n)) in self.runs().enumerate() {
+            let count = (start..start+n).filter(|i| bitmap.get(*i)).count();
+            res.push_run_opt(Some(v).filter(|_| !self.is_null_run(run)), count);
+            start += n;
+        }
+        res
+    }
+}
+
+/// Null values are encoded as null runs.
+impl From<&SemiStrVec> for RleVec {
+    #[inline]
+    fn from(strs: &SemiStrVec) -> Self {
+        if strs.validity().is_none() {
+            return RleVec::encode(strs)
+        }
+        let mut res = RleVec::new();
+        for i in 0..strs.len() {
+            res.push_run_opt(strs.get_opt(i), 1);
+        }
+        res
+    }
+}
+
+impl From<&RleVec> for SemiStrVec {
+    #[inline]
+    fn from(rle: &RleVec) -> Self {
+        rle.decode()
+    }
+}
+
+/// Iterator over runs of RleVec.
+pub struct Runs<'a> {
+    rle: &'a RleVec,
+    run: usize,
+}
+
+impl<'a> Iterator for Runs<'a> {
+    type Item = (&'a SemiStr, usize);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.rle.values.get(self.run)?;
+        let start = if self.run == 0 { 0 } else { self.rle.run_ends[self.run - 1] };
+        let end = self.rle.run_ends[self.run];
+        self.run += 1;
+        Some((value, end - start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rle_vec() {
+        let flat: SemiStrVec = ["a", "a", "a", "long string value", "long string value", "b", "a"].into_iter().collect();
+        let rle = RleVec::from(&flat);
+        assert_eq!(rle.len(), 7);
+        assert_eq!(rle.num_runs(), 4);
+        assert_eq!(rle.get(4).unwrap(), "long string value");
+        assert_eq!(rle.get(6).unwrap(), "a");
+        assert!(rle.get(7).is_none());
+        assert!(rle.iter().eq(flat.iter()));
+        assert_eq!(SemiStrVec::from(&rle), flat);
+        let (eq, nulls) = rle.eq_scalar("a");
+        assert_eq!(eq, Bitmap::from(&[true, true, true, false, false, false, true][..]));
+        assert!(!nulls.any());
+        let filtered = rle.filter(&Bitmap::from(&[true, false, true, false, false, true, true][..]));
+        assert_eq!(filtered.runs().map(|(v, n)| (v.as_str(), n)).collect::<Vec<_>>(), vec![("a", 2), ("b", 1), ("a", 1)]);
+        let filtered = rle.filter(&Bitmap::from(&[false, false, false, true, true, false, false][..]));
+        assert_eq!(filtered.num_runs(), 1);
+        assert!(RleVec::encode(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_rle_vec_nulls() {
+        let mut flat = SemiStrVec::new();
+        flat.push(SemiStr::new("a"));
+        flat.push_null();
+        flat.push_null();
+        flat.push(SemiStr::new("a"));
+        flat.push(SemiStr::new(""));
+        let rle = RleVec::from(&flat);
+        assert_eq!(rle.num_runs(), 4);
+        assert_eq!(rle.null_count(), 2);
+        assert!(rle.is_null(1) && rle.is_null_run(1));
+        assert!(!rle.is_null(4));
+        assert_eq!(rle.get_opt(2), None);
+        assert_eq!(rle.get_opt(4).unwrap(), "");
+        let decoded = rle.decode();
+        assert_eq!(decoded.null_count(), 2);
+        assert!(decoded.is_null(1) && decoded.is_null(2) && !decoded.is_null(4));
+        let (eq, nulls) = rle.eq_scalar("");
+        assert_eq!(eq, Bitmap::from(&[false, false, false, false, true][..]));
+        assert_eq!(nulls, Bitmap::from(&[false, true, true, false, false][..]));
+        let filtered = rle.filter(&Bitmap::from(&[true, false, true, true, false][..]));
+        assert_eq!(filtered.num_runs(), 3);
+        assert!(filtered.is_null(1));
+        let filtered = rle.filter(&Bitmap::from(&[true, false, false, true, true][..]));
+        assert_eq!(filtered, RleVec::encode(&[SemiStr::new("a"), SemiStr::new("a"), SemiStr::new("")]));
+    }
+}
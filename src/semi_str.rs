@@ -0,0 +1,3311 @@
+#[cfg(feature = "alloc-check")]
+use crate::alloc_check;
+use crate::{validate_utf8, Error, FromBytesError, Result};
+use std::mem::{size_of, align_of, ManuallyDrop};
+use std::num::NonZeroU32;
+use std::alloc::{alloc, dealloc, realloc, handle_alloc_error, Layout};
+use std::ops::{Add, Bound, Deref, RangeBounds};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::cmp::Ordering;
+use std::any::TypeId;
+use std::sync::Arc;
+use std::sync::atomic::{fence, AtomicUsize, Ordering as AtomicOrdering};
+use std::hint::select_unpredictable;
+use std::iter;
+use std::borrow::{Borrow, Cow};
+use std::str::FromStr;
+use std::ffi::OsStr;
+use std::path::Path;
+
+pub(crate) const INLINE_CAP: usize = 12;
+
+/// Maximum length of SemiStr, u32::MAX is reserved as niche.
+pub(crate) const MAX_LEN: usize = u32::MAX as usize - 1;
+
+/// SemiStrN is an immutable string with length no more than 4GB,
+/// storing strings no longer than INLINE bytes inline.
+///
+/// Longer strings use the same heap, view and static formats in the
+/// first 12 bytes after the length whatever INLINE is, so converting
+/// between capacities never copies heap payload, see
+/// [`SemiStrN::into_inline_cap`]. Struct size is `4 + INLINE` rounded
+/// up to multiple of 8, e.g. `SemiStrN<20>` takes 24 bytes and
+/// `SemiStrN<28>` takes 32 bytes. INLINE must be at least 12.
+///
+/// Most methods are only provided by [`SemiStr`], the 12-byte instance.
+/// `Option<SemiStrN>` has the same size as SemiStrN.
+///
+/// ```
+/// let s = semistr::SemiStrN::<20>::new("customer_address_id");
+/// assert!(s.is_inline());
+/// assert_eq!(std::mem::size_of_val(&s), 24);
+/// ```
+#[repr(C, align(8))]
+pub struct SemiStrN<const INLINE: usize> {
+    // length stored bitwise inverted, so u32::MAX, which is not a
+    // valid length, is stored as zero and serves as niche.
+    len: NonZeroU32,
+    body: Body<INLINE>,
+}
+
+/// SemiStr is an immutable string with length no more than 4GB,
+/// storing strings no longer than 12 bytes inline in 16 bytes.
+pub type SemiStr = SemiStrN<INLINE_CAP>;
+
+const _: () = assert!(size_of::<SemiStr>() == 16 && size_of::<Option<SemiStr>>() == 16);
+
+/// Parent block of a view is at least this many times larger than
+/// the view, the view is copied out on compaction.
+const COMPACT_RATIO: usize = 2;
+
+/// Internal representation of SemiStr following the length.
+///
+/// The length together with the tag bits of heap pointer determines
+/// the active variant, see [`SemiStr::kind`].
+/// Variants are packed to 4-byte alignment, so the body is placed
+/// right after the length, and the pointer is still aligned as
+/// SemiStr is aligned to 8 bytes.
+/// Inline buffer larger than 12 bytes is zero padded after heap and
+/// view formats.
+#[repr(C)]
+#[derive(Clone, Copy)]
+union Body<const INLINE: usize> {
+    inline: [u8; INLINE],
+    heap: HeapBody,
+    view: ViewBody,
+}
+
+#[repr(C, packed(4))]
+#[derive(Clone, Copy)]
+struct HeapBody {
+    prefix: [u8; 4],
+    ptr: *const u8,
+}
+
+#[repr(C, packed(4))]
+#[derive(Clone, Copy)]
+struct ViewBody {
+    offset: u32,
+    ptr: *const u8,
+}
+
+/// Kind of the active variant of the representation.
+/// Discriminant is the tag of raw parts, see [`SemiStr::into_raw_parts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Inline,
+    Heap,
+    View,
+    // view format over static memory, never reference counted.
+    Static,
+    // view format over a foreign block, which adopts an allocation
+    // owned by another type.
+    Foreign,
+}
+
+impl<const INLINE: usize> SemiStrN<INLINE> {
+    /// Inline capacity in bytes.
+    pub const INLINE_CAP: usize = INLINE;
+
+    const CHECK_CAP: () = assert!(INLINE >= INLINE_CAP, "inline capacity of SemiStrN must be at least 12");
+
+    #[inline]
+    pub fn new(s: &str) -> Self {
+        Self::try_from(s).unwrap()
+    }
+
+    /// Create a string, same as [`SemiStrN::new`], but returns error
+    /// instead of aborting if heap allocation fails, or if the string
+    /// is longer than 4GB.
+    #[inline]
+    pub fn try_new(s: &str) -> Result<Self> {
+        if s.len() <= INLINE {
+            return Ok(Self::from_inline_str(s))
+        }
+        if s.len() > MAX_LEN {
+            return Err(Error::StringTooLong(s.len()))
+        }
+        // SAFETY
+        //
+        // block has capacity of the string, which is valid utf-8.
+        unsafe {
+            let ptr = try_alloc_block(s.len()).ok_or(Error::AllocFailed(s.len()))?;
+            std::ptr::copy_nonoverlapping(s.as_ptr(), ptr, s.len());
+            Ok(adopt_block(ptr, s.len()).into_inline_cap())
+        }
+    }
+
+    /// Convert to another inline capacity.
+    ///
+    /// Strings no longer than M bytes are copied inline, other long
+    /// strings keep sharing their heap payload, and inline strings
+    /// longer than M bytes are copied into a new allocation.
+    ///
+    /// ```
+    /// use semistr::{SemiStr, SemiStrN};
+    ///
+    /// let s = SemiStr::new("a string longer than twenty bytes");
+    /// let n: SemiStrN<20> = s.clone().into_inline_cap();
+    /// assert_eq!(n.as_str().as_ptr(), s.as_str().as_ptr());
+    /// ```
+    #[inline]
+    pub fn into_inline_cap<const M: usize>(self) -> SemiStrN<M> {
+        if self.len() <= M || self.is_inline() {
+            return SemiStrN::new(self.as_str())
+        }
+        SemiStrN::from_long(self)
+    }
+
+    /// Create an inline string, whose length must be no more than
+    /// INLINE.
+    #[inline]
+    fn from_inline_str(s: &str) -> Self {
+        let () = Self::CHECK_CAP;
+        debug_assert!(s.len() <= INLINE);
+        let mut data = [0u8; INLINE];
+        data[..s.len()].copy_from_slice(s.as_bytes());
+        SemiStrN{len: encode_len(s.len() as u32), body: Body{inline: data}}
+    }
+
+    /// Move the header of a long string, which is not inline in
+    /// either capacity, into a zero padded body.
+    #[inline]
+    fn from_long<const M: usize>(s: SemiStrN<M>) -> Self {
+        let () = Self::CHECK_CAP;
+        debug_assert!(s.len() > INLINE && s.len() > M);
+        let s = ManuallyDrop::new(s);
+        let mut body = Body{inline: [0u8; INLINE]};
+        // SAFETY
+        //
+        // All variants are fully initialized.
+        body.view = unsafe { s.body.view };
+        SemiStrN{len: s.len, body}
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        !self.len.get() as usize
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        let len = self.len();
+        // heap pointer of inline string is never dereferenced.
+        let ptr = select_unpredictable(len <= INLINE, self.inline_data().as_ptr(), self.heap_data_ptr());
+        unsafe { std::slice::from_raw_parts(ptr, len) }
+    }
+    /// Returns the string slice.
+    ///
+    /// The data pointer is selected from inline buffer and heap pointer
+    /// without branching, which avoids misprediction on columns
+    /// mixing short and long strings.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        unsafe { std::str::from_utf8_unchecked(self.as_bytes()) }
+    }
+    /// Returns whether the string is stored inline, which holds for
+    /// all strings no longer than INLINE bytes.
+    #[inline]
+    pub fn is_inline(&self) -> bool {
+        self.kind() == Kind::Inline
+    }
+    /// Returns the 4-byte prefix, padded with zeros if shorter.
+    #[inline]
+    pub(crate) fn prefix_bytes(&self) -> [u8; 4] {
+        match self.kind() {
+            Kind::Inline | Kind::Heap => self.as_heap().prefix,
+            Kind::View | Kind::Static | Kind::Foreign => {
+                let mut prefix = [0u8; 4];
+                prefix.copy_from_slice(&self.as_bytes()[..4]);
+                prefix
+            }
+        }
+    }
+    /// Returns the 4-byte prefix as big-endian integer.
+    /// Inline strings shorter than 4 bytes are padded with zeros.
+    #[inline]
+    pub(crate) fn prefix_key(&self) -> u32 {
+        u32::from_be_bytes(self.prefix_bytes())
+    }
+    /// Compare stored prefixes first, and payload bytes only if they
+    /// are equal. Ordering of bytes is the same as ordering of strings.
+    #[inline]
+    pub(crate) fn cmp_bytes(&self, other: &Self) -> Ordering {
+        match self.prefix_key().cmp(&other.prefix_key()) {
+            Ordering::Equal => self.as_bytes().cmp(other.as_bytes()),
+            ord => ord,
+        }
+    }
+    /// Returns kind of the active variant.
+    /// Strings no longer than INLINE bytes are always inline.
+    #[inline]
+    pub(crate) fn kind(&self) -> Kind {
+        if self.len() <= INLINE {
+            Kind::Inline
+        } else {
+            match self.as_heap().ptr.addr() & TAG_MASK {
+                0 => Kind::Heap,
+                VIEW_TAG => Kind::View,
+                FOREIGN_TAG => Kind::Foreign,
+                _ => Kind::Static,
+            }
+        }
+    }
+    /// Returns pointer to heap data, the offset of view or static string
+    /// is applied without branching.
+    /// Payload of foreign block is read from its metadata, which takes
+    /// a rarely taken branch.
+    /// Result is meaningful only if kind is not inline.
+    #[inline]
+    fn heap_data_ptr(&self) -> *const u8 {
+        let view = self.as_view();
+        // non-short-circuit and, so inline strings whose bytes look like
+        // the foreign tag do not introduce another branch.
+        if (view.ptr.addr() & TAG_MASK == FOREIGN_TAG) & (self.len() > INLINE) {
+            // SAFETY
+            //
+            // foreign block is kept alive by self.
+            return unsafe { foreign_meta(untag_ptr(view.ptr)).data }.wrapping_add(view.offset as usize)
+        }
+        let tag = view.ptr.addr() & VIEW_TAG;
+        let offset = view.offset as usize & tag.wrapping_neg();
+        untag_ptr(view.ptr).wrapping_add(offset)
+    }
+    /// Returns the inline buffer.
+    /// Data bytes are meaningful only if kind is inline.
+    #[inline]
+    fn inline_data(&self) -> &[u8; INLINE] {
+        // SAFETY
+        //
+        // All variants are fully initialized.
+        unsafe { &self.body.inline }
+    }
+    /// Read self as heap format.
+    /// Length and prefix are always meaningful, pointer is meaningful
+    /// only if kind is heap.
+    #[inline]
+    fn as_heap(&self) -> Heap {
+        // SAFETY
+        //
+        // All variants are fully initialized.
+        let body = unsafe { self.body.heap };
+        Heap{len: self.len() as u32, prefix: body.prefix, ptr: body.ptr}
+    }
+    /// Read self as view format.
+    /// Offset and pointer are meaningful only if kind is view.
+    #[inline]
+    fn as_view(&self) -> View {
+        // SAFETY
+        //
+        // All variants are fully initialized.
+        let body = unsafe { self.body.view };
+        View{len: self.len() as u32, offset: body.offset, ptr: body.ptr}
+    }
+    /// Returns pointer of the shared block and offset of the string in
+    /// its payload, see [`block_payload`], or None if the string is
+    /// inline or static.
+    #[inline]
+    pub(crate) fn block_ref(&self) -> Option<(*const u8, usize)> {
+        match self.kind() {
+            Kind::Inline | Kind::Static => None,
+            Kind::Heap => Some((self.as_heap().ptr, 0)),
+            Kind::View | Kind::Foreign => {
+                let view = self.as_view();
+                Some((untag_ptr(view.ptr), view.offset as usize))
+            }
+        }
+    }
+}
+
+impl SemiStr {
+    /// Create a string referring to static memory without copying.
+    ///
+    /// Long static strings are neither allocated nor reference counted,
+    /// and slices of them keep referring to the static memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is longer than 4GB.
+    #[inline]
+    pub fn from_static(s: &'static str) -> Self {
+        // SAFETY
+        //
+        // valid utf-8 string.
+        unsafe { Self::from_static_bytes(s.as_bytes()) }
+    }
+
+    /// Create a string referring to static bytes, see [`SemiStr::from_static`].
+    ///
+    /// # Safety
+    ///
+    /// bytes must be valid utf-8, unless the result is wrapped in
+    /// [`SemiBytes`] or [`SemiCStr`].
+    #[inline]
+    pub(crate) unsafe fn from_static_bytes(s: &'static [u8]) -> Self {
+        if s.len() <= INLINE_CAP {
+            return inline_str(s)
+        }
+        assert!(s.len() <= MAX_LEN, "string too long: {}", s.len());
+        // static memory is not aligned, the low bits are kept in offset.
+        let offset = s.as_ptr().addr() & TAG_MASK;
+        let ptr = s.as_ptr().wrapping_sub(offset).map_addr(|a| a | TAG_MASK);
+        SemiStr::from_view(View{len: s.len() as u32, offset: offset as u32, ptr})
+    }
+
+    /// Concatenate all pieces of the iterator.
+    ///
+    /// Pieces are collected first so the total length is known, and
+    /// the result is built in a single allocation, or inline.
+    /// Returns error if total length is greater than 4GB.
+    #[inline]
+    pub fn try_from_iter<I>(iter: I) -> Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let pieces: Vec<I::Item> = iter.into_iter().collect();
+        Self::join_pieces("", &pieces)
+    }
+
+    /// Concatenate strings in a single allocation, or inline.
+    /// Returns error if total length is greater than 4GB.
+    #[inline]
+    pub fn concat<S: AsRef<str>>(strs: &[S]) -> Result<Self> {
+        Self::join_pieces("", strs)
+    }
+
+    /// Join strings with separator in a single allocation, or inline.
+    /// Returns error if total length is greater than 4GB.
+    #[inline]
+    pub fn join<I>(sep: &str, iter: I) -> Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let pieces: Vec<I::Item> = iter.into_iter().collect();
+        Self::join_pieces(sep, &pieces)
+    }
+
+    /// Build inline format in const context, returns None if the
+    /// string is longer than 12 bytes. Used by [`semistr!`].
+    #[doc(hidden)]
+    #[inline]
+    pub const fn __literal_inline(s: &str) -> Option<Self> {
+        if s.len() > INLINE_CAP {
+            return None
+        }
+        Some(Self::const_inline(s))
+    }
+
+    /// Create an inline string in const context, so it can be used to
+    /// initialize constants and statics.
+    ///
+    /// ```
+    /// use semistr::SemiStr;
+    ///
+    /// static FIELD: SemiStr = SemiStr::const_inline("id");
+    /// assert_eq!(FIELD, "id");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is longer than 12 bytes, which fails
+    /// compilation in const context.
+    #[inline]
+    pub const fn const_inline(s: &str) -> Self {
+        let bytes = s.as_bytes();
+        assert!(bytes.len() <= INLINE_CAP, "string too long for inline format");
+        let mut data = [0u8; INLINE_CAP];
+        let mut i = 0;
+        while i < bytes.len() {
+            data[i] = bytes[i];
+            i += 1;
+        }
+        SemiStr{len: encode_len(bytes.len() as u32), body: Body{inline: data}}
+    }
+
+    #[inline]
+    pub fn inline(s: &str) -> Self {
+        assert!(s.len() <= INLINE_CAP);
+        unsafe { inline_str(s.as_bytes()) }
+    }
+
+    /// Copy the string out of its heap buffer if the buffer is much
+    /// larger than the string.
+    ///
+    /// Views are produced by zero-copy slicing and keep the whole
+    /// parent buffer alive, and strings frozen from [`SemiString`]
+    /// keep its spare capacity. Long-lived values should be compacted
+    /// to release memory not referenced by themselves.
+    /// Slices no longer than 12 bytes are always stored inline, so
+    /// they never retain any buffer.
+    #[inline]
+    pub fn compact(&mut self) {
+        let cap = match self.kind() {
+            Kind::Inline | Kind::Static => return,
+            Kind::Heap => unsafe { block_cap(self.as_heap().ptr) },
+            Kind::View => unsafe { block_cap(untag_ptr(self.as_view().ptr)) },
+            Kind::Foreign => unsafe { block_size(untag_ptr(self.as_view().ptr)) },
+        };
+        if cap >= self.len() * COMPACT_RATIO {
+            *self = unsafe { heap_str(self.as_bytes()) };
+        }
+    }
+
+    /// Compact all strings, see [`SemiStr::compact`].
+    #[inline]
+    pub fn compact_all(strs: &mut [SemiStr]) {
+        strs.iter_mut().for_each(SemiStr::compact)
+    }
+
+    /// Returns a string sharing the buffer of self.
+    /// Result no longer than 12 bytes is stored inline.
+    ///
+    /// # Safety
+    ///
+    /// start and end must be on char boundary, and start <= end <= len.
+    #[inline]
+    pub(crate) unsafe fn slice_shared(&self, start: usize, end: usize) -> SemiStr {
+        debug_assert!(start <= end && end <= self.len());
+        let bytes = &self.as_bytes()[start..end];
+        if bytes.len() <= INLINE_CAP {
+            return inline_str(bytes)
+        }
+        if self.kind() == Kind::Static {
+            let view = self.as_view();
+            return SemiStr::from_view(View{len: bytes.len() as u32, offset: view.offset + start as u32, ptr: view.ptr})
+        }
+        let (owner, offset) = self.block_ref().unwrap();
+        let offset = offset + start;
+        retain_block(owner);
+        if self.kind() == Kind::Foreign {
+            return SemiStr::from_view(View{len: bytes.len() as u32, offset: offset as u32, ptr: owner.map_addr(|a| a | FOREIGN_TAG)})
+        }
+        if offset == 0 {
+            let mut prefix = [0u8; 4];
+            prefix.copy_from_slice(&bytes[..4]);
+            return SemiStr::from_heap(Heap{len: bytes.len() as u32, prefix, ptr: owner})
+        }
+        SemiStr::from_view(View{len: bytes.len() as u32, offset: offset as u32, ptr: owner.map_addr(|a| a | VIEW_TAG)})
+    }
+
+    /// Divide the string into two at given byte index.
+    ///
+    /// Both halves share the heap buffer of self without copying,
+    /// and halves no longer than 12 bytes are stored inline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if idx is not on char boundary, or is past the end.
+    #[inline]
+    pub fn split_at(&self, idx: usize) -> (SemiStr, SemiStr) {
+        assert!(self.is_char_boundary(idx), "failed to split string at {}", idx);
+        // SAFETY
+        //
+        // idx is checked to be on char boundary.
+        unsafe { (self.slice_shared(0, idx), self.slice_shared(idx, self.len())) }
+    }
+
+    /// Returns the substring in given byte range.
+    ///
+    /// The result shares the heap buffer of self without copying,
+    /// unless it is no longer than 12 bytes and stored inline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if range is out of bounds or not on char boundary.
+    #[inline]
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> SemiStr {
+        let (start, end) = range_bounds(range, self.len());
+        assert!(start <= end && self.is_char_boundary(start) && self.is_char_boundary(end),
+            "failed to slice string at {}..{}", start, end);
+        // SAFETY
+        //
+        // both ends are checked to be on char boundary.
+        unsafe { self.slice_shared(start, end) }
+    }
+
+    /// Returns the substring of at most len chars, starting at 0-based
+    /// char position start. The window is clipped to the string.
+    ///
+    /// Like [`SemiStr::slice`], the result shares the heap buffer of self.
+    #[inline]
+    pub fn substr(&self, start: usize, len: usize) -> SemiStr {
+        let (b_start, b_end) = if self.is_ascii() {
+            (start.min(self.len()), start.saturating_add(len).min(self.len()))
+        } else {
+            let mut idx = self.char_indices().map(|(i, _)| i).chain(iter::once(self.len()));
+            let b_start = idx.nth(start).unwrap_or(self.len());
+            let b_end = match len {
+                0 => b_start,
+                _ => idx.nth(len - 1).unwrap_or(self.len()),
+            };
+            (b_start, b_end)
+        };
+        // SAFETY
+        //
+        // byte range is computed from char indices, so on char boundary.
+        unsafe { self.slice_shared(b_start, b_end) }
+    }
+
+    /// Convert into String.
+    ///
+    /// If the string adopted the buffer of a String, Vec<u8> or Box<str>,
+    /// is not sliced from its start and has no other reference, the
+    /// buffer is handed back without copying. Otherwise payload is
+    /// copied. Native heap block keeps its header in front of the
+    /// payload, so it is always copied even if uniquely owned.
+    #[inline]
+    pub fn into_string(self) -> String {
+        let len = self.len();
+        let mut s = match self.into_foreign_owner::<String>() {
+            Ok(s) => s,
+            Err(this) => match this.into_foreign_owner::<Vec<u8>>() {
+                // SAFETY
+                //
+                // bytes are validated when adopted.
+                Ok(v) => unsafe { String::from_utf8_unchecked(v) },
+                Err(this) => match this.into_foreign_owner::<Box<str>>() {
+                    Ok(b) => b.into_string(),
+                    Err(this) => return this.as_str().to_owned(),
+                },
+            },
+        };
+        // length of self is on char boundary of the owner.
+        s.truncate(len);
+        s
+    }
+
+    /// Returns mutable string slice if the string is not shared,
+    /// i.e. it is inline, or its heap block has no other reference.
+    /// Returns None if the string is shared, static or adopted from a
+    /// foreign allocation.
+    ///
+    /// Long string in heap format is turned into view format at offset
+    /// 0, as its stored prefix may be invalidated by the mutation.
+    #[inline]
+    pub fn get_mut(&mut self) -> Option<&mut str> {
+        match self.kind() {
+            Kind::Inline => (),
+            Kind::Static | Kind::Foreign => return None,
+            kind @ (Kind::Heap | Kind::View) => {
+                let (owner, _) = self.block_ref().unwrap();
+                // SAFETY
+                //
+                // owner is payload pointer of heap block kept alive by self.
+                if unsafe { block_header(owner).rc.load(AtomicOrdering::Acquire) } != 1 {
+                    return None
+                }
+                if kind == Kind::Heap {
+                    self.body = Body{view: ViewBody{offset: 0, ptr: owner.map_addr(|a| a | VIEW_TAG)}};
+                }
+            }
+        }
+        let len = self.len();
+        let ptr = if len <= INLINE_CAP {
+            // SAFETY
+            //
+            // inline data is owned by self.
+            unsafe { self.body.inline.as_mut_ptr() }
+        } else {
+            self.heap_data_ptr() as *mut u8
+        };
+        // SAFETY
+        //
+        // bytes are valid utf-8 string, uniquely referenced by self.
+        Some(unsafe { std::str::from_utf8_unchecked_mut(std::slice::from_raw_parts_mut(ptr, len)) })
+    }
+
+    /// Returns mutable string slice, copying the string into a new
+    /// heap block first if it is shared or static, like `Arc::make_mut`.
+    #[inline]
+    pub fn make_mut(&mut self) -> &mut str {
+        if self.get_mut().is_none() {
+            *self = unsafe { heap_str(self.as_bytes()) };
+        }
+        self.get_mut().unwrap()
+    }
+
+    /// Decompose the string into its raw representation:
+    /// tag, length, 4-byte prefix and pointer.
+    ///
+    /// Tag is 0 for inline, 1 for heap, 2 for view, 3 for static and 4
+    /// for foreign string. For inline string, prefix and pointer hold
+    /// the inline bytes. For view, static and foreign string, prefix
+    /// holds the offset.
+    ///
+    /// The reference to heap block is transferred into the parts, and
+    /// must be given back by [`SemiStr::from_raw_parts`], otherwise
+    /// the block is leaked.
+    #[inline]
+    pub fn into_raw_parts(self) -> (u8, u32, [u8; 4], *const u8) {
+        let this = ManuallyDrop::new(self);
+        let heap = this.as_heap();
+        (this.kind() as u8, heap.len, heap.prefix, heap.ptr)
+    }
+
+    /// Reconstitute a string from raw parts returned by
+    /// [`SemiStr::into_raw_parts`].
+    ///
+    /// # Safety
+    ///
+    /// The parts must be returned by into_raw_parts(), and parts of
+    /// heap or view string must be converted back at most once.
+    #[inline]
+    pub unsafe fn from_raw_parts(tag: u8, len: u32, prefix: [u8; 4], ptr: *const u8) -> SemiStr {
+        let res = SemiStr::from_heap(Heap{len, prefix, ptr});
+        debug_assert_eq!(tag, res.kind() as u8, "tag does not match raw parts");
+        res
+    }
+
+    /// Leak the string, returns a static reference to it.
+    ///
+    /// Reference of heap block is never released, so the block
+    /// lives forever. Inline string is copied into a leaked box, and
+    /// static string is returned as is.
+    #[inline]
+    pub fn leak(self) -> &'static str {
+        match self.kind() {
+            Kind::Inline => Box::leak(Box::from(self.as_str())),
+            Kind::Heap | Kind::View | Kind::Static | Kind::Foreign => {
+                let this = ManuallyDrop::new(self);
+                // SAFETY
+                //
+                // the buffer is kept alive forever by the leaked reference,
+                // or is static memory.
+                unsafe { std::str::from_utf8_unchecked(std::slice::from_raw_parts(this.heap_data_ptr(), this.len())) }
+            }
+        }
+    }
+
+    /// Returns the string repeated n times, built in a single
+    /// allocation, or inline.
+    /// Returns error if the result is longer than 4GB.
+    #[inline]
+    pub fn repeat(&self, n: usize) -> Result<SemiStr> {
+        let len = self.len().saturating_mul(n);
+        if len > MAX_LEN {
+            return Err(Error::StringTooLong(len))
+        }
+        if n == 1 {
+            return Ok(self.clone())
+        }
+        // SAFETY
+        //
+        // buffer is filled with copies of self.
+        Ok(unsafe {
+            build_str(len, |buf| {
+                if len == 0 {
+                    return
+                }
+                buf[..self.len()].copy_from_slice(self.as_bytes());
+                // double the filled part until buffer is full.
+                let mut filled = self.len();
+                while filled < len {
+                    let n = filled.min(len - filled);
+                    buf.copy_within(..n, filled);
+                    filled += n;
+                }
+            })
+        })
+    }
+
+    /// Replaces all matches of from with to, same as [`str::replace`].
+    ///
+    /// The result is built in a single allocation, or stored inline if
+    /// short, or shares the buffer of self if nothing matches.
+    /// Returns error if the result is longer than 4GB.
+    #[inline]
+    pub fn replace_shared(&self, from: &str, to: &str) -> Result<SemiStr> {
+        self.replacen_shared(from, to, usize::MAX)
+    }
+
+    /// Replaces first count matches of from with to, same as
+    /// [`str::replacen`].
+    ///
+    /// The result is built in a single allocation, or stored inline if
+    /// short, or shares the buffer of self if nothing matches.
+    /// Returns error if the result is longer than 4GB.
+    #[inline]
+    pub fn replacen_shared(&self, from: &str, to: &str, count: usize) -> Result<SemiStr> {
+        let matches = || self.as_str().match_indices(from).take(count);
+        let n = matches().count();
+        if n == 0 {
+            return Ok(self.share())
+        }
+        let len = (self.len() - n * from.len()).saturating_add(n.saturating_mul(to.len()));
+        if len > MAX_LEN {
+            return Err(Error::StringTooLong(len))
+        }
+        // SAFETY
+        //
+        // buffer is filled with unmatched parts of self and replacements,
+        // matches are on char boundaries.
+        Ok(unsafe {
+            build_str(len, |buf| {
+                let src = self.as_bytes();
+                let mut pos = 0;
+                let mut last = 0;
+                for (start, _) in matches() {
+                    let part = &src[last..start];
+                    buf[pos..pos + part.len()].copy_from_slice(part);
+                    pos += part.len();
+                    buf[pos..pos + to.len()].copy_from_slice(to.as_bytes());
+                    pos += to.len();
+                    last = start + from.len();
+                }
+                buf[pos..].copy_from_slice(&src[last..]);
+            })
+        })
+    }
+
+    /// Returns the longest prefix of at most max_bytes bytes which
+    /// ends on a char boundary, sharing the buffer of self.
+    #[inline]
+    pub fn truncated(&self, max_bytes: usize) -> SemiStr {
+        self.shared_sub(&self.as_str()[..floor_char_boundary(self.as_str(), max_bytes)])
+    }
+
+    /// Returns the string truncated to at most max_bytes bytes
+    /// including the suffix, e.g. "…", which is appended only if
+    /// the string is truncated.
+    /// If the suffix itself is longer than max_bytes, the string is
+    /// truncated without suffix.
+    ///
+    /// The result is built in a single allocation, or stored inline
+    /// if short, or shares the buffer of self if not truncated.
+    #[inline]
+    pub fn truncated_with(&self, max_bytes: usize, suffix: &str) -> SemiStr {
+        if self.len() <= max_bytes || suffix.len() > max_bytes {
+            return self.truncated(max_bytes)
+        }
+        let keep = floor_char_boundary(self.as_str(), max_bytes - suffix.len());
+        // SAFETY
+        //
+        // buffer is filled with prefix of self on char boundary and suffix.
+        unsafe {
+            build_str(keep + suffix.len(), |buf| {
+                buf[..keep].copy_from_slice(&self.as_bytes()[..keep]);
+                buf[keep..].copy_from_slice(suffix.as_bytes());
+            })
+        }
+    }
+
+    /// Returns the string with leading and trailing whitespaces removed,
+    /// sharing the buffer of self.
+    #[inline]
+    pub fn trim(&self) -> SemiStr {
+        self.shared_sub(self.as_str().trim())
+    }
+
+    /// Returns the string with leading whitespaces removed,
+    /// sharing the buffer of self.
+    #[inline]
+    pub fn trim_start(&self) -> SemiStr {
+        self.shared_sub(self.as_str().trim_start())
+    }
+
+    /// Returns the string with trailing whitespaces removed,
+    /// sharing the buffer of self.
+    #[inline]
+    pub fn trim_end(&self) -> SemiStr {
+        self.shared_sub(self.as_str().trim_end())
+    }
+
+    /// Returns the string with leading and trailing chars matching the
+    /// predicate removed, sharing the buffer of self.
+    #[inline]
+    pub fn trim_matches<F: FnMut(char) -> bool>(&self, f: F) -> SemiStr {
+        self.shared_sub(self.as_str().trim_matches(f))
+    }
+
+    /// Returns the string with leading chars matching the predicate
+    /// removed, sharing the buffer of self.
+    #[inline]
+    pub fn trim_start_matches<F: FnMut(char) -> bool>(&self, f: F) -> SemiStr {
+        self.shared_sub(self.as_str().trim_start_matches(f))
+    }
+
+    /// Returns the string with trailing chars matching the predicate
+    /// removed, sharing the buffer of self.
+    #[inline]
+    pub fn trim_end_matches<F: FnMut(char) -> bool>(&self, f: F) -> SemiStr {
+        self.shared_sub(self.as_str().trim_end_matches(f))
+    }
+
+    /// Returns a string sharing the buffer of self, whose content is
+    /// the given substring of self.
+    #[inline]
+    pub(crate) fn shared_sub(&self, sub: &str) -> SemiStr {
+        let start = (sub.as_ptr() as usize).wrapping_sub(self.payload_ptr() as usize);
+        assert!(start <= self.len() && sub.len() <= self.len() - start, "not a substring");
+        // SAFETY
+        //
+        // sub is a str slice of self, so on char boundary.
+        unsafe { self.slice_shared(start, start + sub.len()) }
+    }
+
+    /// Returns the longest common prefix of sorted strings.
+    ///
+    /// Only the first and the last strings are compared, as they
+    /// differ the most in a sorted slice. Stored prefixes are compared
+    /// first, so payloads are not touched if they differ in the first
+    /// 4 bytes. The result shares the buffer of the first string, and
+    /// empty slice produces empty string.
+    pub fn common_prefix_of(strs: &[SemiStr]) -> SemiStr {
+        let (Some(first), Some(last)) = (strs.first(), strs.last()) else {
+            return SemiStr::default()
+        };
+        debug_assert!(first <= last, "strings are not sorted");
+        let n = first.common_prefix_len(last);
+        // SAFETY
+        //
+        // n is on char boundary and no more than length.
+        unsafe { first.slice_shared(0, n) }
+    }
+
+    /// Returns the stored 4-byte prefix, padded with zeros if shorter.
+    ///
+    /// Prefix of inline and heap strings is read from the header
+    /// without dereferencing heap memory.
+    #[inline]
+    pub fn prefix(&self) -> [u8; 4] {
+        self.prefix_bytes()
+    }
+
+    /// Returns whether the string starts with given prefix.
+    ///
+    /// The stored prefix is compared first, so heap memory is not
+    /// touched if they differ in the first 4 bytes.
+    #[inline]
+    pub fn starts_with_prefix(&self, prefix: &str) -> bool {
+        if prefix.len() > self.len() {
+            return false
+        }
+        let n = prefix.len().min(4);
+        if self.prefix_bytes()[..n] != prefix.as_bytes()[..n] {
+            return false
+        }
+        prefix.len() <= 4 || self.as_bytes().starts_with(prefix.as_bytes())
+    }
+
+    /// Checks that two strings are equal ignoring ASCII case.
+    ///
+    /// Lengths and stored prefixes are compared first, so heap memory
+    /// is not touched if they differ in the first 4 bytes.
+    #[inline]
+    pub fn eq_ignore_ascii_case(&self, other: &str) -> bool {
+        if self.len() != other.len() {
+            return false
+        }
+        let n = other.len().min(4);
+        if !self.prefix_bytes()[..n].eq_ignore_ascii_case(&other.as_bytes()[..n]) {
+            return false
+        }
+        other.len() <= 4 || self.as_bytes()[4..].eq_ignore_ascii_case(&other.as_bytes()[4..])
+    }
+
+    /// Returns byte length of the longest common prefix, which is
+    /// on char boundary.
+    ///
+    /// Stored prefixes are compared first, so heap memory is not
+    /// touched if they differ in the first 4 bytes.
+    #[inline]
+    pub fn common_prefix_len(&self, other: &SemiStr) -> usize {
+        let max = self.len().min(other.len());
+        let (p1, p2) = (self.prefix_bytes(), other.prefix_bytes());
+        let mut n = p1.iter().zip(&p2).take(max).take_while(|(a, b)| a == b).count();
+        if n == 4 {
+            n += self.as_bytes()[4..max].iter()
+                .zip(&other.as_bytes()[4..max])
+                .take_while(|(a, b)| a == b)
+                .count();
+        }
+        while !self.is_char_boundary(n) {
+            n -= 1;
+        }
+        n
+    }
+
+    /// Wrap the string into lines no wider than given width.
+    ///
+    /// Words are separated by whitespaces and placed greedily, and
+    /// words wider than a line are broken at char boundary. Newlines
+    /// in the string are kept as line breaks. Width is measured in
+    /// chars, or in display columns if feature `unicode-width` is
+    /// enabled, so that wide chars take two columns.
+    ///
+    /// Lines share the buffer of self without copying.
+    ///
+    /// # Panics
+    ///
+    /// Panics if width is zero.
+    pub fn wrap(&self, width: usize) -> Vec<SemiStr> {
+        assert!(width > 0, "wrap width must be positive");
+        let mut res = vec![];
+        // SAFETY
+        //
+        // all slice positions below are word or char boundaries.
+        let mut push = |start: usize, end: usize| res.push(unsafe { self.slice_shared(start, end) });
+        let mut base = 0;
+        for seg in self.as_str().split_inclusive('\n') {
+            let hard = seg.strip_suffix('\n').unwrap_or(seg);
+            let hard = hard.strip_suffix('\r').unwrap_or(hard);
+            // start, end and width of current line.
+            let mut line: Option<(usize, usize, usize)> = None;
+            let mut blank = true;
+            for (pos, word) in split_words(hard) {
+                blank = false;
+                let mut start = base + pos;
+                let mut word = word;
+                let mut ww = display_width(word);
+                if let Some((ls, le, lw)) = line {
+                    let lw = lw + display_width(&hard[le - base..pos]) + ww;
+                    if lw <= width {
+                        line = Some((ls, start + word.len(), lw));
+                        continue
+                    }
+                    push(ls, le);
+                    line = None;
+                }
+                while ww > width {
+                    let (cut, cw) = cut_width(word, width);
+                    push(start, start + cut);
+                    start += cut;
+                    word = &word[cut..];
+                    ww -= cw;
+                }
+                if !word.is_empty() {
+                    line = Some((start, start + word.len(), ww));
+                }
+            }
+            match line {
+                Some((ls, le, _)) => push(ls, le),
+                None if blank => push(base, base),
+                None => (),
+            }
+            base += seg.len();
+        }
+        res
+    }
+
+    /// Returns owner of the adopted allocation if self is a foreign
+    /// string owned by T, and covers the owner payload from its start.
+    #[inline]
+    fn foreign_owner<T: 'static>(&self) -> Option<&T> {
+        if self.kind() != Kind::Foreign {
+            return None
+        }
+        let view = self.as_view();
+        if view.offset != 0 {
+            return None
+        }
+        // SAFETY
+        //
+        // pointer of foreign string refers to a foreign block.
+        unsafe { foreign_owner(untag_ptr(view.ptr)) }
+    }
+
+    /// Returns owner of the adopted allocation if self is a foreign
+    /// string owned by T, covers the owner payload from its start, and
+    /// has no other reference. Otherwise returns self back.
+    #[inline]
+    fn into_foreign_owner<T: 'static>(self) -> std::result::Result<T, SemiStr> {
+        if self.foreign_owner::<T>().is_none() {
+            return Err(self)
+        }
+        let ptr = untag_ptr(self.as_view().ptr);
+        // SAFETY
+        //
+        // ptr refers to a foreign block owned by T, which is kept alive
+        // by self. Acquire load synchronizes with releases of other
+        // references, so the block is exclusively owned if count is 1.
+        unsafe {
+            if block_header(ptr).rc.load(AtomicOrdering::Acquire) != 1 {
+                return Err(self)
+            }
+            let _this = ManuallyDrop::new(self);
+            let block = Box::from_raw(ptr.sub(size_of::<ForeignBlock<T>>()) as *mut ForeignBlock<T>);
+            Ok(block.owner)
+        }
+    }
+
+    /// Returns a string sharing the buffer of self, without copying
+    /// heap payload.
+    #[inline]
+    pub(crate) fn share(&self) -> SemiStr {
+        unsafe { self.slice_shared(0, self.len()) }
+    }
+
+    /// Returns the string with chars escaped by [`char::escape_debug`].
+    ///
+    /// The result is built in a single allocation, or shares the buffer
+    /// of self if no char needs escaping.
+    #[inline]
+    pub fn escape_debug(&self) -> SemiStr {
+        self.escaped(|s| s.escape_debug())
+    }
+
+    /// Returns the string with chars escaped by [`char::escape_default`].
+    ///
+    /// The result is built in a single allocation, or shares the buffer
+    /// of self if no char needs escaping.
+    #[inline]
+    pub fn escape_default(&self) -> SemiStr {
+        self.escaped(|s| s.escape_default())
+    }
+
+    #[inline]
+    fn escaped<'a, I, F>(&'a self, f: F) -> SemiStr
+    where
+        I: Iterator<Item = char>,
+        F: Fn(&'a str) -> I,
+    {
+        let len: usize = f(self.as_str()).map(char::len_utf8).sum();
+        // escaping never shortens a char.
+        if len == self.len() {
+            return self.share()
+        }
+        unsafe { build_str(len, |buf| write_chars(buf, f(self.as_str()))) }
+    }
+
+    /// Returns the string with ASCII letters mapped to lowercase.
+    ///
+    /// The result is converted in a copy of the inline buffer or a
+    /// single allocation, or shares the buffer of self if no letter
+    /// is changed.
+    #[inline]
+    pub fn to_ascii_lowercase(&self) -> SemiStr {
+        if !self.bytes().any(|b| b.is_ascii_uppercase()) {
+            return self.share()
+        }
+        self.ascii_mapped(<[u8]>::make_ascii_lowercase)
+    }
+
+    /// Returns the string with ASCII letters mapped to uppercase.
+    ///
+    /// The result is converted in a copy of the inline buffer or a
+    /// single allocation, or shares the buffer of self if no letter
+    /// is changed.
+    #[inline]
+    pub fn to_ascii_uppercase(&self) -> SemiStr {
+        if !self.bytes().any(|b| b.is_ascii_lowercase()) {
+            return self.share()
+        }
+        self.ascii_mapped(<[u8]>::make_ascii_uppercase)
+    }
+
+    /// Returns the string mapped to lowercase, same as [`str::to_lowercase`].
+    ///
+    /// ASCII string is converted by [`SemiStr::to_ascii_lowercase`],
+    /// otherwise the result is built in a single allocation, or stored
+    /// inline if short, or shares the buffer of self if no char is
+    /// changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result is longer than 4GB.
+    #[inline]
+    pub fn to_lowercase(&self) -> SemiStr {
+        if self.is_ascii() {
+            return self.to_ascii_lowercase()
+        }
+        // final sigma depends on context, leave it to std.
+        if self.contains('Σ') {
+            return SemiStr::try_from(self.as_str().to_lowercase()).unwrap()
+        }
+        let lower = || self.chars().flat_map(char::to_lowercase);
+        if lower().eq(self.chars()) {
+            return self.share()
+        }
+        self.mapped(lower)
+    }
+
+    /// Returns the string mapped to uppercase, same as [`str::to_uppercase`].
+    ///
+    /// ASCII string is converted by [`SemiStr::to_ascii_uppercase`],
+    /// otherwise the result is built in a single allocation, or stored
+    /// inline if short, or shares the buffer of self if no char is
+    /// changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result is longer than 4GB.
+    #[inline]
+    pub fn to_uppercase(&self) -> SemiStr {
+        if self.is_ascii() {
+            return self.to_ascii_uppercase()
+        }
+        let upper = || self.chars().flat_map(char::to_uppercase);
+        if upper().eq(self.chars()) {
+            return self.share()
+        }
+        self.mapped(upper)
+    }
+
+    /// Copy the string and convert bytes in place with f, which must
+    /// keep them valid utf-8.
+    #[inline]
+    fn ascii_mapped(&self, f: fn(&mut [u8])) -> SemiStr {
+        if self.kind() == Kind::Inline {
+            let mut inline = self.as_inline();
+            f(&mut inline.data[..self.len()]);
+            return SemiStr::from_inline(inline)
+        }
+        unsafe {
+            build_str(self.len(), |buf| {
+                buf.copy_from_slice(self.as_bytes());
+                f(buf);
+            })
+        }
+    }
+
+    /// Returns the string with its first char mapped to uppercase.
+    ///
+    /// ASCII inline string is converted in a copy of the inline buffer,
+    /// otherwise the result is built in a single allocation, or shares
+    /// the buffer of self if the first char is unchanged.
+    #[inline]
+    pub fn capitalized(&self) -> SemiStr {
+        if self.kind() == Kind::Inline && self.is_ascii() {
+            let mut inline = self.as_inline();
+            if let Some(b) = inline.data.first_mut() {
+                b.make_ascii_uppercase();
+            }
+            return SemiStr::from_inline(inline)
+        }
+        let mut chars = self.chars();
+        let Some(first) = chars.next() else {
+            return self.share()
+        };
+        if first.to_uppercase().eq(iter::once(first)) {
+            return self.share()
+        }
+        self.mapped(|| first.to_uppercase().chain(chars.clone()))
+    }
+
+    /// Returns the string with the first char of every word mapped to
+    /// uppercase, and all other chars mapped to lowercase.
+    /// Words are separated by whitespaces.
+    ///
+    /// ASCII inline string is converted in a copy of the inline buffer,
+    /// otherwise the result is built in a single allocation, or shares
+    /// the buffer of self if no char is changed.
+    #[inline]
+    pub fn title_case(&self) -> SemiStr {
+        if self.kind() == Kind::Inline && self.is_ascii() {
+            let mut inline = self.as_inline();
+            let mut word_start = true;
+            for b in &mut inline.data[..self.len()] {
+                if word_start {
+                    b.make_ascii_uppercase();
+                } else {
+                    b.make_ascii_lowercase();
+                }
+                word_start = b.is_ascii_whitespace();
+            }
+            return SemiStr::from_inline(inline)
+        }
+        let title = || {
+            let mut word_start = true;
+            self.chars().flat_map(move |ch| {
+                let res = if word_start { CaseMapped::Upper(ch.to_uppercase()) } else { CaseMapped::Lower(ch.to_lowercase()) };
+                word_start = ch.is_whitespace();
+                res
+            })
+        };
+        if title().eq(self.chars()) {
+            return self.share()
+        }
+        self.mapped(title)
+    }
+
+    /// Returns the string with chars in reverse order.
+    ///
+    /// The result has the same length, and is built in a single
+    /// allocation, or stored inline if short.
+    #[inline]
+    pub fn reversed(&self) -> SemiStr {
+        unsafe { build_str(self.len(), |buf| write_chars(buf, self.chars().rev())) }
+    }
+
+    /// Returns the string with extended grapheme clusters in reverse
+    /// order, so that combining marks stay with their base chars.
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn reversed_graphemes(&self) -> SemiStr {
+        use unicode_segmentation::UnicodeSegmentation;
+        unsafe {
+            build_str(self.len(), |buf| {
+                let mut pos = 0;
+                for g in self.graphemes(true).rev() {
+                    buf[pos..pos + g.len()].copy_from_slice(g.as_bytes());
+                    pos += g.len();
+                }
+            })
+        }
+    }
+
+    /// Build a string of chars produced by f, which is called twice to
+    /// compute length first.
+    #[inline]
+    fn mapped<I, F>(&self, f: F) -> SemiStr
+    where
+        I: Iterator<Item = char>,
+        F: Fn() -> I,
+    {
+        let len: usize = f().map(char::len_utf8).sum();
+        unsafe { build_str(len, |buf| write_chars(buf, f())) }
+    }
+
+    /// Returns the first 8 bytes as big-endian integer, padded with zeros.
+    /// Ordering of sort keys is consistent with ordering of strings,
+    /// equal keys require full comparison.
+    #[inline]
+    pub(crate) fn sort_key(&self) -> u64 {
+        let mut key = [0u8; 8];
+        match self.kind() {
+            Kind::Inline => key.copy_from_slice(&self.inline_data()[..8]),
+            Kind::Heap | Kind::View | Kind::Static | Kind::Foreign => key.copy_from_slice(&self.as_bytes()[..8]),
+        }
+        u64::from_be_bytes(key)
+    }
+
+    /// Returns pointer to the 16-byte header.
+    #[inline]
+    pub(crate) fn header_ptr(&self) -> *const u8 {
+        self as *const SemiStr as *const u8
+    }
+
+    /// Returns the first 8 bytes of the header: 4-byte length in
+    /// native endian, followed by 4-byte prefix of the string.
+    /// Prefix of string shorter than 4 bytes is padded with zeros.
+    ///
+    /// The header is laid out identically for inline and heap format,
+    /// so it can be used for sorting or hashing without dereferencing
+    /// the payload.
+    #[inline]
+    pub fn header_bytes(&self) -> [u8; 8] {
+        let mut res = [0u8; 8];
+        res[..4].copy_from_slice(&self.as_heap().len.to_ne_bytes());
+        res[4..].copy_from_slice(&self.prefix_bytes());
+        res
+    }
+
+    /// Returns pointer to the payload bytes, which is the inline buffer
+    /// inside self for short string, or the shared heap block for long
+    /// string.
+    ///
+    /// The pointer is valid for reads of `len()` bytes as long as self
+    /// is alive, and must not be written through.
+    #[inline]
+    pub fn payload_ptr(&self) -> *const u8 {
+        self.as_str().as_ptr()
+    }
+
+    /// Returns whether the string is stored in a shared heap block,
+    /// either owning the whole block or viewing part of it, including
+    /// blocks adopting a foreign allocation such as String.
+    #[inline]
+    pub fn is_heap(&self) -> bool {
+        matches!(self.kind(), Kind::Heap | Kind::View | Kind::Foreign)
+    }
+
+    /// Returns whether the string refers to static memory,
+    /// see [`SemiStr::from_static`].
+    #[inline]
+    pub fn is_static(&self) -> bool {
+        self.kind() == Kind::Static
+    }
+
+    /// Returns number of strings sharing the heap block,
+    /// or None if the string is inline or static.
+    ///
+    /// The count may be changed concurrently by other threads.
+    #[inline]
+    pub fn ref_count(&self) -> Option<usize> {
+        let (owner, _) = self.block_ref()?;
+        // SAFETY
+        //
+        // owner is payload pointer of heap block kept alive by self.
+        Some(unsafe { block_header(owner).rc.load(AtomicOrdering::Relaxed) })
+    }
+
+    /// Returns size in bytes of the heap block retained by the string,
+    /// including header and spare capacity, or 0 if the string is
+    /// inline or static. The block may be shared with other strings,
+    /// see [`SemiStr::ref_count`].
+    #[inline]
+    pub fn heap_size_bytes(&self) -> usize {
+        match self.block_ref() {
+            // SAFETY
+            //
+            // owner is payload pointer of heap block kept alive by self.
+            Some((owner, _)) => unsafe { block_size(owner) },
+            None => 0,
+        }
+    }
+
+    /// Returns whether two strings are identical without comparing
+    /// heap memory: long strings must refer to the same bytes of the
+    /// same buffer, and inline strings must have equal bytes.
+    ///
+    /// Equal long strings in different buffers are not identical.
+    #[inline]
+    pub fn ptr_eq(&self, other: &SemiStr) -> bool {
+        if self.len() != other.len() {
+            return false
+        }
+        if self.len() <= INLINE_CAP {
+            return self.inline_data() == other.inline_data()
+        }
+        self.heap_data_ptr() == other.heap_data_ptr()
+    }
+
+    /// Read self as inline format.
+    /// Data bytes are meaningful only if kind is inline.
+    #[inline]
+    pub(crate) fn as_inline(&self) -> Inline {
+        Inline{len: self.len() as u32, data: *self.inline_data()}
+    }
+
+    #[inline]
+    fn from_inline(inline: Inline) -> SemiStr {
+        SemiStr{len: encode_len(inline.len), body: Body{inline: inline.data}}
+    }
+
+    #[inline]
+    pub(crate) fn from_heap(heap: Heap) -> SemiStr {
+        SemiStr{len: encode_len(heap.len), body: Body{heap: HeapBody{prefix: heap.prefix, ptr: heap.ptr}}}
+    }
+
+    #[inline]
+    pub(crate) fn from_view(view: View) -> SemiStr {
+        SemiStr{len: encode_len(view.len), body: Body{view: ViewBody{offset: view.offset, ptr: view.ptr}}}
+    }
+
+    /// Join pieces with separator into a single allocation, or inline.
+    fn join_pieces<S: AsRef<str>>(sep: &str, pieces: &[S]) -> Result<SemiStr> {
+        let len = pieces.iter().fold(0usize, |n, s| n.saturating_add(s.as_ref().len()))
+            .saturating_add(sep.len().saturating_mul(pieces.len().saturating_sub(1)));
+        if len > MAX_LEN {
+            return Err(Error::StringTooLong(len))
+        }
+        // SAFETY
+        //
+        // buffer is filled with utf-8 strings and separators.
+        Ok(unsafe {
+            build_str(len, |buf| {
+                let mut pos = 0;
+                for (i, s) in pieces.iter().enumerate() {
+                    if i > 0 {
+                        buf[pos..pos + sep.len()].copy_from_slice(sep.as_bytes());
+                        pos += sep.len();
+                    }
+                    let s = s.as_ref().as_bytes();
+                    buf[pos..pos + s.len()].copy_from_slice(s);
+                    pos += s.len();
+                }
+            })
+        })
+    }
+
+    fn from_char_iter<I: iter::Iterator<Item = char>>(mut iter: I) -> SemiStr {
+        let (min_size, _) = iter.size_hint();
+        assert!(min_size <= MAX_LEN);
+        if min_size > INLINE_CAP {
+            let s: String = iter.collect();
+            return unsafe { heap_str(s.as_bytes()) }
+        }
+        let mut len = 0;
+        let mut data = [0u8; INLINE_CAP];
+        while let Some(ch) = iter.next() {
+            let size = ch.len_utf8();
+            if size + len > INLINE_CAP {
+                let (min_remaining, _) = iter.size_hint();
+                let mut heap = String::with_capacity(size + len + min_remaining);
+                heap.push_str(core::str::from_utf8(&data[..len]).unwrap());
+                heap.push(ch);
+                heap.extend(iter);
+                return unsafe { heap_str(heap.as_bytes()) }
+            }
+            ch.encode_utf8(&mut data[len..]);
+            len += size;
+            assert!(len <= MAX_LEN);
+        }
+        SemiStr::from_inline(Inline{len: len as u32, data})
+    }
+}
+
+// SAFETY
+//
+// SemiStr is immutable, and the shared heap block is managed
+// with atomic reference count.
+unsafe impl<const INLINE: usize> Send for SemiStrN<INLINE> {}
+unsafe impl<const INLINE: usize> Sync for SemiStrN<INLINE> {}
+
+impl<const INLINE: usize> Deref for SemiStrN<INLINE> {
+    type Target = str;
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const INLINE: usize> AsRef<str> for SemiStrN<INLINE> {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<const INLINE: usize> AsRef<[u8]> for SemiStrN<INLINE> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsRef<OsStr> for SemiStr {
+    #[inline]
+    fn as_ref(&self) -> &OsStr {
+        OsStr::new(self.as_str())
+    }
+}
+
+impl AsRef<Path> for SemiStr {
+    #[inline]
+    fn as_ref(&self) -> &Path {
+        Path::new(self.as_str())
+    }
+}
+
+impl<const INLINE: usize> Default for SemiStrN<INLINE> {
+    #[inline]
+    fn default() -> Self {
+        Self::from_inline_str("")
+    }
+}
+
+impl<'s, const INLINE: usize> TryFrom<&'s str> for SemiStrN<INLINE> {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: &'s str) -> Result<Self> {
+        if value.len() <= INLINE {
+            Ok(Self::from_inline_str(value))
+        } else if value.len() <= MAX_LEN {
+            // SAFETY
+            // 
+            // valid utf-8 string and length between INLINE + 1 and MAX_LEN
+            Ok(unsafe { heap_str(value.as_bytes()) }.into_inline_cap())
+        } else {
+            Err(Error::StringTooLong(value.len()))
+        }
+    }
+}
+
+impl<'s> TryFrom<&'s [u8]> for SemiStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: &'s [u8]) -> Result<Self> {
+        Self::try_from(validate_utf8(value)?)
+    }
+}
+
+/// Buffer of long string is adopted without copying, including its
+/// spare capacity, see [`SemiStr::compact`].
+impl TryFrom<String> for SemiStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: String) -> Result<Self> {
+        if value.len() > MAX_LEN {
+            return Err(Error::StringTooLong(value.len()))
+        }
+        let (data, len, cap) = (value.as_ptr(), value.len(), value.capacity());
+        // SAFETY
+        //
+        // buffer of String stays in place when it is moved.
+        Ok(unsafe { foreign_str(value, data, len, cap) })
+    }
+}
+
+/// Buffer of long string is adopted without copying.
+impl TryFrom<Box<str>> for SemiStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: Box<str>) -> Result<Self> {
+        if value.len() > MAX_LEN {
+            return Err(Error::StringTooLong(value.len()))
+        }
+        let (data, len) = (value.as_ptr(), value.len());
+        // SAFETY
+        //
+        // buffer of Box stays in place when it is moved.
+        Ok(unsafe { foreign_str(value, data, len, len) })
+    }
+}
+
+/// Bytes are validated, and buffer of long string is adopted
+/// without copying.
+impl TryFrom<Box<[u8]>> for SemiStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: Box<[u8]>) -> Result<Self> {
+        if value.len() > MAX_LEN {
+            return Err(Error::StringTooLong(value.len()))
+        }
+        validate_utf8(&value)?;
+        let (data, len) = (value.as_ptr(), value.len());
+        // SAFETY
+        //
+        // bytes are validated, and buffer of Box stays in place when
+        // it is moved.
+        Ok(unsafe { foreign_str(value, data, len, len) })
+    }
+}
+
+/// Bytes are validated and buffer of long string is adopted without
+/// copying, including its spare capacity. The vector is given back in
+/// the error on failure.
+impl TryFrom<Vec<u8>> for SemiStr {
+    type Error = FromBytesError;
+    #[inline]
+    fn try_from(value: Vec<u8>) -> std::result::Result<Self, FromBytesError> {
+        if value.len() > MAX_LEN {
+            let len = value.len();
+            return Err(FromBytesError::new(value, Error::StringTooLong(len)))
+        }
+        if let Err(e) = validate_utf8(&value) {
+            return Err(FromBytesError::new(value, e))
+        }
+        let (data, len, cap) = (value.as_ptr(), value.len(), value.capacity());
+        // SAFETY
+        //
+        // bytes are validated, and buffer of Vec stays in place when it
+        // is moved.
+        Ok(unsafe { foreign_str(value, data, len, cap) })
+    }
+}
+
+/// Arc of long string is held by the foreign block, so its payload is
+/// shared without copying.
+impl TryFrom<Arc<str>> for SemiStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: Arc<str>) -> Result<Self> {
+        if value.len() > MAX_LEN {
+            return Err(Error::StringTooLong(value.len()))
+        }
+        let (data, len) = (value.as_ptr(), value.len());
+        // SAFETY
+        //
+        // payload of Arc stays in place when it is moved.
+        Ok(unsafe { foreign_str(value, data, len, len) })
+    }
+}
+
+/// Bytes are validated, and Arc of long string is held by the foreign
+/// block, so its payload is shared without copying.
+impl TryFrom<Arc<[u8]>> for SemiStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: Arc<[u8]>) -> Result<Self> {
+        if value.len() > MAX_LEN {
+            return Err(Error::StringTooLong(value.len()))
+        }
+        validate_utf8(&value)?;
+        let (data, len) = (value.as_ptr(), value.len());
+        // SAFETY
+        //
+        // bytes are validated, and payload of Arc stays in place when
+        // it is moved.
+        Ok(unsafe { foreign_str(value, data, len, len) })
+    }
+}
+
+/// The Arc is cloned without copying if the string was converted from
+/// it and not sliced, otherwise payload is copied.
+impl From<SemiStr> for Arc<str> {
+    #[inline]
+    fn from(value: SemiStr) -> Self {
+        match value.foreign_owner::<Arc<str>>() {
+            Some(arc) if arc.len() == value.len() => arc.clone(),
+            _ => Arc::from(value.as_str()),
+        }
+    }
+}
+
+/// The Arc is cloned without copying if the string was converted from
+/// Arc<[u8]> or Arc<str> and not sliced, otherwise payload is copied.
+impl From<SemiStr> for Arc<[u8]> {
+    #[inline]
+    fn from(value: SemiStr) -> Self {
+        if let Some(arc) = value.foreign_owner::<Arc<[u8]>>() {
+            if arc.len() == value.len() {
+                return arc.clone()
+            }
+        }
+        match value.foreign_owner::<Arc<str>>() {
+            Some(arc) if arc.len() == value.len() => Arc::from(arc.clone()),
+            _ => Arc::from(value.as_bytes()),
+        }
+    }
+}
+
+impl<'a> TryFrom<Cow<'a, str>> for SemiStr {
+    type Error = Error;
+    #[inline]
+    fn try_from(value: Cow<'a, str>) -> Result<Self> {
+        match value {
+            Cow::Borrowed(s) => Self::try_from(s),
+            Cow::Owned(s) => Self::try_from(s),
+        }
+    }
+}
+
+impl<'a> From<&'a SemiStr> for Cow<'a, str> {
+    #[inline]
+    fn from(value: &'a SemiStr) -> Self {
+        Cow::Borrowed(value.as_str())
+    }
+}
+
+impl From<SemiStr> for String {
+    #[inline]
+    fn from(value: SemiStr) -> Self {
+        value.into_string()
+    }
+}
+
+impl From<char> for SemiStr {
+    #[inline]
+    fn from(ch: char) -> Self {
+        let mut data = [0u8; INLINE_CAP];
+        let len = ch.encode_utf8(&mut data).len();
+        SemiStr::from_inline(Inline{len: len as u32, data})
+    }
+}
+
+/// Concatenation operators, which build a new string.
+///
+/// # Panics
+///
+/// Panics if total length is greater than 4GB.
+impl Add<&str> for SemiStr {
+    type Output = SemiStr;
+    #[inline]
+    fn add(self, rhs: &str) -> SemiStr {
+        if rhs.is_empty() {
+            return self
+        }
+        SemiStr::join_pieces("", &[self.as_str(), rhs]).unwrap()
+    }
+}
+
+impl Add<&SemiStr> for SemiStr {
+    type Output = SemiStr;
+    #[inline]
+    fn add(self, rhs: &SemiStr) -> SemiStr {
+        if self.is_empty() {
+            return rhs.clone()
+        }
+        self + rhs.as_str()
+    }
+}
+
+impl Add<SemiStr> for SemiStr {
+    type Output = SemiStr;
+    #[inline]
+    fn add(self, rhs: SemiStr) -> SemiStr {
+        if self.is_empty() {
+            return rhs
+        }
+        self + rhs.as_str()
+    }
+}
+
+impl Add<char> for SemiStr {
+    type Output = SemiStr;
+    #[inline]
+    fn add(self, rhs: char) -> SemiStr {
+        self + rhs.encode_utf8(&mut [0u8; 4]) as &str
+    }
+}
+
+impl FromStr for SemiStr {
+    type Err = Error;
+    #[inline]
+    fn from_str(s: &str) -> Result<Self> {
+        Self::try_from(s)
+    }
+}
+
+impl<const INLINE: usize> Drop for SemiStrN<INLINE> {
+    #[inline]
+    fn drop(&mut self) {
+        match self.kind() {
+            Kind::Inline | Kind::Static => (),
+            Kind::Heap => unsafe { release_block(self.as_heap().ptr) },
+            Kind::View | Kind::Foreign => unsafe { release_block(untag_ptr(self.as_view().ptr)) },
+        }
+    }
+}
+
+impl<const INLINE: usize> PartialEq<str> for SemiStrN<INLINE> {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        if self.len() != other.len() {
+            return false
+        }
+        if other.len() <= INLINE {
+            return self.as_str() == other
+        }
+        // compare prefix first
+        if self.prefix_bytes() != other.as_bytes()[..4] {
+            return false
+        }
+        self.as_str() == other
+    }
+}
+
+impl<const INLINE: usize> PartialEq<SemiStrN<INLINE>> for str {
+    #[inline]
+    fn eq(&self, other: &SemiStrN<INLINE>) -> bool {
+        other.eq(self)
+    }
+}
+
+impl<const INLINE: usize> PartialEq<&'_ str> for SemiStrN<INLINE> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        if self.len() != other.len() {
+            return false
+        }
+        if other.len() <= INLINE {
+            return self.as_str() == *other
+        }
+        // compare prefix first
+        if self.prefix_bytes() != other.as_bytes()[..4] {
+            return false
+        }
+        self.as_str() == *other
+    }
+}
+
+impl<const INLINE: usize> PartialEq<SemiStrN<INLINE>> for &'_ str {
+    #[inline]
+    fn eq(&self, other: &SemiStrN<INLINE>) -> bool {
+        other.eq(self)
+    }
+}
+
+impl<const INLINE: usize> PartialEq<String> for SemiStrN<INLINE> {
+    #[inline]
+    fn eq(&self, other: &String) -> bool {
+        self.eq(other.as_str())
+    }
+}
+
+impl<const INLINE: usize> PartialEq<SemiStrN<INLINE>> for String {
+    #[inline]
+    fn eq(&self, other: &SemiStrN<INLINE>) -> bool {
+        other.eq(self.as_str())
+    }
+}
+
+impl<const INLINE: usize> PartialEq<Cow<'_, str>> for SemiStrN<INLINE> {
+    #[inline]
+    fn eq(&self, other: &Cow<'_, str>) -> bool {
+        self.eq(other.as_ref())
+    }
+}
+
+impl<const INLINE: usize> PartialEq<SemiStrN<INLINE>> for Cow<'_, str> {
+    #[inline]
+    fn eq(&self, other: &SemiStrN<INLINE>) -> bool {
+        other.eq(self.as_ref())
+    }
+}
+
+impl<const INLINE: usize> PartialOrd<str> for SemiStrN<INLINE> {
+    #[inline]
+    fn partial_cmp(&self, other: &str) -> Option<Ordering> {
+        Some(self.as_str().cmp(other))
+    }
+}
+
+impl<const INLINE: usize> PartialOrd<SemiStrN<INLINE>> for str {
+    #[inline]
+    fn partial_cmp(&self, other: &SemiStrN<INLINE>) -> Option<Ordering> {
+        Some(self.cmp(other.as_str()))
+    }
+}
+
+impl<const INLINE: usize> PartialOrd<&'_ str> for SemiStrN<INLINE> {
+    #[inline]
+    fn partial_cmp(&self, other: &&str) -> Option<Ordering> {
+        Some(self.as_str().cmp(*other))
+    }
+}
+
+impl<const INLINE: usize> PartialOrd<SemiStrN<INLINE>> for &'_ str {
+    #[inline]
+    fn partial_cmp(&self, other: &SemiStrN<INLINE>) -> Option<Ordering> {
+        Some((*self).cmp(other.as_str()))
+    }
+}
+
+/// Strings of different inline capacities are comparable.
+impl<const INLINE: usize, const M: usize> PartialEq<SemiStrN<M>> for SemiStrN<INLINE> {
+    #[inline]
+    fn eq(&self, other: &SemiStrN<M>) -> bool {
+        if self.len() != other.len() {
+            return false
+        }
+        if self.len() <= INLINE_CAP {
+            // both are inline and zero padded.
+            return self.inline_data()[..INLINE_CAP] == other.inline_data()[..INLINE_CAP]
+        }
+        // compare prefix
+        if self.prefix_bytes() != other.prefix_bytes() {
+            return false
+        }
+        self.as_str() == other.as_str()
+    }
+} 
+
+impl<const INLINE: usize> Eq for SemiStrN<INLINE> {}
+
+impl<const INLINE: usize> Hash for SemiStrN<INLINE> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl<const INLINE: usize> PartialOrd for SemiStrN<INLINE> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const INLINE: usize> Ord for SemiStrN<INLINE> {
+    /// Stored prefixes are compared first as big-endian integers, so
+    /// heap memory is only touched if they are equal. Zero padding of
+    /// short strings never orders them after longer strings.
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_bytes(other)
+    }
+}
+
+impl<const INLINE: usize> fmt::Debug for SemiStrN<INLINE> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const INLINE: usize> fmt::Display for SemiStrN<INLINE> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const INLINE: usize> Clone for SemiStrN<INLINE> {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self.kind() {
+            Kind::Inline => SemiStrN{len: self.len, body: self.body},
+            Kind::Static => unsafe { std::ptr::read(self) },
+            // Heap and view share the block by bumping its reference count.
+            Kind::Heap | Kind::View | Kind::Foreign => unsafe {
+                retain_block(self.block_ref().unwrap().0);
+                std::ptr::read(self)
+            }
+        }
+    }
+}
+
+impl<const INLINE: usize> Borrow<str> for SemiStrN<INLINE> {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_ref()
+    }
+}
+
+impl iter::FromIterator<char> for SemiStr {
+    fn from_iter<I: iter::IntoIterator<Item = char>>(iter: I) -> SemiStr {
+        let iter = iter.into_iter();
+        Self::from_char_iter(iter)
+    }
+}
+
+/// Concatenating constructors, see [`SemiStr::try_from_iter`].
+///
+/// # Panics
+///
+/// Panics if total length is greater than 4GB.
+impl<'a> iter::FromIterator<&'a str> for SemiStr {
+    #[inline]
+    fn from_iter<I: iter::IntoIterator<Item = &'a str>>(iter: I) -> SemiStr {
+        Self::try_from_iter(iter).unwrap()
+    }
+}
+
+impl iter::FromIterator<String> for SemiStr {
+    #[inline]
+    fn from_iter<I: iter::IntoIterator<Item = String>>(iter: I) -> SemiStr {
+        Self::try_from_iter(iter).unwrap()
+    }
+}
+
+impl<'a> iter::FromIterator<Cow<'a, str>> for SemiStr {
+    #[inline]
+    fn from_iter<I: iter::IntoIterator<Item = Cow<'a, str>>>(iter: I) -> SemiStr {
+        Self::try_from_iter(iter).unwrap()
+    }
+}
+
+/// Concatenating constructor of owned pieces.
+///
+/// If at most one piece is non-empty, it is returned as is, sharing
+/// its buffer without copying.
+///
+/// # Panics
+///
+/// Panics if total length is greater than 4GB.
+impl iter::FromIterator<SemiStr> for SemiStr {
+    #[inline]
+    fn from_iter<I: iter::IntoIterator<Item = SemiStr>>(iter: I) -> SemiStr {
+        let mut pieces: Vec<SemiStr> = iter.into_iter().filter(|s| !s.is_empty()).collect();
+        match pieces.len() {
+            0 => SemiStr::default(),
+            1 => pieces.pop().unwrap(),
+            _ => Self::join_pieces("", &pieces).unwrap(),
+        }
+    }
+}
+
+/// Inline represents the inline format of short string,
+/// which is no longer than 12 bytes.
+/// In this scenario, all bytes are stored on stack.
+#[derive(Clone, Copy)]
+pub(crate) struct Inline {
+    len: u32,
+    pub(crate) data: [u8; INLINE_CAP],
+}
+
+/// Heap represents the long string stored on heap.
+///
+/// The pointer refers to the payload bytes of a shared block,
+/// so reading the string takes a single indirection.
+#[derive(Clone, Copy)]
+pub(crate) struct Heap {
+    pub(crate) len: u32,
+    pub(crate) prefix: [u8; 4],
+    pub(crate) ptr: *const u8,
+}
+
+/// View represents a long string sharing the heap block of another
+/// string, starting at given offset.
+///
+/// The pointer refers to the payload of the parent block, with
+/// VIEW_TAG set. The offset occupies the place of prefix in Heap.
+/// Static strings share this format, with STATIC_TAG also set, and
+/// so do foreign strings, with only FOREIGN_TAG set.
+#[derive(Clone, Copy)]
+pub(crate) struct View {
+    pub(crate) len: u32,
+    pub(crate) offset: u32,
+    pub(crate) ptr: *const u8,
+}
+
+/// Tag bit of heap pointer marking the view format.
+/// Payload of heap block is aligned to header, so the lowest bits are
+/// always free.
+pub(crate) const VIEW_TAG: usize = 1;
+
+/// Tag bit marking static memory, which is set together with VIEW_TAG,
+/// so static strings are read the same way as views.
+const STATIC_TAG: usize = 2;
+
+/// Tag of pointer to a foreign block, whose string is read the same
+/// way as views, except that payload is located by the metadata.
+const FOREIGN_TAG: usize = STATIC_TAG;
+
+const TAG_MASK: usize = VIEW_TAG | STATIC_TAG;
+
+#[inline]
+fn untag_ptr(ptr: *const u8) -> *const u8 {
+    ptr.map_addr(|a| a & !TAG_MASK)
+}
+
+/// Header of the shared heap block, which precedes the payload bytes
+/// in the same allocation.
+#[repr(C)]
+pub(crate) struct BlockHeader {
+    // atomic reference count.
+    pub(crate) rc: AtomicUsize,
+    // length of payload bytes.
+    cap: usize,
+}
+
+pub(crate) const BLOCK_HEADER: usize = size_of::<BlockHeader>();
+
+#[inline]
+fn block_layout(cap: usize) -> Layout {
+    Layout::from_size_align(BLOCK_HEADER + cap, align_of::<BlockHeader>()).unwrap()
+}
+
+#[inline]
+pub(crate) unsafe fn block_header<'a>(ptr: *const u8) -> &'a BlockHeader {
+    &*(ptr.sub(BLOCK_HEADER) as *const BlockHeader)
+}
+
+/// Allocate a shared block with reference count 1,
+/// returns pointer to the uninitialized payload.
+///
+/// # Safety
+///
+/// cap must be greater than 0.
+#[inline]
+pub(crate) unsafe fn alloc_block(cap: usize) -> *mut u8 {
+    match try_alloc_block(cap) {
+        Some(ptr) => ptr,
+        None => handle_alloc_error(block_layout(cap)),
+    }
+}
+
+/// Allocate a shared block with reference count 1, same as
+/// alloc_block(), but returns None on allocation failure.
+///
+/// # Safety
+///
+/// cap must be greater than 0.
+#[inline]
+pub(crate) unsafe fn try_alloc_block(cap: usize) -> Option<*mut u8> {
+    #[cfg(feature = "alloc-check")]
+    alloc_check::on_alloc(cap);
+    let ptr = alloc(block_layout(cap));
+    if ptr.is_null() {
+        return None
+    }
+    (ptr as *mut BlockHeader).write(BlockHeader{rc: AtomicUsize::new(1), cap});
+    Some(ptr.add(BLOCK_HEADER))
+}
+
+/// Returns payload length of the shared block.
+///
+/// # Safety
+///
+/// ptr must be payload pointer returned by alloc_block(), or pointer
+/// of a foreign block.
+#[inline]
+pub(crate) unsafe fn block_cap(ptr: *const u8) -> usize {
+    block_header(ptr).cap & !FOREIGN_FLAG
+}
+
+/// Returns pointer to payload of the shared block, which is ptr itself
+/// unless the block is foreign.
+///
+/// # Safety
+///
+/// ptr must be payload pointer returned by alloc_block(), or pointer
+/// of a foreign block.
+#[inline]
+pub(crate) unsafe fn block_payload(ptr: *const u8) -> *const u8 {
+    if block_header(ptr).cap & FOREIGN_FLAG == 0 {
+        ptr
+    } else {
+        foreign_meta(ptr).data
+    }
+}
+
+/// Returns number of bytes retained by the shared block, including
+/// header and the adopted allocation of foreign block.
+///
+/// # Safety
+///
+/// ptr must be payload pointer returned by alloc_block(), or pointer
+/// of a foreign block.
+#[inline]
+unsafe fn block_size(ptr: *const u8) -> usize {
+    let cap = block_header(ptr).cap;
+    if cap & FOREIGN_FLAG == 0 {
+        BLOCK_HEADER + cap
+    } else {
+        foreign_meta(ptr).size
+    }
+}
+
+/// Deallocate the shared block, or drop the foreign block with its
+/// owner.
+///
+/// # Safety
+///
+/// The block must have no more reference.
+#[inline]
+unsafe fn free_block(ptr: *const u8) {
+    let cap = block_header(ptr).cap;
+    if cap & FOREIGN_FLAG == 0 {
+        dealloc(ptr.sub(BLOCK_HEADER) as *mut u8, block_layout(cap));
+    } else {
+        (foreign_meta(ptr).drop)(ptr)
+    }
+}
+
+/// Flag in capacity of block header marking a foreign block.
+/// Capacity never reaches it, as allocation is limited to isize::MAX.
+const FOREIGN_FLAG: usize = 1 << (usize::BITS - 1);
+
+/// Block adopting an allocation owned by another type, e.g. String or
+/// Arc<str>, so that its payload is shared by SemiStr without copying.
+///
+/// The header is placed at the end, and the block is referred to by
+/// the pointer past the header, same as payload pointer of a native
+/// block, so reference counting is identical. The payload lives in
+/// the owner allocation, and is located by metadata right before
+/// the header.
+#[repr(C)]
+struct ForeignBlock<T> {
+    owner: T,
+    meta: ForeignMeta,
+    header: BlockHeader,
+}
+
+impl<T: 'static> ForeignBlock<T> {
+    const CHECK_ALIGN: () = assert!(align_of::<T>() <= align_of::<BlockHeader>());
+
+    unsafe fn drop_block(ptr: *const u8) {
+        drop(Box::from_raw(ptr.sub(size_of::<Self>()) as *mut Self));
+    }
+}
+
+#[repr(C)]
+struct ForeignMeta {
+    // payload pointer into the owner allocation.
+    data: *const u8,
+    // bytes retained by the block, including the owner allocation.
+    size: usize,
+    drop: unsafe fn(*const u8),
+    owner_type: fn() -> TypeId,
+}
+
+/// # Safety
+///
+/// ptr must be pointer of a foreign block.
+#[inline]
+unsafe fn foreign_meta<'a>(ptr: *const u8) -> &'a ForeignMeta {
+    &*(ptr.sub(BLOCK_HEADER + size_of::<ForeignMeta>()) as *const ForeignMeta)
+}
+
+/// Returns owner of the block if it is owned by T.
+///
+/// # Safety
+///
+/// ptr must be pointer of a foreign block.
+#[inline]
+unsafe fn foreign_owner<'a, T: 'static>(ptr: *const u8) -> Option<&'a T> {
+    if (foreign_meta(ptr).owner_type)() != TypeId::of::<T>() {
+        return None
+    }
+    Some(&(*(ptr.sub(size_of::<ForeignBlock<T>>()) as *const ForeignBlock<T>)).owner)
+}
+
+/// Construct SemiStr adopting the allocation of owner without copying.
+/// Short string is copied inline and owner is dropped.
+///
+/// # Safety
+///
+/// data must point to len bytes of valid utf-8 string owned by owner,
+/// which stay in place when owner is moved, len must be no more than
+/// MAX_LEN, and alloc_size is size of the owner allocation.
+#[inline]
+unsafe fn foreign_str<T: 'static>(owner: T, data: *const u8, len: usize, alloc_size: usize) -> SemiStr {
+    if len <= INLINE_CAP {
+        return inline_str(std::slice::from_raw_parts(data, len))
+    }
+    let () = ForeignBlock::<T>::CHECK_ALIGN;
+    #[cfg(feature = "alloc-check")]
+    alloc_check::on_alloc(size_of::<ForeignBlock<T>>());
+    let block = Box::into_raw(Box::new(ForeignBlock{
+        owner,
+        meta: ForeignMeta{
+            data,
+            size: size_of::<ForeignBlock<T>>() + alloc_size,
+            drop: ForeignBlock::<T>::drop_block,
+            owner_type: TypeId::of::<T>,
+        },
+        header: BlockHeader{rc: AtomicUsize::new(1), cap: len | FOREIGN_FLAG},
+    }));
+    let ptr = (block as *const u8).add(size_of::<ForeignBlock<T>>());
+    SemiStr::from_view(View{len: len as u32, offset: 0, ptr: ptr.map_addr(|a| a | FOREIGN_TAG)})
+}
+
+/// Increase reference count of the shared block.
+///
+/// # Safety
+///
+/// ptr must be payload pointer returned by alloc_block().
+#[inline]
+pub(crate) unsafe fn retain_block(ptr: *const u8) {
+    block_header(ptr).rc.fetch_add(1, AtomicOrdering::Relaxed);
+}
+
+/// Decrease reference count of the shared block,
+/// and deallocate it if this is the last reference.
+///
+/// # Safety
+///
+/// ptr must be payload pointer returned by alloc_block().
+#[inline]
+pub(crate) unsafe fn release_block(ptr: *const u8) {
+    let header = block_header(ptr);
+    if header.rc.fetch_sub(1, AtomicOrdering::Release) != 1 {
+        return
+    }
+    fence(AtomicOrdering::Acquire);
+    free_block(ptr);
+}
+
+/// Increase reference count of the shared block, without atomic
+/// read-modify-write.
+///
+/// # Safety
+///
+/// ptr must be payload pointer returned by alloc_block(), and all
+/// references to the block must be held on current thread.
+#[inline]
+pub(crate) unsafe fn retain_block_local(ptr: *const u8) {
+    let rc = &block_header(ptr).rc;
+    rc.store(rc.load(AtomicOrdering::Relaxed) + 1, AtomicOrdering::Relaxed);
+}
+
+/// Decrease reference count of the shared block, without atomic
+/// read-modify-write, and deallocate it if this is the last reference.
+///
+/// # Safety
+///
+/// ptr must be payload pointer returned by alloc_block(), and all
+/// references to the block must be held on current thread.
+#[inline]
+pub(crate) unsafe fn release_block_local(ptr: *const u8) {
+    let header = block_header(ptr);
+    let rc = header.rc.load(AtomicOrdering::Relaxed);
+    if rc != 1 {
+        header.rc.store(rc - 1, AtomicOrdering::Relaxed);
+        return
+    }
+    free_block(ptr);
+}
+
+/// Reallocate a uniquely owned shared block to new capacity,
+/// returns pointer to the new payload.
+///
+/// # Safety
+///
+/// ptr must be payload pointer returned by alloc_block() with reference
+/// count 1, and new_cap must be greater than 0.
+#[inline]
+pub(crate) unsafe fn realloc_block(ptr: *mut u8, new_cap: usize) -> *mut u8 {
+    match try_realloc_block(ptr, new_cap) {
+        Some(ptr) => ptr,
+        None => handle_alloc_error(block_layout(new_cap)),
+    }
+}
+
+/// Reallocate a uniquely owned shared block, same as realloc_block(),
+/// but returns None on allocation failure, in which case the block
+/// is left unchanged.
+///
+/// # Safety
+///
+/// ptr must be payload pointer returned by alloc_block() with reference
+/// count 1, and new_cap must be greater than 0.
+#[inline]
+pub(crate) unsafe fn try_realloc_block(ptr: *mut u8, new_cap: usize) -> Option<*mut u8> {
+    #[cfg(feature = "alloc-check")]
+    alloc_check::on_alloc(new_cap);
+    let old_layout = block_layout(block_cap(ptr));
+    let header = realloc(ptr.sub(BLOCK_HEADER), old_layout, block_layout(new_cap).size());
+    if header.is_null() {
+        return None
+    }
+    (*(header as *mut BlockHeader)).cap = new_cap;
+    Some(header.add(BLOCK_HEADER))
+}
+
+/// Construct SemiStr by taking over a uniquely owned shared block,
+/// whose first len bytes are initialized.
+/// Short string is copied inline and the block is released.
+///
+/// # Safety
+///
+/// ptr must be payload pointer returned by alloc_block() with reference
+/// count 1, the first len bytes must be valid utf-8 string, and len
+/// must be no more than MAX_LEN.
+#[inline]
+pub(crate) unsafe fn adopt_block(ptr: *const u8, len: usize) -> SemiStr {
+    let bytes = std::slice::from_raw_parts(ptr, len);
+    if len <= INLINE_CAP {
+        let res = inline_str(bytes);
+        release_block(ptr);
+        return res
+    }
+    let mut prefix = [0u8; 4];
+    prefix.copy_from_slice(&bytes[..4]);
+    SemiStr::from_heap(Heap{len: len as u32, prefix, ptr})
+}
+
+/// Returns start and end of the range, whose unbounded end is len.
+#[inline]
+pub(crate) fn range_bounds<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(n) => *n,
+        Bound::Excluded(n) => n.checked_add(1).expect("attempted to index slice from after maximum usize"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(n) => n.checked_add(1).expect("attempted to index slice up to maximum usize"),
+        Bound::Excluded(n) => *n,
+        Bound::Unbounded => len,
+    };
+    (start, end)
+}
+
+/// Encode length into the length field of SemiStr.
+///
+/// # Panics
+///
+/// Panics if length is u32::MAX.
+#[inline]
+pub(crate) const fn encode_len(len: u32) -> NonZeroU32 {
+    match NonZeroU32::new(!len) {
+        Some(len) => len,
+        None => panic!("string too long"),
+    }
+}
+
+/// Construct SemiStr with inline format.
+/// 
+/// # Safety
+/// 
+/// input bytes must be valid utf-8 string, unless the result is wrapped
+/// in SemiBytes, and length should be no more than 12.
+#[inline]
+pub(crate) unsafe fn inline_str(value: &[u8]) -> SemiStr {
+    let mut data = [0u8; INLINE_CAP];
+    data[..value.len()].copy_from_slice(value);
+    SemiStr::from_inline(Inline{len: value.len() as u32, data})
+}
+
+/// Construct SemiStr of given length, whose bytes are filled by f.
+/// Short string is built in inline buffer, otherwise in a single
+/// heap allocation.
+///
+/// # Panics
+///
+/// Panics if length is greater than MAX_LEN.
+///
+/// # Safety
+///
+/// f must fill the buffer with valid utf-8 string, unless the result
+/// is wrapped in SemiCStr.
+#[inline]
+pub(crate) unsafe fn build_str<F: FnOnce(&mut [u8])>(len: usize, f: F) -> SemiStr {
+    assert!(len <= MAX_LEN);
+    if len <= INLINE_CAP {
+        let mut data = [0u8; INLINE_CAP];
+        f(&mut data[..len]);
+        return SemiStr::from_inline(Inline{len: len as u32, data})
+    }
+    let ptr = alloc_block(len);
+    ptr.write_bytes(0, len);
+    let buf = std::slice::from_raw_parts_mut(ptr, len);
+    f(buf);
+    let mut prefix = [0u8; 4];
+    prefix.copy_from_slice(&buf[..4]);
+    SemiStr::from_heap(Heap{len: len as u32, prefix, ptr})
+}
+
+/// Encode chars into buffer, which must have exact length to hold them.
+#[inline]
+pub(crate) fn write_chars<I: Iterator<Item = char>>(buf: &mut [u8], iter: I) {
+    let mut pos = 0;
+    for ch in iter {
+        pos += ch.encode_utf8(&mut buf[pos..]).len();
+    }
+    debug_assert_eq!(pos, buf.len());
+}
+
+/// Case mapping of a single char.
+enum CaseMapped {
+    Upper(std::char::ToUppercase),
+    Lower(std::char::ToLowercase),
+}
+
+impl Iterator for CaseMapped {
+    type Item = char;
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        match self {
+            CaseMapped::Upper(it) => it.next(),
+            CaseMapped::Lower(it) => it.next(),
+        }
+    }
+}
+
+/// Split string into words separated by whitespaces, with their
+/// byte positions.
+#[inline]
+fn split_words(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    s.split(char::is_whitespace)
+        .filter(|w| !w.is_empty())
+        .map(move |w| (w.as_ptr() as usize - s.as_ptr() as usize, w))
+}
+
+/// Returns the largest char boundary of the string no greater than
+/// given index.
+#[inline]
+fn floor_char_boundary(s: &str, idx: usize) -> usize {
+    if idx >= s.len() {
+        return s.len()
+    }
+    // a char is at most 4 bytes.
+    (idx.saturating_sub(3)..=idx).rev().find(|&i| s.is_char_boundary(i)).unwrap()
+}
+
+/// Returns display width of the string, in columns.
+#[cfg(feature = "unicode-width")]
+#[inline]
+fn display_width(s: &str) -> usize {
+    unicode_width::UnicodeWidthStr::width(s)
+}
+
+/// Returns display width of the string, in chars.
+#[cfg(not(feature = "unicode-width"))]
+#[inline]
+fn display_width(s: &str) -> usize {
+    s.chars().count()
+}
+
+/// Returns byte length and width of the longest prefix no wider than
+/// given width, which contains at least one char.
+#[inline]
+fn cut_width(s: &str, width: usize) -> (usize, usize) {
+    let mut len = 0;
+    let mut w = 0;
+    for ch in s.chars() {
+        let cw = display_width(ch.encode_utf8(&mut [0u8; 4]));
+        if w + cw > width && len > 0 {
+            break
+        }
+        len += ch.len_utf8();
+        w += cw;
+    }
+    (len, w)
+}
+
+/// Construct SemiStr with heap format.
+/// 
+/// # Safety
+/// 
+/// input bytes must be valid utf-8 string, unless the result is wrapped
+/// in SemiBytes, and length should be between 13 and MAX_LEN.
+#[inline]
+pub(crate) unsafe fn heap_str(value: &[u8]) -> SemiStr {
+    let mut prefix = [0u8; 4];
+    prefix.copy_from_slice(&value[..4]);
+    let ptr = alloc_block(value.len());
+    std::ptr::copy_nonoverlapping(value.as_ptr(), ptr, value.len());
+    SemiStr::from_heap(Heap{len: value.len() as u32, prefix, ptr})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semi_str() {
+        let s1 = SemiStr::new("hello");
+        assert!(!s1.is_empty());
+        assert_eq!(5, s1.len());
+        assert_eq!(s1, "hello");
+        assert_eq!("hello", s1);
+        let s2 = SemiStr::inline("world");
+        assert!(s2 > s1);
+        let s3 = SemiStr::new("a little longer than 12 bytes");
+        let s4 = SemiStr::from_iter(b"a little longer than 12 bytes".iter().map(|b| *b as char));
+        assert_eq!(s3, s4);
+        let s5 = SemiStr::from_iter("short str".chars());
+        assert_eq!(s5.len(), 9);
+        println!("{:?}, {}", s5, s5);
+        let s6 = SemiStr::default();
+        assert!(s6.is_empty());
+        let s7 = SemiStr::new("");
+        assert_eq!(s6, s7);
+        assert!(SemiStr::try_from(&[0u8, 0xff, 0xff, 0xff][..]).is_err());
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SemiStr>();
+    }
+
+    #[test]
+    fn test_from_str() {
+        let s: SemiStr = "abc".parse().unwrap();
+        assert_eq!(s, "abc");
+        let s = "a string parsed onto heap".parse::<SemiStr>().unwrap();
+        assert_eq!(s, "a string parsed onto heap");
+    }
+
+    #[test]
+    fn test_from_char() {
+        for ch in ['a', 'é', '中', '🦀'] {
+            let s = SemiStr::from(ch);
+            assert_eq!(s.kind(), Kind::Inline);
+            assert_eq!(s, ch.to_string().as_str());
+        }
+    }
+
+    #[test]
+    fn test_from_str_iter() {
+        let parts = ["SELECT", " * ", "FROM", " t"];
+        let s: SemiStr = parts.iter().copied().collect();
+        assert_eq!(s, "SELECT * FROM t");
+        assert_eq!(s.kind(), Kind::Heap);
+        let s: SemiStr = parts[..2].iter().map(|p| p.to_string()).collect();
+        assert_eq!(s, "SELECT * ");
+        assert_eq!(s.kind(), Kind::Inline);
+        let s: SemiStr = [Cow::Borrowed("a"), Cow::Owned("b".repeat(20))].into_iter().collect();
+        assert_eq!(s.len(), 21);
+        let s: SemiStr = std::iter::empty::<&str>().collect();
+        assert!(s.is_empty());
+        assert!(SemiStr::try_from_iter(["x"; 3]).is_ok());
+        let mb = "x".repeat(1 << 20);
+        let res = SemiStr::try_from_iter(std::iter::repeat_n(mb.as_str(), 4097));
+        assert!(matches!(res, Err(Error::StringTooLong(_))));
+    }
+
+    #[test]
+    fn test_from_semistr_iter() {
+        let long = SemiStr::new("a token longer than inline capacity");
+        let s: SemiStr = [SemiStr::default(), long.clone(), SemiStr::default()].into_iter().collect();
+        assert_eq!(s.payload_ptr(), long.payload_ptr());
+        let s: SemiStr = [SemiStr::default(), SemiStr::default()].into_iter().collect();
+        assert!(s.is_empty());
+        let s: SemiStr = ["a", "b", "c"].into_iter().map(SemiStr::new).chain([long]).collect();
+        assert_eq!(s, "abca token longer than inline capacity");
+    }
+
+    #[test]
+    fn test_concat_join() {
+        assert_eq!(SemiStr::concat(&["ab", "cd"]).unwrap(), "abcd");
+        let cols = [SemiStr::new("customer_id"), SemiStr::new("order_date")];
+        assert_eq!(SemiStr::concat(&cols).unwrap(), "customer_idorder_date");
+        let s = SemiStr::join(", ", &cols).unwrap();
+        assert_eq!(s, "customer_id, order_date");
+        assert_eq!(s.kind(), Kind::Heap);
+        assert_eq!(SemiStr::join(", ", ["one"]).unwrap(), "one");
+        assert!(SemiStr::join(", ", Vec::<&str>::new()).unwrap().is_empty());
+        let mb = "x".repeat(1 << 20);
+        let res = SemiStr::join(&mb, ["a"; 4098]);
+        assert!(matches!(res, Err(Error::StringTooLong(_))));
+    }
+
+    #[test]
+    fn test_add() {
+        let s = SemiStr::new("SELECT") + " * FROM t" + ';';
+        assert_eq!(s, "SELECT * FROM t;");
+        let long = SemiStr::new("a string longer than 12");
+        let s = SemiStr::default() + long.clone();
+        assert_eq!(s.payload_ptr(), long.payload_ptr());
+        let s = s + "";
+        assert_eq!(s.payload_ptr(), long.payload_ptr());
+        assert_eq!(SemiStr::new("ab") + &SemiStr::new("cd"), "abcd");
+    }
+
+    #[test]
+    fn test_repeat() {
+        let s = SemiStr::new("ab");
+        assert_eq!(s.repeat(3).unwrap(), "ababab");
+        assert_eq!(s.repeat(0).unwrap(), "");
+        let dashes = SemiStr::new("-").repeat(41).unwrap();
+        assert_eq!(dashes.as_str(), "-".repeat(41));
+        let long = SemiStr::new("a string longer than 12");
+        assert_eq!(long.repeat(1).unwrap().payload_ptr(), long.payload_ptr());
+        assert_eq!(long.repeat(3).unwrap().as_str(), long.as_str().repeat(3));
+        assert!(matches!(long.repeat(usize::MAX), Err(Error::StringTooLong(_))));
+    }
+
+    #[test]
+    fn test_replace() {
+        let s = SemiStr::new("select * from t where a = 1 and b = 1");
+        assert_eq!(s.replace_shared("1", "42").unwrap(), "select * from t where a = 42 and b = 42");
+        assert_eq!(s.replacen_shared("1", "2", 1).unwrap(), "select * from t where a = 2 and b = 1");
+        assert_eq!(s.replace_shared(" = 1", "").unwrap(), "select * from t where a and b");
+        assert_eq!(s.replace_shared("missing", "x").unwrap().payload_ptr(), s.payload_ptr());
+        assert_eq!(s.replacen_shared("1", "2", 0).unwrap().payload_ptr(), s.payload_ptr());
+        assert_eq!(SemiStr::new("héllo").replace_shared("", "-").unwrap(), "héllo".replace("", "-"));
+        let short = SemiStr::new("a,b,c").replace_shared(",", "").unwrap();
+        assert_eq!(short, "abc");
+        assert!(short.is_inline());
+        let owned: String = s.replace('1', "2");
+        assert_eq!(owned, "select * from t where a = 2 and b = 2");
+    }
+
+    #[test]
+    fn test_truncated() {
+        let s = SemiStr::new("héllo wörld, a long message");
+        assert_eq!(s.truncated(100).payload_ptr(), s.payload_ptr());
+        assert_eq!(s.truncated(2), "h");
+        assert_eq!(s.truncated(3), "hé");
+        assert_eq!(s.truncated(0), "");
+        let t = s.truncated(20);
+        assert_eq!(t, "héllo wörld, a lon");
+        assert_eq!(t.payload_ptr(), s.payload_ptr());
+        assert_eq!(s.truncated_with(100, "…").payload_ptr(), s.payload_ptr());
+        assert_eq!(s.truncated_with(5, "…"), "h…");
+        assert_eq!(s.truncated_with(6, "…"), "hé…");
+        assert_eq!(s.truncated_with(16, "…"), "héllo wörld…");
+        assert!(s.truncated_with(16, "…").len() <= 16);
+        assert_eq!(s.truncated_with(2, "…"), "h");
+        assert_eq!(SemiStr::new("ab").truncated_with(2, "…"), "ab");
+    }
+
+    #[test]
+    fn test_validate_utf8() {
+        let text = "ascii prefix long enough for simd, then héllo wörld 𝄞";
+        assert_eq!(validate_utf8(text.as_bytes()).unwrap(), text);
+        assert!(matches!(validate_utf8(b"ascii prefix long enough for simd \xc3"), Err(Error::InvalidUtf8String)));
+        assert!(matches!(SemiStr::try_from(&b"\xed\xa0\x80"[..]), Err(Error::InvalidUtf8String)));
+    }
+
+    #[test]
+    fn test_try_new() {
+        let s = SemiStr::try_new("short").unwrap();
+        assert!(s.is_inline());
+        let s = SemiStr::try_new("a string longer than 12").unwrap();
+        assert_eq!(s, "a string longer than 12");
+        assert_eq!(s.ref_count(), Some(1));
+    }
+
+    #[test]
+    fn test_option_niche() {
+        assert_eq!(size_of::<Option<SemiStr>>(), size_of::<SemiStr>());
+        let strs = [Some(SemiStr::new("short")), None, Some(SemiStr::new("a string longer than 12"))];
+        assert_eq!(strs[0].as_deref(), Some("short"));
+        assert!(strs[1].is_none());
+        assert_eq!(strs[2].clone().unwrap().ref_count(), Some(2));
+        assert_eq!(SemiStr::default().len(), 0);
+        assert_eq!(encode_len(0).get(), u32::MAX);
+    }
+
+    #[test]
+    fn test_const_inline() {
+        const KEY: SemiStr = SemiStr::const_inline("key");
+        static EMPTY: SemiStr = SemiStr::const_inline("");
+        assert_eq!(KEY, "key");
+        assert_eq!(KEY.kind(), Kind::Inline);
+        assert!(EMPTY.is_empty());
+        assert_eq!(SemiStr::const_inline("twelve bytes"), SemiStr::inline("twelve bytes"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_const_inline_too_long() {
+        SemiStr::const_inline("thirteen byte");
+    }
+
+    #[test]
+    fn test_try_from_box() {
+        let b: Box<str> = "a boxed string longer than 12".into();
+        let ptr = b.as_ptr();
+        let s = SemiStr::try_from(b).unwrap();
+        assert_eq!(s, "a boxed string longer than 12");
+        assert_eq!(s.kind(), Kind::Foreign);
+        assert_eq!(s.payload_ptr(), ptr);
+        let b: Box<[u8]> = b"boxed".to_vec().into_boxed_slice();
+        assert_eq!(SemiStr::try_from(b).unwrap(), "boxed");
+        let b: Box<[u8]> = b"boxed bytes longer than 12".to_vec().into_boxed_slice();
+        let ptr = b.as_ptr();
+        let s = SemiStr::try_from(b).unwrap();
+        assert_eq!(s, "boxed bytes longer than 12");
+        assert_eq!(s.payload_ptr(), ptr);
+        let b: Box<[u8]> = vec![0xff, 0xfe].into_boxed_slice();
+        assert!(matches!(SemiStr::try_from(b), Err(Error::InvalidUtf8String)));
+    }
+
+    #[test]
+    fn test_try_from_vec() {
+        let v = b"bytes of a long string".to_vec();
+        let ptr = v.as_ptr();
+        let s = SemiStr::try_from(v).unwrap();
+        assert_eq!(s, "bytes of a long string");
+        assert_eq!(s.kind(), Kind::Foreign);
+        assert_eq!(s.payload_ptr(), ptr);
+        let v = vec![b'a', 0xff, b'b'];
+        let ptr = v.as_ptr();
+        let err = SemiStr::try_from(v).unwrap_err();
+        assert!(matches!(err.error(), Error::InvalidUtf8String));
+        assert_eq!(err.as_bytes(), &[b'a', 0xff, b'b']);
+        let bytes = err.into_bytes();
+        assert_eq!(bytes.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_into_string() {
+        assert_eq!(SemiStr::new("short").into_string(), "short");
+        let s: String = SemiStr::new("a string longer than 12").into();
+        assert_eq!(s, "a string longer than 12");
+        let mut owned = String::with_capacity(64);
+        owned.push_str("an adopted string value");
+        let ptr = owned.as_ptr();
+        let s = SemiStr::try_from(owned).unwrap();
+        let shared = s.clone();
+        let copied = s.into_string();
+        assert_ne!(copied.as_ptr(), ptr);
+        let part = shared.slice(..17);
+        drop(shared);
+        let back = part.into_string();
+        assert_eq!(back, "an adopted string");
+        assert_eq!(back.as_ptr(), ptr);
+        assert_eq!(back.capacity(), 64);
+        let v = b"adopted bytes longer than 12".to_vec();
+        let ptr = v.as_ptr();
+        let back = SemiStr::try_from(v).unwrap().into_string();
+        assert_eq!(back, "adopted bytes longer than 12");
+        assert_eq!(back.as_ptr(), ptr);
+        let s = SemiStr::try_from(Box::<str>::from("an adopted boxed string")).unwrap();
+        let ptr = s.payload_ptr();
+        let copied = s.slice(1..).into_string();
+        assert_ne!(copied.as_ptr(), ptr);
+        let back = s.into_string();
+        assert_eq!(back.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_cow_conversions() {
+        let s = SemiStr::try_from(Cow::Borrowed("borrowed")).unwrap();
+        assert_eq!(s, "borrowed");
+        let s = SemiStr::try_from(Cow::<str>::Owned("an owned string value".to_string())).unwrap();
+        let c: Cow<str> = (&s).into();
+        assert!(matches!(c, Cow::Borrowed(b) if b.as_ptr() == s.payload_ptr()));
+    }
+
+    #[test]
+    fn test_cmp_std_strings() {
+        let s = SemiStr::new("a string longer than 12");
+        assert_eq!(s, String::from("a string longer than 12"));
+        assert_eq!(String::from("a string longer than 12"), s);
+        assert_eq!(s, Cow::Borrowed("a string longer than 12"));
+        assert_ne!(Cow::<str>::Owned("other".into()), s);
+        assert!(s < "b");
+        assert!("a" < s);
+        assert!(*"a string" < s);
+        assert!(s > *"a");
+        assert!(SemiStr::new("abc") <= "abc");
+    }
+
+    #[test]
+    fn test_as_ref() {
+        fn bytes<B: AsRef<[u8]>>(b: B) -> usize {
+            b.as_ref().len()
+        }
+        let s = SemiStr::new("dir/file.txt");
+        assert_eq!(bytes(&s), 12);
+        let p: &Path = s.as_ref();
+        assert_eq!(p.extension(), Some(OsStr::new("txt")));
+        let os: &OsStr = s.as_ref();
+        assert_eq!(os, "dir/file.txt");
+        let cmd = std::process::Command::new("echo").arg(&s).get_args().count();
+        assert_eq!(cmd, 1);
+    }
+
+    #[test]
+    fn test_prefix() {
+        let s = SemiStr::new("customer_id_primary");
+        assert_eq!(s.prefix(), *b"cust");
+        assert_eq!(SemiStr::new("ab").prefix(), *b"ab\0\0");
+        assert!(s.starts_with_prefix("cus"));
+        assert!(s.starts_with_prefix("customer_"));
+        assert!(!s.starts_with_prefix("customer-"));
+        assert!(!s.starts_with_prefix("orders"));
+        assert!(s.starts_with_prefix(""));
+        assert!(!SemiStr::new("ab").starts_with_prefix("abc"));
+        assert!(s.starts_with('c') && s.starts_with(char::is_alphabetic));
+        assert_eq!(s.common_prefix_len(&SemiStr::new("customer_name")), 9);
+        assert_eq!(s.common_prefix_len(&SemiStr::new("cu")), 2);
+        assert_eq!(SemiStr::new("héllo").common_prefix_len(&SemiStr::new("hèllo")), 1);
+    }
+
+    #[test]
+    fn test_eq_ignore_ascii_case() {
+        let s = SemiStr::new("Content-Type");
+        assert!(s.eq_ignore_ascii_case("content-type"));
+        assert!(s.eq_ignore_ascii_case("CONTENT-TYPE"));
+        assert!(!s.eq_ignore_ascii_case("content-typo"));
+        assert!(!s.eq_ignore_ascii_case("content"));
+        let s = SemiStr::new("X-Forwarded-For-Header");
+        assert!(s.eq_ignore_ascii_case("x-forwarded-for-header"));
+        assert!(!s.eq_ignore_ascii_case("y-forwarded-for-header"));
+        assert!(SemiStr::new("É").eq_ignore_ascii_case("É"));
+        assert!(!SemiStr::new("É").eq_ignore_ascii_case("é"));
+    }
+
+    #[test]
+    fn test_introspection() {
+        let s = SemiStr::new("short");
+        assert!(s.is_inline() && !s.is_heap());
+        assert_eq!(s.ref_count(), None);
+        assert_eq!(s.heap_size_bytes(), 0);
+        let s = SemiStr::new("a string longer than 12");
+        assert!(s.is_heap() && !s.is_inline());
+        assert_eq!(s.ref_count(), Some(1));
+        assert_eq!(s.heap_size_bytes(), BLOCK_HEADER + 23);
+        let v = s.slice(2..20);
+        assert!(v.is_heap());
+        assert_eq!(s.ref_count(), Some(2));
+        assert_eq!(v.heap_size_bytes(), s.heap_size_bytes());
+        drop(v);
+        assert_eq!(s.ref_count(), Some(1));
+        let s = SemiStr::from_static("a static string longer than 12");
+        assert!(s.is_static() && !s.is_heap());
+        assert_eq!(s.ref_count(), None);
+        assert_eq!(s.heap_size_bytes(), 0);
+    }
+
+    #[test]
+    fn test_ptr_eq() {
+        let s = SemiStr::new("a string longer than 12");
+        assert!(s.ptr_eq(&s.clone()));
+        assert!(!s.ptr_eq(&SemiStr::new("a string longer than 12")));
+        assert!(s.slice(2..).ptr_eq(&s.slice(2..)));
+        assert!(!s.slice(2..).ptr_eq(&s.slice(2..22)));
+        assert!(SemiStr::new("short").ptr_eq(&SemiStr::new("short")));
+        assert!(!SemiStr::new("short").ptr_eq(&SemiStr::new("shirt")));
+    }
+
+    #[test]
+    fn test_leak() {
+        let s = SemiStr::new("a string longer than 12");
+        let ptr = s.payload_ptr();
+        let leaked: &'static str = s.clone().leak();
+        assert_eq!(leaked.as_ptr(), ptr);
+        assert_eq!(s.ref_count(), Some(2));
+        drop(s);
+        assert_eq!(leaked, "a string longer than 12");
+        assert_eq!(SemiStr::new("short").leak(), "short");
+        static TEXT: &str = "a static string longer than 12";
+        assert_eq!(SemiStr::from_static(TEXT).leak().as_ptr(), TEXT.as_ptr());
+    }
+
+    #[test]
+    fn test_raw_parts() {
+        let long = SemiStr::new("a string longer than 12");
+        let owned = SemiStr::try_from(String::from("an owned string longer than 12")).unwrap();
+        let strs = [SemiStr::new("short"), long.clone(), long.slice(2..20), SemiStr::from_static("a static string longer than 12"), owned];
+        for (i, s) in strs.into_iter().enumerate() {
+            let expected = s.as_str().to_string();
+            let ptr = s.payload_ptr();
+            let (tag, len, prefix, raw) = s.into_raw_parts();
+            assert_eq!(tag as usize, i);
+            assert_eq!(len as usize, expected.len());
+            let s = unsafe { SemiStr::from_raw_parts(tag, len, prefix, raw) };
+            assert_eq!(s, expected.as_str());
+            if i > 0 {
+                assert_eq!(s.payload_ptr(), ptr);
+            }
+        }
+        assert_eq!(long.ref_count(), Some(1));
+    }
+
+    #[test]
+    fn test_foreign_string() {
+        let owned = String::from("an owned string longer than 12");
+        let ptr = owned.as_ptr();
+        let s = SemiStr::try_from(owned).unwrap();
+        assert_eq!(s.kind(), Kind::Foreign);
+        assert_eq!(s, "an owned string longer than 12");
+        assert_eq!(s.payload_ptr(), ptr);
+        assert_eq!(s.prefix(), *b"an o");
+        assert!(s.is_heap());
+        let v = s.slice(3..20);
+        assert_eq!(v.kind(), Kind::Foreign);
+        assert_eq!(v, "owned string long");
+        assert_eq!(v.payload_ptr(), ptr.wrapping_add(3));
+        assert_eq!(v.slice(6..), "string long");
+        assert_eq!(s.clone().payload_ptr(), ptr);
+        assert_eq!(s.ref_count(), Some(2));
+        assert_eq!(&*s.as_buffer().unwrap(), s.as_bytes());
+        assert_eq!(crate::kernel::extract_prefixes(&[v.clone(), v.clone(), v.clone(), v.clone()])[3], u32::from_be_bytes(*b"owne"));
+        let mut m = s.clone();
+        assert!(m.get_mut().is_none());
+        m.make_mut().make_ascii_uppercase();
+        assert_eq!(m, "AN OWNED STRING LONGER THAN 12");
+        assert_ne!(m.kind(), Kind::Foreign);
+        assert_eq!(s, "an owned string longer than 12");
+        drop(s);
+        assert_eq!(v, "owned string long");
+        assert_eq!(SemiStr::try_from(String::from("short")).unwrap().kind(), Kind::Inline);
+        let mut spare = String::with_capacity(1024);
+        spare.push_str("a string with spare capacity");
+        let mut s = SemiStr::try_from(spare).unwrap();
+        assert!(s.heap_size_bytes() > 1024);
+        s.compact();
+        assert_eq!(s.kind(), Kind::Heap);
+        assert_eq!(s, "a string with spare capacity");
+    }
+
+    #[test]
+    fn test_make_mut() {
+        let mut s = SemiStr::new("short");
+        s.make_mut().make_ascii_uppercase();
+        assert_eq!(s, "SHORT");
+        let mut s = SemiStr::new("a string longer than 12");
+        let ptr = s.payload_ptr();
+        s.make_mut().make_ascii_uppercase();
+        assert_eq!(s.payload_ptr(), ptr);
+        assert_eq!(s, "A STRING LONGER THAN 12");
+        assert_eq!(s.prefix(), *b"A ST");
+        let shared = s.clone();
+        assert!(s.get_mut().is_none());
+        s.make_mut().make_ascii_lowercase();
+        assert_ne!(s.payload_ptr(), ptr);
+        assert_eq!(s, "a string longer than 12");
+        assert_eq!(shared, "A STRING LONGER THAN 12");
+        let mut v = shared.slice(2..);
+        drop(shared);
+        v.get_mut().unwrap().make_ascii_lowercase();
+        assert_eq!(v, "string longer than 12");
+        let mut st = SemiStr::from_static("a static string longer than 12");
+        assert!(st.get_mut().is_none());
+        st.make_mut().make_ascii_uppercase();
+        assert_eq!(st, "A STATIC STRING LONGER THAN 12");
+    }
+
+    #[test]
+    fn test_ascii_case() {
+        let s = SemiStr::new("Content-Type");
+        assert_eq!(s.to_ascii_lowercase(), "content-type");
+        assert_eq!(s.to_ascii_uppercase(), "CONTENT-TYPE");
+        let s = SemiStr::new("X-Forwarded-For-Ünïcode");
+        let lower = s.to_ascii_lowercase();
+        assert_eq!(lower, "x-forwarded-for-Ünïcode");
+        assert!(lower.is_heap());
+        let unchanged = lower.to_ascii_lowercase();
+        assert!(unchanged.ptr_eq(&lower));
+    }
+
+    #[test]
+    fn test_unicode_case() {
+        for text in ["Hello", "Straße", "ÉCOLE normale supérieure", "ὈΔΥΣΣΕΎΣ", "İstanbul", "ﬁ", "日本語"] {
+            let s = SemiStr::new(text);
+            assert_eq!(s.to_lowercase(), text.to_lowercase().as_str(), "{}", text);
+            assert_eq!(s.to_uppercase(), text.to_uppercase().as_str(), "{}", text);
+        }
+        let s = SemiStr::new("straße");
+        assert_eq!(s.to_uppercase(), "STRASSE");
+        assert!(s.to_uppercase().is_inline());
+        let s = SemiStr::new("déjà vu, already lowercase");
+        assert!(s.to_lowercase().ptr_eq(&s));
+    }
+
+    #[test]
+    fn test_trim() {
+        let s = SemiStr::new("  \ta padded field value \n");
+        let t = s.trim();
+        assert_eq!(t, "a padded field value");
+        assert_eq!(t.payload_ptr(), unsafe { s.payload_ptr().add(3) });
+        assert_eq!(s.trim_start(), "a padded field value \n");
+        assert_eq!(s.trim_end(), "  \ta padded field value");
+        let s = SemiStr::new("\"quoted string value\"");
+        assert_eq!(s.trim_matches(|c| c == '"'), "quoted string value");
+        assert_eq!(s.trim_start_matches(|c| c == '"'), "quoted string value\"");
+        assert_eq!(s.trim_end_matches(|c| c == '"'), "\"quoted string value");
+        assert_eq!(SemiStr::new("   ").trim(), "");
+    }
+
+    #[test]
+    fn test_ord() {
+        let strs = ["", "a", "a\0", "a\0\0\0\0", "ab", "abcd", "abcde", "abcdefghijklmnopq",
+            "abcdefghijklmnopr", "b", "\u{ff}", "é", "zz", "a string longer than 12"];
+        for a in strs {
+            for b in strs {
+                assert_eq!(SemiStr::new(a).cmp(&SemiStr::new(b)), a.cmp(b), "{:?} vs {:?}", a, b);
+            }
+        }
+        let long = SemiStr::new("a parent string sliced into views");
+        let (v1, v2) = (long.slice(2..20), long.slice(9..30));
+        assert_eq!(v1.cmp(&v2), v1.as_str().cmp(v2.as_str()));
+    }
+
+    #[test]
+    fn test_arc_conversions() {
+        let a: Arc<str> = Arc::from("an interned string longer than 12");
+        let s = SemiStr::try_from(a.clone()).unwrap();
+        assert_eq!(s, &*a);
+        assert_eq!(s.kind(), Kind::Foreign);
+        assert_eq!(s.payload_ptr(), a.as_ptr());
+        let back: Arc<str> = s.clone().into();
+        assert!(Arc::ptr_eq(&back, &a));
+        let sub: Arc<str> = s.slice(3..).into();
+        assert_eq!(&*sub, &a[3..]);
+        let bytes: Arc<[u8]> = s.into();
+        assert_eq!(bytes.as_ptr(), a.as_ptr());
+        let s = SemiStr::try_from(bytes.clone()).unwrap();
+        assert_eq!(s, &*a);
+        let back: Arc<[u8]> = s.into();
+        assert!(Arc::ptr_eq(&back, &bytes));
+        let bad: Arc<[u8]> = Arc::from(&[0xffu8][..]);
+        assert!(SemiStr::try_from(bad).is_err());
+    }
+
+    #[test]
+    fn test_raw_accessors() {
+        let s1 = SemiStr::new("ab");
+        assert_eq!(&s1.header_bytes()[..4], &2u32.to_ne_bytes());
+        assert_eq!(&s1.header_bytes()[4..], b"ab\0\0");
+        let s2 = SemiStr::new("hello, world!!");
+        assert_eq!(&s2.header_bytes()[..4], &14u32.to_ne_bytes());
+        assert_eq!(&s2.header_bytes()[4..], b"hell");
+        let payload = unsafe { std::slice::from_raw_parts(s2.payload_ptr(), s2.len()) };
+        assert_eq!(payload, b"hello, world!!");
+        assert_eq!(s1.payload_ptr(), s1.as_str().as_ptr());
+    }
+
+    #[test]
+    fn test_clone() {
+        let s1 = SemiStr::new("a heap string shared by clones");
+        let s2 = s1.clone();
+        assert_eq!(s2.kind(), Kind::Heap);
+        assert_eq!(s2.payload_ptr(), s1.payload_ptr());
+        let v1 = unsafe { s1.slice_shared(2, 20) };
+        drop(s1);
+        let v2 = v1.clone();
+        drop(v1);
+        assert_eq!(v2.kind(), Kind::View);
+        assert_eq!(v2.payload_ptr(), unsafe { s2.payload_ptr().add(2) });
+        assert_eq!(v2, "heap string shared");
+        assert_eq!(SemiStr::new("short").clone(), "short");
+    }
+
+    #[test]
+    fn test_compact() {
+        let parent = SemiStr::new("a long parent string, which is shared by views");
+        let mut v1 = unsafe { parent.slice_shared(2, 20) };
+        assert_eq!(v1, "long parent string");
+        assert_eq!(v1.kind(), Kind::View);
+        assert_eq!(v1.header_bytes()[4..], *b"long");
+        let v2 = unsafe { v1.slice_shared(5, 11) };
+        assert_eq!(v2.kind(), Kind::Inline);
+        assert_eq!(v2, "parent");
+        let v3 = unsafe { parent.slice_shared(0, parent.len()) };
+        assert_eq!(v3.kind(), Kind::Heap);
+        assert_eq!(v3, parent);
+        let mut v4 = unsafe { parent.slice_shared(2, 40) };
+        drop(parent);
+        v4.compact();
+        assert_eq!(v4.kind(), Kind::View);
+        SemiStr::compact_all(std::slice::from_mut(&mut v1));
+        assert_eq!(v1.kind(), Kind::Heap);
+        assert_eq!(v1, "long parent string");
+        assert_eq!(v4, "long parent string, which is shared by");
+    }
+
+    #[test]
+    fn test_escape() {
+        let s1 = SemiStr::new("tab\there");
+        assert_eq!(s1.escape_debug(), "tab\\there");
+        assert_eq!(s1.escape_default(), "tab\\there");
+        let s2 = SemiStr::new("a \"quoted\" string with ünicode");
+        assert_eq!(s2.escape_debug(), s2.as_str().escape_debug().to_string().as_str());
+        assert_eq!(s2.escape_default(), s2.as_str().escape_default().to_string().as_str());
+        let s3 = SemiStr::new("nothing to escape here");
+        let e3 = s3.escape_debug();
+        assert_eq!(e3, s3);
+        assert_eq!(e3.payload_ptr(), s3.payload_ptr());
+    }
+
+    #[test]
+    fn test_split_at() {
+        let s = SemiStr::new("key_prefix/some long suffix");
+        let (a, b) = s.split_at(11);
+        assert_eq!(a, "key_prefix/");
+        assert_eq!(a.kind(), Kind::Inline);
+        assert_eq!(b, "some long suffix");
+        assert_eq!(b.kind(), Kind::View);
+        let (c, d) = b.split_at(0);
+        assert!(c.is_empty());
+        assert_eq!(d, b);
+        let (e, f) = SemiStr::new("héllo").split_at(3);
+        assert_eq!((e.as_str(), f.as_str()), ("hé", "llo"));
+    }
+
+    #[test]
+    fn test_case_helpers() {
+        let s = SemiStr::new("hello world");
+        assert_eq!(s.capitalized(), "Hello world");
+        assert_eq!(s.title_case(), "Hello World");
+        assert_eq!(SemiStr::new("mIXED  case").title_case(), "Mixed  Case");
+        assert_eq!(SemiStr::default().capitalized(), "");
+        let s = SemiStr::new("élan vital of the long string");
+        assert_eq!(s.capitalized(), "Élan vital of the long string");
+        assert_eq!(s.title_case(), "Élan Vital Of The Long String");
+        let s = SemiStr::new("Already Titled Long String");
+        assert_eq!(s.capitalized().payload_ptr(), s.payload_ptr());
+        assert_eq!(s.title_case().payload_ptr(), s.payload_ptr());
+        assert_eq!(SemiStr::new("ßtraße").capitalized(), "SStraße");
+    }
+
+    #[test]
+    fn test_reversed() {
+        assert_eq!(SemiStr::new("héllo").reversed(), "olléh");
+        assert_eq!(SemiStr::new("a longer string to reverse").reversed(), "esrever ot gnirts regnol a");
+        assert_eq!(SemiStr::default().reversed(), "");
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn test_reversed_graphemes() {
+        // 'e' followed by combining acute accent.
+        let s = SemiStr::new("cafe\u{301} and te\u{301}a");
+        assert_eq!(s.reversed_graphemes(), "ae\u{301}t dna e\u{301}fac");
+    }
+
+    #[test]
+    fn test_common_prefix_of() {
+        let mut strs: Vec<SemiStr> = ["users/2024/alice/profile", "users/2024/bob", "users/2024/alicia/settings"]
+            .into_iter()
+            .map(SemiStr::new)
+            .collect();
+        strs.sort();
+        let p = SemiStr::common_prefix_of(&strs);
+        assert_eq!(p, "users/2024/");
+        let strs = [SemiStr::new("a long shared prefix here"), SemiStr::new("a long shared prefix there")];
+        let p = SemiStr::common_prefix_of(&strs);
+        assert_eq!(p, "a long shared prefix ");
+        assert_eq!(p.payload_ptr(), strs[0].payload_ptr());
+        assert_eq!(SemiStr::common_prefix_of(&[SemiStr::new("ab"), SemiStr::new("abc")]), "ab");
+        assert_eq!(SemiStr::common_prefix_of(&[SemiStr::new("héllo"), SemiStr::new("hëllo")]), "h");
+        assert_eq!(SemiStr::common_prefix_of(&[SemiStr::new("apple"), SemiStr::new("banana")]), "");
+        assert_eq!(SemiStr::common_prefix_of(&[]), "");
+    }
+
+    #[test]
+    fn test_wrap() {
+        let s = SemiStr::new("the quick brown fox jumps over the lazy dog");
+        let lines = s.wrap(15);
+        assert_eq!(lines, vec!["the quick brown", "fox jumps over", "the lazy dog"]);
+        assert_eq!(lines[0].payload_ptr(), s.payload_ptr());
+        let s = SemiStr::new("first line\n\r\nsupercalifragilistic word");
+        assert_eq!(s.wrap(8), vec!["first", "line", "", "supercal", "ifragili", "stic", "word"]);
+        assert_eq!(SemiStr::new("héllo wörld").wrap(5), vec!["héllo", "wörld"]);
+        assert!(SemiStr::default().wrap(10).is_empty());
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn test_wrap_display_width() {
+        let s = SemiStr::new("漢字の テキスト");
+        assert_eq!(s.wrap(6), vec!["漢字の", "テキス", "ト"]);
+    }
+
+    #[test]
+    fn test_from_static() {
+        static TEXT: &str = "0123456789, a static string of some length";
+        for i in 0..4 {
+            let s = SemiStr::from_static(&TEXT[i..]);
+            assert_eq!(s.kind(), Kind::Static);
+            assert_eq!(s, &TEXT[i..]);
+            assert_eq!(s.payload_ptr(), TEXT[i..].as_ptr());
+            assert_eq!(s.prefix_bytes(), TEXT.as_bytes()[i..i + 4]);
+            let c = s.clone();
+            let v = c.slice(12..);
+            drop(s);
+            assert_eq!(v.kind(), Kind::Static);
+            assert_eq!(v.payload_ptr(), TEXT[i + 12..].as_ptr());
+            let mut v = v.slice(1..);
+            v.compact();
+            assert_eq!(v, &TEXT[i + 13..]);
+            assert_eq!(v.kind(), Kind::Static);
+        }
+        assert_eq!(SemiStr::from_static("short").kind(), Kind::Inline);
+    }
+
+    #[test]
+    fn test_slice() {
+        let s = SemiStr::new("field1,field2 is a long one,f3");
+        let v = s.slice(7..27);
+        assert_eq!(v, "field2 is a long one");
+        assert_eq!(v.kind(), Kind::View);
+        assert_eq!(v.payload_ptr(), unsafe { s.payload_ptr().add(7) });
+        assert_eq!(s.slice(..6), "field1");
+        assert_eq!(s.slice(28..), "f3");
+        assert_eq!(s.slice(..=4), "field");
+        assert_eq!(s.slice(..), s);
+        let s = SemiStr::new("héllo wörld, this is long");
+        assert_eq!(s.substr(1, 4), "éllo");
+        assert_eq!(s.substr(6, 100), "wörld, this is long");
+        assert_eq!(s.substr(100, 1), "");
+        assert_eq!(s.substr(2, 0), "");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_slice_non_boundary() {
+        SemiStr::new("héllo").slice(2..);
+    }
+
+    #[test]
+    #[should_panic(expected = "up to maximum usize")]
+    fn test_slice_overflow() {
+        SemiStr::new("hello").slice(..=usize::MAX);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_split_at_non_boundary() {
+        SemiStr::new("héllo").split_at(2);
+    }
+
+    #[test]
+    fn test_semi_str_n() {
+        assert_eq!(size_of::<SemiStrN<20>>(), 24);
+        assert_eq!(size_of::<SemiStrN<28>>(), 32);
+        assert_eq!(size_of::<Option<SemiStrN<20>>>(), 24);
+        let s = SemiStrN::<20>::new("customer_address_id");
+        assert!(s.is_inline());
+        assert_eq!(s, "customer_address_id");
+        assert_eq!(s.clone(), s);
+        let long = SemiStr::new("a string longer than twenty bytes");
+        let ptr = long.payload_ptr();
+        let s: SemiStrN<20> = long.into_inline_cap();
+        assert!(!s.is_inline());
+        assert_eq!(s.as_str().as_ptr(), ptr);
+        let copy = s.clone();
+        assert_eq!(copy, s);
+        let back: SemiStr = copy.into_inline_cap();
+        assert_eq!(back.payload_ptr(), ptr);
+        assert_eq!(back.ref_count(), Some(2));
+        drop(s);
+        assert_eq!(back.ref_count(), Some(1));
+        assert!(SemiStrN::<20>::new("abc") < SemiStrN::new("abd"));
+        assert!(SemiStrN::<20>::new("ab") < SemiStrN::new("ab\0"));
+        assert!(SemiStrN::<16>::new("zz") > SemiStrN::new("a string longer than sixteen"));
+        assert!(SemiStrN::<16>::new("a string longer than sixteen") < SemiStrN::new("a string longer than sixteen!"));
+        assert_ne!(SemiStrN::<16>::new("abcd_1"), SemiStrN::<16>::new("abce_1"));
+        assert!(SemiStrN::<16>::default().is_empty());
+        let short: SemiStrN<20> = SemiStr::new("short").into_inline_cap();
+        assert!(short.is_inline());
+        let inline = SemiStrN::<20>::new("customer_address_id");
+        let back: SemiStr = inline.clone().into_inline_cap();
+        assert!(back.is_heap());
+        assert_eq!(back, inline);
+    }
+}
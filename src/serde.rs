@@ -0,0 +1,1231 @@
+//! Serde support of SemiStr, SemiStrVec, and dictionary encoding of
+//! records.
+//!
+//! SemiStrVec is encoded in columnar layout, as a few byte sections:
+//! lengths of all values, concatenated payload bytes, and the optional
+//! validity bitmap. Long values of decoded column are written into
+//! shared buffers, same as [`crate::SemiStrVecBuilder`].
+//!
+//! [`DictBatch`] serializes a batch of records with each distinct
+//! string written once into a dictionary section, which precedes the
+//! records, and every string in the records replaced by its index in
+//! the dictionary. On deserialization, SemiStrs in the records share
+//! buffers of the dictionary entries.
+//!
+//! Strings are replaced at the level of serde data model, so records
+//! need no annotation. The records must not rely on
+//! `deserialize_any` for strings, e.g. untagged enums and flattened
+//! fields, because the format only sees indexes.
+
+use crate::{inline_str, validate_utf8, SemiStr, SemiStrVec, SemiStrVecBuilder, INLINE_CAP};
+use ::serde::de::{self, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor};
+use ::serde::ser::{self, Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer};
+use ::serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+impl Serialize for SemiStr {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for SemiStr {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(SemiStrVisitor)
+    }
+}
+
+/// Visitor of SemiStr, which builds short strings directly into the
+/// inline format, so they are deserialized without any allocation.
+struct SemiStrVisitor;
+
+impl Visitor<'_> for SemiStrVisitor {
+    type Value = SemiStr;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string no longer than 4GB")
+    }
+
+    #[inline]
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<SemiStr, E> {
+        if v.len() <= INLINE_CAP {
+            // SAFETY
+            //
+            // length is checked above.
+            return Ok(unsafe { inline_str(v.as_bytes()) })
+        }
+        if let Some(s) = resolved_entry(v) {
+            return Ok(s)
+        }
+        SemiStr::try_from(v).map_err(E::custom)
+    }
+
+    #[inline]
+    fn visit_borrowed_str<E: de::Error>(self, v: &str) -> Result<SemiStr, E> {
+        self.visit_str(v)
+    }
+
+    #[inline]
+    fn visit_string<E: de::Error>(self, v: String) -> Result<SemiStr, E> {
+        SemiStr::try_from(v).map_err(E::custom)
+    }
+
+    #[inline]
+    fn visit_char<E: de::Error>(self, v: char) -> Result<SemiStr, E> {
+        self.visit_str(v.encode_utf8(&mut [0u8; 4]))
+    }
+
+    #[inline]
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<SemiStr, E> {
+        let s = validate_utf8(v).map_err(|_| E::invalid_value(de::Unexpected::Bytes(v), &self))?;
+        self.visit_str(s)
+    }
+
+    #[inline]
+    fn visit_borrowed_bytes<E: de::Error>(self, v: &[u8]) -> Result<SemiStr, E> {
+        self.visit_bytes(v)
+    }
+
+    #[inline]
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<SemiStr, E> {
+        self.visit_bytes(&v)
+    }
+}
+
+thread_local! {
+    // long dictionary entry being visited by the deserializer of
+    // DictBatch. The entry is owned, so a stale entry left by a panic
+    // never dangles, and its buffer is never reused by other strings.
+    static RESOLVED: RefCell<Option<SemiStr>> = const { RefCell::new(None) };
+}
+
+/// Returns the dictionary entry if the visited string is its payload,
+/// sharing its buffer.
+#[inline]
+fn resolved_entry(v: &str) -> Option<SemiStr> {
+    RESOLVED.with_borrow(|entry| {
+        entry.as_ref()
+            .filter(|e| e.payload_ptr() == v.as_ptr() && e.len() == v.len())
+            .map(SemiStr::share)
+    })
+}
+
+/// Guard restoring the previously resolved entry on drop, so that
+/// the entry is reset even if the visitor panics.
+struct ResolvedGuard(Option<SemiStr>);
+
+impl ResolvedGuard {
+    #[inline]
+    fn set(entry: &SemiStr) -> Self {
+        ResolvedGuard(RESOLVED.with(|r| r.replace(Some(entry.share()))))
+    }
+}
+
+impl Drop for ResolvedGuard {
+    #[inline]
+    fn drop(&mut self) {
+        let prev = self.0.take();
+        RESOLVED.with(|r| *r.borrow_mut() = prev);
+    }
+}
+
+impl Serialize for SemiStrVec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut lens = Vec::with_capacity(self.len() * 4);
+        let mut data = Vec::with_capacity(self.iter().map(|s| s.len()).sum());
+        for s in self.iter() {
+            lens.extend_from_slice(&(s.len() as u32).to_le_bytes());
+            data.extend_from_slice(s.as_bytes());
+        }
+        let validity: Option<Vec<u8>> = self.validity().map(|v| {
+            let mut bytes: Vec<u8> = v.as_words().iter().flat_map(|w| w.to_le_bytes()).collect();
+            bytes.truncate(v.len().div_ceil(8));
+            bytes
+        });
+        let mut st = serializer.serialize_struct("SemiStrVec", 3)?;
+        st.serialize_field("lens", &Bytes(&lens))?;
+        st.serialize_field("data", &Bytes(&data))?;
+        st.serialize_field("validity", &validity.as_deref().map(Bytes))?;
+        st.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for SemiStrVec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("SemiStrVec", VEC_FIELDS, VecVisitor)
+    }
+}
+
+const VEC_FIELDS: &[&str] = &["lens", "data", "validity"];
+
+/// Byte section serialized with serialize_bytes().
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Byte section deserialized from bytes, or sequence of u8 if the
+/// format has no native bytes.
+struct ByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_byte_buf(ByteBufVisitor)
+    }
+}
+
+struct ByteBufVisitor;
+
+impl<'de> Visitor<'de> for ByteBufVisitor {
+    type Value = ByteBuf;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a byte array")
+    }
+
+    #[inline]
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<ByteBuf, E> {
+        Ok(ByteBuf(v.to_vec()))
+    }
+
+    #[inline]
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<ByteBuf, E> {
+        Ok(ByteBuf(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ByteBuf, A::Error> {
+        let mut res = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(b) = seq.next_element()? {
+            res.push(b);
+        }
+        Ok(ByteBuf(res))
+    }
+}
+
+/// Field of SemiStrVec, deserialized as identifier.
+enum VecField {
+    Lens,
+    Data,
+    Validity,
+}
+
+impl<'de> Deserialize<'de> for VecField {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(VecFieldVisitor)
+    }
+}
+
+struct VecFieldVisitor;
+
+impl Visitor<'_> for VecFieldVisitor {
+    type Value = VecField;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("field of SemiStrVec")
+    }
+
+    #[inline]
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<VecField, E> {
+        match v {
+            "lens" => Ok(VecField::Lens),
+            "data" => Ok(VecField::Data),
+            "validity" => Ok(VecField::Validity),
+            other => Err(E::unknown_field(other, VEC_FIELDS)),
+        }
+    }
+}
+
+struct VecVisitor;
+
+impl VecVisitor {
+    /// Rebuild the column from byte sections.
+    fn build<E: de::Error>(lens: &[u8], data: &[u8], validity: Option<&[u8]>) -> Result<SemiStrVec, E> {
+        if !lens.len().is_multiple_of(4) {
+            return Err(E::custom("length section is not multiple of 4 bytes"))
+        }
+        let n = lens.len() / 4;
+        if validity.is_some_and(|v| v.len() != n.div_ceil(8)) {
+            return Err(E::custom("validity section does not match number of values"))
+        }
+        let data = validate_utf8(data).map_err(E::custom)?;
+        let lens = lens.chunks_exact(4).map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize);
+        let long: usize = lens.clone().filter(|len| *len > INLINE_CAP).sum();
+        let mut builder = SemiStrVecBuilder::with_capacity(n, long);
+        let mut pos = 0;
+        for (i, len) in lens.enumerate() {
+            let end = pos + len;
+            if end > data.len() || !data.is_char_boundary(end) {
+                return Err(E::custom("invalid value length"))
+            }
+            if validity.is_some_and(|v| v[i / 8] & (1 << (i % 8)) == 0) {
+                builder.append_null();
+            } else {
+                builder.append(&data[pos..end]);
+            }
+            pos = end;
+        }
+        if pos != data.len() {
+            return Err(E::custom("trailing bytes in data section"))
+        }
+        Ok(builder.finish())
+    }
+}
+
+impl<'de> Visitor<'de> for VecVisitor {
+    type Value = SemiStrVec;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("struct SemiStrVec")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<SemiStrVec, A::Error> {
+        let lens: ByteBuf = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let data: ByteBuf = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let validity: Option<ByteBuf> = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        Self::build(&lens.0, &data.0, validity.as_ref().map(|v| &v.0[..]))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<SemiStrVec, A::Error> {
+        let mut lens: Option<ByteBuf> = None;
+        let mut data: Option<ByteBuf> = None;
+        let mut validity: Option<Option<ByteBuf>> = None;
+        while let Some(field) = map.next_key::<VecField>()? {
+            match field {
+                VecField::Lens => lens = Some(map.next_value()?),
+                VecField::Data => data = Some(map.next_value()?),
+                VecField::Validity => validity = Some(map.next_value()?),
+            }
+        }
+        let lens = lens.ok_or_else(|| de::Error::missing_field("lens"))?;
+        let data = data.ok_or_else(|| de::Error::missing_field("data"))?;
+        let validity = validity.flatten();
+        Self::build(&lens.0, &data.0, validity.as_ref().map(|v| &v.0[..]))
+    }
+}
+
+/// DictBatch is a batch of records serialized with a string dictionary.
+///
+/// It is serialized as a tuple of the dictionary and the records, in
+/// which strings are replaced by u32 indexes. Repeated strings are
+/// therefore written only once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DictBatch<T>(pub Vec<T>);
+
+impl<T: Serialize> Serialize for DictBatch<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut dict = Dict::default();
+        let human_readable = serializer.is_human_readable();
+        for record in &self.0 {
+            record.serialize(Collect{dict: &mut dict, human_readable, _err: PhantomData::<S::Error>})?;
+        }
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&dict.strs)?;
+        tuple.serialize_element(&Records{records: &self.0, map: &dict.map})?;
+        tuple.end()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for DictBatch<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_tuple(2, BatchVisitor(PhantomData))
+    }
+}
+
+/// Distinct strings in order of first occurrence.
+#[derive(Default)]
+struct Dict {
+    map: HashMap<SemiStr, u32>,
+    strs: Vec<SemiStr>,
+}
+
+struct Records<'a, T> {
+    records: &'a [T],
+    map: &'a HashMap<SemiStr, u32>,
+}
+
+impl<T: Serialize> Serialize for Records<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.records.len()))?;
+        for record in self.records {
+            seq.serialize_element(&Interned{value: record, map: self.map})?;
+        }
+        seq.end()
+    }
+}
+
+/// Value serialized with strings replaced by dictionary indexes.
+struct Interned<'a, T: ?Sized> {
+    value: &'a T,
+    map: &'a HashMap<SemiStr, u32>,
+}
+
+impl<T: Serialize + ?Sized> Serialize for Interned<'_, T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(Interning{inner: serializer, map: self.map})
+    }
+}
+
+/// Serializer collecting distinct strings into dictionary,
+/// all other values are discarded.
+///
+/// It reports readability of the actual serializer, so that values
+/// emit the same strings as in the interning pass.
+struct Collect<'a, E> {
+    dict: &'a mut Dict,
+    human_readable: bool,
+    _err: PhantomData<E>,
+}
+
+impl<E> Collect<'_, E> {
+    #[inline]
+    fn reborrow(&mut self) -> Collect<'_, E> {
+        Collect{dict: self.dict, human_readable: self.human_readable, _err: PhantomData}
+    }
+}
+
+macro_rules! collect_ignore {
+    ($($f:ident($($t:ty),*);)*) => {
+        $(
+            #[inline]
+            fn $f(self, $(_: $t),*) -> Result<(), E> {
+                Ok(())
+            }
+        )*
+    };
+}
+
+impl<'a, E: ser::Error> Serializer for Collect<'a, E> {
+    type Ok = ();
+    type Error = E;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    collect_ignore! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<(), E> {
+        if !self.dict.map.contains_key(v) {
+            let idx = u32::try_from(self.dict.strs.len()).map_err(|_| E::custom("too many distinct strings"))?;
+            let s = SemiStr::try_from(v).map_err(E::custom)?;
+            self.dict.map.insert(s.share(), idx);
+            self.dict.strs.push(s);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), E> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _: &'static str, value: &T) -> Result<(), E> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _: &'static str, _: u32, _: &'static str, value: &T) -> Result<(), E> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self, E> {
+        Ok(self)
+    }
+
+    #[inline]
+    fn serialize_tuple(self, _: usize) -> Result<Self, E> {
+        Ok(self)
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, E> {
+        Ok(self)
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self, E> {
+        Ok(self)
+    }
+
+    #[inline]
+    fn serialize_map(self, _: Option<usize>) -> Result<Self, E> {
+        Ok(self)
+    }
+
+    #[inline]
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, E> {
+        Ok(self)
+    }
+
+    #[inline]
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self, E> {
+        Ok(self)
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+}
+
+macro_rules! collect_compound {
+    ($($tr:ident::$f:ident($($k:ty),*);)*) => {
+        $(
+            impl<E: ser::Error> $tr for Collect<'_, E> {
+                type Ok = ();
+                type Error = E;
+
+                #[inline]
+                fn $f<T: Serialize + ?Sized>(&mut self, $(_: $k,)* value: &T) -> Result<(), E> {
+                    value.serialize(self.reborrow())
+                }
+
+                #[inline]
+                fn end(self) -> Result<(), E> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+collect_compound! {
+    SerializeSeq::serialize_element();
+    SerializeTuple::serialize_element();
+    SerializeTupleStruct::serialize_field();
+    SerializeTupleVariant::serialize_field();
+    SerializeStruct::serialize_field(&'static str);
+    SerializeStructVariant::serialize_field(&'static str);
+}
+
+impl<E: ser::Error> SerializeMap for Collect<'_, E> {
+    type Ok = ();
+    type Error = E;
+
+    #[inline]
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), E> {
+        key.serialize(self.reborrow())
+    }
+
+    #[inline]
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), E> {
+        value.serialize(self.reborrow())
+    }
+
+    #[inline]
+    fn end(self) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+/// Serializer replacing strings by dictionary indexes,
+/// all other values are forwarded to the inner serializer.
+struct Interning<'a, S> {
+    inner: S,
+    map: &'a HashMap<SemiStr, u32>,
+}
+
+macro_rules! forward_ser {
+    ($($f:ident($($a:ident: $t:ty),*);)*) => {
+        $(
+            #[inline]
+            fn $f(self, $($a: $t),*) -> Result<S::Ok, S::Error> {
+                self.inner.$f($($a),*)
+            }
+        )*
+    };
+}
+
+impl<'a, S: Serializer> Serializer for Interning<'a, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Interning<'a, S::SerializeSeq>;
+    type SerializeTuple = Interning<'a, S::SerializeTuple>;
+    type SerializeTupleStruct = Interning<'a, S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Interning<'a, S::SerializeTupleVariant>;
+    type SerializeMap = Interning<'a, S::SerializeMap>;
+    type SerializeStruct = Interning<'a, S::SerializeStruct>;
+    type SerializeStructVariant = Interning<'a, S::SerializeStructVariant>;
+
+    forward_ser! {
+        serialize_bool(v: bool);
+        serialize_i8(v: i8);
+        serialize_i16(v: i16);
+        serialize_i32(v: i32);
+        serialize_i64(v: i64);
+        serialize_i128(v: i128);
+        serialize_u8(v: u8);
+        serialize_u16(v: u16);
+        serialize_u32(v: u32);
+        serialize_u64(v: u64);
+        serialize_u128(v: u128);
+        serialize_f32(v: f32);
+        serialize_f64(v: f64);
+        serialize_char(v: char);
+        serialize_bytes(v: &[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(name: &'static str);
+        serialize_unit_variant(name: &'static str, idx: u32, variant: &'static str);
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        match self.map.get(v) {
+            Some(idx) => self.inner.serialize_u32(*idx),
+            None => Err(ser::Error::custom("string not collected into dictionary")),
+        }
+    }
+
+    #[inline]
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_some(&Interned{value, map: self.map})
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, name: &'static str, value: &T) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_newtype_struct(name, &Interned{value, map: self.map})
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        idx: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_newtype_variant(name, idx, variant, &Interned{value, map: self.map})
+    }
+
+    #[inline]
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        Ok(Interning{inner: self.inner.serialize_seq(len)?, map: self.map})
+    }
+
+    #[inline]
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        Ok(Interning{inner: self.inner.serialize_tuple(len)?, map: self.map})
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct, S::Error> {
+        Ok(Interning{inner: self.inner.serialize_tuple_struct(name, len)?, map: self.map})
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        idx: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        Ok(Interning{inner: self.inner.serialize_tuple_variant(name, idx, variant, len)?, map: self.map})
+    }
+
+    #[inline]
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        Ok(Interning{inner: self.inner.serialize_map(len)?, map: self.map})
+    }
+
+    #[inline]
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct, S::Error> {
+        Ok(Interning{inner: self.inner.serialize_struct(name, len)?, map: self.map})
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        idx: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        Ok(Interning{inner: self.inner.serialize_struct_variant(name, idx, variant, len)?, map: self.map})
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+macro_rules! interning_compound {
+    ($($tr:ident::$f:ident($($k:ident: $t:ty),*);)*) => {
+        $(
+            impl<S: $tr> $tr for Interning<'_, S> {
+                type Ok = S::Ok;
+                type Error = S::Error;
+
+                #[inline]
+                fn $f<T: Serialize + ?Sized>(&mut self, $($k: $t,)* value: &T) -> Result<(), S::Error> {
+                    self.inner.$f($($k,)* &Interned{value, map: self.map})
+                }
+
+                #[inline]
+                fn end(self) -> Result<S::Ok, S::Error> {
+                    self.inner.end()
+                }
+            }
+        )*
+    };
+}
+
+interning_compound! {
+    SerializeSeq::serialize_element();
+    SerializeTuple::serialize_element();
+    SerializeTupleStruct::serialize_field();
+    SerializeTupleVariant::serialize_field();
+    SerializeStruct::serialize_field(key: &'static str);
+    SerializeStructVariant::serialize_field(key: &'static str);
+}
+
+impl<S: SerializeMap> SerializeMap for Interning<'_, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    #[inline]
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), S::Error> {
+        self.inner.serialize_key(&Interned{value: key, map: self.map})
+    }
+
+    #[inline]
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), S::Error> {
+        self.inner.serialize_value(&Interned{value, map: self.map})
+    }
+
+    #[inline]
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+struct BatchVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for BatchVisitor<T> {
+    type Value = DictBatch<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a tuple of string dictionary and records")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<DictBatch<T>, A::Error> {
+        let dict: Vec<SemiStr> = seq.next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let records = seq.next_element_seed(RecordsSeed{dict: &dict, _marker: PhantomData})?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(DictBatch(records))
+    }
+}
+
+struct RecordsSeed<'a, T> {
+    dict: &'a [SemiStr],
+    _marker: PhantomData<T>,
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for RecordsSeed<'_, T> {
+    type Value = Vec<T>;
+
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Vec<T>, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for RecordsSeed<'_, T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence of records")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error> {
+        let mut res = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(record) = seq.next_element_seed(Seed{inner: PhantomData::<T>, dict: self.dict})? {
+            res.push(record);
+        }
+        Ok(res)
+    }
+}
+
+/// Seed deserializing value with strings resolved from dictionary.
+struct Seed<'a, S> {
+    inner: S,
+    dict: &'a [SemiStr],
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Seed<'_, S> {
+    type Value = S::Value;
+
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S::Value, D::Error> {
+        self.inner.deserialize(Resolving{inner: deserializer, dict: self.dict})
+    }
+}
+
+/// Deserializer resolving dictionary indexes to strings,
+/// all other values are forwarded to the inner deserializer.
+///
+/// This wrapper also wraps visitors and accessors, so that nested
+/// values are resolved as well.
+struct Resolving<'a, T> {
+    inner: T,
+    dict: &'a [SemiStr],
+}
+
+impl<'a, T> Resolving<'a, T> {
+    #[inline]
+    fn wrap<U>(&self, inner: U) -> Resolving<'a, U> {
+        Resolving{inner, dict: self.dict}
+    }
+}
+
+macro_rules! forward_de {
+    ($($f:ident($($a:ident: $t:ty),*);)*) => {
+        $(
+            #[inline]
+            fn $f<V: Visitor<'de>>(self, $($a: $t,)* visitor: V) -> Result<V::Value, D::Error> {
+                let visitor = self.wrap(visitor);
+                self.inner.$f($($a,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Resolving<'_, D> {
+    type Error = D::Error;
+
+    forward_de! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    #[inline]
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_u32(IndexVisitor{inner: visitor, dict: self.dict})
+    }
+
+    #[inline]
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_u32(IndexVisitor{inner: visitor, dict: self.dict})
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+macro_rules! forward_visit {
+    ($($f:ident($t:ty);)*) => {
+        $(
+            #[inline]
+            fn $f<E: de::Error>(self, v: $t) -> Result<V::Value, E> {
+                self.inner.$f(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Resolving<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_str(&str);
+        visit_borrowed_str(&'de str);
+        visit_string(String);
+        visit_bytes(&[u8]);
+        visit_borrowed_bytes(&'de [u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    #[inline]
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_none()
+    }
+
+    #[inline]
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    #[inline]
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        let deserializer = self.wrap(deserializer);
+        self.inner.visit_some(deserializer)
+    }
+
+    #[inline]
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        let deserializer = self.wrap(deserializer);
+        self.inner.visit_newtype_struct(deserializer)
+    }
+
+    #[inline]
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        let seq = self.wrap(seq);
+        self.inner.visit_seq(seq)
+    }
+
+    #[inline]
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        let map = self.wrap(map);
+        self.inner.visit_map(map)
+    }
+
+    #[inline]
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        let data = self.wrap(data);
+        self.inner.visit_enum(data)
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for Resolving<'_, A> {
+    type Error = A::Error;
+
+    #[inline]
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, A::Error> {
+        self.inner.next_element_seed(Seed{inner: seed, dict: self.dict})
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Resolving<'_, A> {
+    type Error = A::Error;
+
+    #[inline]
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error> {
+        self.inner.next_key_seed(Seed{inner: seed, dict: self.dict})
+    }
+
+    #[inline]
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+        self.inner.next_value_seed(Seed{inner: seed, dict: self.dict})
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, 'de, A: EnumAccess<'de>> EnumAccess<'de> for Resolving<'a, A> {
+    type Error = A::Error;
+    type Variant = Resolving<'a, A::Variant>;
+
+    #[inline]
+    fn variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<(T::Value, Self::Variant), A::Error> {
+        let (value, variant) = self.inner.variant_seed(Seed{inner: seed, dict: self.dict})?;
+        Ok((value, Resolving{inner: variant, dict: self.dict}))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for Resolving<'_, A> {
+    type Error = A::Error;
+
+    #[inline]
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.inner.unit_variant()
+    }
+
+    #[inline]
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, A::Error> {
+        self.inner.newtype_variant_seed(Seed{inner: seed, dict: self.dict})
+    }
+
+    #[inline]
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+        let visitor = self.wrap(visitor);
+        self.inner.tuple_variant(len, visitor)
+    }
+
+    #[inline]
+    fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, A::Error> {
+        let visitor = self.wrap(visitor);
+        self.inner.struct_variant(fields, visitor)
+    }
+}
+
+/// Visitor receiving dictionary index, and passing the resolved
+/// string to the inner visitor.
+struct IndexVisitor<'a, V> {
+    inner: V,
+    dict: &'a [SemiStr],
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for IndexVisitor<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an index of string dictionary")
+    }
+
+    #[inline]
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<V::Value, E> {
+        let entry = usize::try_from(v).ok()
+            .and_then(|idx| self.dict.get(idx))
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(v), &self))?;
+        // short entries are always copied inline.
+        if entry.len() <= INLINE_CAP {
+            return self.inner.visit_str(entry.as_str())
+        }
+        let _guard = ResolvedGuard::set(entry);
+        self.inner.visit_str(entry.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Level {
+        Info,
+        Warn(SemiStr),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        service: SemiStr,
+        host: String,
+        level: Level,
+        tags: Vec<SemiStr>,
+        attrs: BTreeMap<String, Option<SemiStr>>,
+        count: u32,
+    }
+
+    fn event(i: u32) -> Event {
+        Event{
+            service: SemiStr::new("checkout-service-frontend"),
+            host: format!("host-{}", i % 2),
+            level: if i.is_multiple_of(2) { Level::Info } else { Level::Warn(SemiStr::new("slow upstream response")) },
+            tags: vec![SemiStr::new("prod"), SemiStr::new("region=us-east-1")],
+            attrs: [("path".to_string(), Some(SemiStr::new("/api/cart"))), ("user".to_string(), None)].into(),
+            count: i,
+        }
+    }
+
+    #[test]
+    fn test_semistr_serde() {
+        let s = SemiStr::new("a string longer than 12");
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, "\"a string longer than 12\"");
+        let d: SemiStr = serde_json::from_str(&json).unwrap();
+        assert_eq!(d, s);
+        // owned string input.
+        let d: SemiStr = serde_json::from_value(serde_json::json!("an owned string value")).unwrap();
+        assert_eq!(d, "an owned string value");
+        #[derive(Deserialize)]
+        struct Config {
+            name: SemiStr,
+            tags: Vec<SemiStr>,
+        }
+        let cfg: Config = serde_json::from_str(r#"{"name":"escaped \"name\"","tags":["a","b"]}"#).unwrap();
+        assert_eq!(cfg.name, "escaped \"name\"");
+        assert_eq!(cfg.tags, ["a", "b"]);
+        assert!(serde_json::from_str::<SemiStr>("1").is_err());
+    }
+
+    #[test]
+    fn test_semistr_deserialize_bytes() {
+        use ::serde::de::value::{BorrowedBytesDeserializer, BytesDeserializer, Error as ValueError};
+        let d = SemiStr::deserialize(BytesDeserializer::<ValueError>::new(b"short key")).unwrap();
+        assert_eq!(d, "short key");
+        let d = SemiStr::deserialize(BorrowedBytesDeserializer::<ValueError>::new(b"a long byte string")).unwrap();
+        assert_eq!(d, "a long byte string");
+        assert!(SemiStr::deserialize(BytesDeserializer::<ValueError>::new(b"\xff")).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc-check")]
+    fn test_semistr_deserialize_inline_no_alloc() {
+        let json = serde_json::to_string(&["k1", "key2", "twelve bytes"]).unwrap();
+        let keys: Vec<SemiStr> = crate::alloc_check::deny_alloc(|| serde_json::from_str(&json).unwrap());
+        assert_eq!(keys, ["k1", "key2", "twelve bytes"]);
+    }
+
+    #[test]
+    fn test_semistr_vec_serde() {
+        let mut builder = SemiStrVecBuilder::new();
+        builder.append("short");
+        builder.append_null();
+        builder.append("a long value written into arena");
+        builder.append("");
+        builder.append("another long value in the column");
+        let col = builder.finish();
+        let json = serde_json::to_string(&col).unwrap();
+        assert!(json.starts_with("{\"lens\":[5,0,0,0,0,0,0,0,31,0,0,0"));
+        let res: SemiStrVec = serde_json::from_str(&json).unwrap();
+        assert_eq!(res, col);
+        assert_eq!(res.null_count(), 1);
+        // long values share one buffer.
+        assert_eq!(res[2].payload_ptr().wrapping_add(31), res[4].payload_ptr());
+        let col: SemiStrVec = ["no", "nulls"].into_iter().collect();
+        let res: SemiStrVec = serde_json::from_str(&serde_json::to_string(&col).unwrap()).unwrap();
+        assert_eq!(res, col);
+        assert!(res.validity().is_none());
+        let bad = "{\"lens\":[9,0,0,0],\"data\":[97],\"validity\":null}";
+        assert!(serde_json::from_str::<SemiStrVec>(bad).is_err());
+    }
+
+    #[test]
+    fn test_dict_batch() {
+        let batch = DictBatch((0..4).map(event).collect());
+        let json = serde_json::to_string(&batch).unwrap();
+        let plain = serde_json::to_string(&batch.0).unwrap();
+        assert!(json.len() < plain.len());
+        assert_eq!(json.matches("checkout-service-frontend").count(), 1);
+        let res: DictBatch<Event> = serde_json::from_str(&json).unwrap();
+        assert_eq!(res, batch);
+        // long strings share buffers of dictionary entries.
+        assert_eq!(res.0[0].service.payload_ptr(), res.0[3].service.payload_ptr());
+        assert_eq!(res.0[0].tags[1].payload_ptr(), res.0[2].tags[1].payload_ptr());
+    }
+
+    #[test]
+    fn test_dict_batch_visitor_panic() {
+        struct Boom;
+
+        impl<'de> Deserialize<'de> for Boom {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct BoomVisitor;
+                impl Visitor<'_> for BoomVisitor {
+                    type Value = Boom;
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("a string")
+                    }
+                    fn visit_str<E: de::Error>(self, _: &str) -> Result<Boom, E> {
+                        panic!("visitor panicked")
+                    }
+                }
+                deserializer.deserialize_str(BoomVisitor)
+            }
+        }
+
+        let json = "[[\"a dictionary entry longer than 12\"],[[0]]]";
+        let res = std::panic::catch_unwind(|| serde_json::from_str::<DictBatch<(Boom,)>>(json));
+        assert!(res.is_err());
+        assert!(RESOLVED.with_borrow(Option::is_none));
+        let s: SemiStr = serde_json::from_str("\"a dictionary entry longer than 12\"").unwrap();
+        assert_eq!(s, "a dictionary entry longer than 12");
+    }
+
+    #[test]
+    fn test_collect_human_readable() {
+        struct Addr;
+
+        impl Serialize for Addr {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if serializer.is_human_readable() {
+                    serializer.serialize_str("readable form")
+                } else {
+                    serializer.serialize_str("compact form")
+                }
+            }
+        }
+
+        for human_readable in [true, false] {
+            let mut dict = Dict::default();
+            Addr.serialize(Collect{dict: &mut dict, human_readable, _err: PhantomData::<serde_json::Error>}).unwrap();
+            let expected = if human_readable { "readable form" } else { "compact form" };
+            assert_eq!(dict.strs, vec![SemiStr::new(expected)]);
+        }
+    }
+
+    #[test]
+    fn test_dict_batch_invalid_index() {
+        let res: Result<DictBatch<Event>, _> = serde_json::from_str("[[\"a\"],[{\"service\":1}]]");
+        assert!(res.is_err());
+    }
+}
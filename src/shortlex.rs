@@ -0,0 +1,91 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+/// Shortlex wraps a string to order by length first, and then
+/// lexicographically.
+///
+/// Length of SemiStr is stored in the header, so strings of different
+/// lengths are ordered without reading their contents.
+///
+/// ```
+/// use semistr::{SemiStr, Shortlex};
+///
+/// let mut v: Vec<_> = ["bb", "a", "ab", "c"].into_iter().map(|s| Shortlex(SemiStr::new(s))).collect();
+/// v.sort();
+/// assert_eq!(v.iter().map(|s| s.as_str()).collect::<Vec<_>>(), ["a", "c", "ab", "bb"]);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Shortlex<T>(pub T);
+
+impl<T> Shortlex<T> {
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Shortlex<T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<T> for Shortlex<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Shortlex(value)
+    }
+}
+
+impl<T: AsRef<str>> PartialEq for Shortlex<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_ref() == other.0.as_ref()
+    }
+}
+
+impl<T: AsRef<str>> Eq for Shortlex<T> {}
+
+impl<T: AsRef<str>> Hash for Shortlex<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_ref().hash(state)
+    }
+}
+
+impl<T: AsRef<str>> PartialOrd for Shortlex<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: AsRef<str>> Ord for Shortlex<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.0.as_ref(), other.0.as_ref());
+        a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SemiStr;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_shortlex() {
+        let set: BTreeSet<Shortlex<SemiStr>> = ["ba", "b", "a long string value", "ab", "", "b"]
+            .into_iter()
+            .map(|s| Shortlex(SemiStr::new(s)))
+            .collect();
+        let order: Vec<&str> = set.iter().map(|s| s.as_str()).collect();
+        assert_eq!(order, vec!["", "b", "ab", "ba", "a long string value"]);
+        assert!(Shortlex("zz") < Shortlex("aaa"));
+        assert_eq!(Shortlex(SemiStr::new("x")).into_inner(), "x");
+    }
+}
@@ -0,0 +1,108 @@
+use crate::SemiStr;
+use std::iter::FusedIterator;
+
+/// Iterator adapter converting substrings of a SemiStr into SemiStrs
+/// sharing its buffer, see [`SemiStr::split_shared`].
+#[derive(Debug, Clone)]
+pub struct Shared<'a, I> {
+    parent: &'a SemiStr,
+    inner: I,
+}
+
+impl SemiStr {
+    /// Returns an iterator over substrings separated by sep, each of
+    /// which shares the buffer of self, or is stored inline if short.
+    #[inline]
+    pub fn split_shared<'a>(&'a self, sep: &'a str) -> Shared<'a, std::str::Split<'a, &'a str>> {
+        self.shared_iter(self.as_str().split(sep))
+    }
+
+    /// Returns an iterator over at most n substrings separated by sep,
+    /// the last of which contains the remainder.
+    /// Substrings share the buffer of self, or are stored inline if short.
+    #[inline]
+    pub fn splitn_shared<'a>(&'a self, n: usize, sep: &'a str) -> Shared<'a, std::str::SplitN<'a, &'a str>> {
+        self.shared_iter(self.as_str().splitn(n, sep))
+    }
+
+    /// Returns an iterator over lines of self, without line endings
+    /// "\n" or "\r\n", like [`str::lines`].
+    /// Lines share the buffer of self, or are stored inline if short.
+    #[inline]
+    pub fn lines_shared(&self) -> Shared<'_, std::str::Lines<'_>> {
+        self.shared_iter(self.as_str().lines())
+    }
+
+    /// Adapt an iterator over substrings of self, e.g. produced by
+    /// other split methods of str, to yield SemiStrs sharing the
+    /// buffer of self.
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if it yields a str not borrowed from self.
+    #[inline]
+    pub fn shared_iter<'a, I: Iterator<Item = &'a str>>(&'a self, inner: I) -> Shared<'a, I> {
+        Shared{parent: self, inner}
+    }
+}
+
+impl<'a, I: Iterator<Item = &'a str>> Iterator for Shared<'a, I> {
+    type Item = SemiStr;
+
+    #[inline]
+    fn next(&mut self) -> Option<SemiStr> {
+        self.inner.next().map(|s| self.parent.shared_sub(s))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, I: DoubleEndedIterator<Item = &'a str>> DoubleEndedIterator for Shared<'a, I> {
+    #[inline]
+    fn next_back(&mut self) -> Option<SemiStr> {
+        self.inner.next_back().map(|s| self.parent.shared_sub(s))
+    }
+}
+
+impl<'a, I: FusedIterator<Item = &'a str>> FusedIterator for Shared<'a, I> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_shared() {
+        let doc = SemiStr::new("id,a long field value shared with parent,,x");
+        let fields: Vec<SemiStr> = doc.split_shared(",").collect();
+        assert_eq!(fields, ["id", "a long field value shared with parent", "", "x"]);
+        assert_eq!(fields[1].payload_ptr(), unsafe { doc.payload_ptr().add(3) });
+        assert_eq!(doc.ref_count(), Some(2));
+        let fields: Vec<SemiStr> = doc.splitn_shared(2, ",").collect();
+        assert_eq!(fields, ["id", "a long field value shared with parent,,x"]);
+        assert_eq!(doc.split_shared(",").last().unwrap(), "x");
+        let words: Vec<SemiStr> = doc.shared_iter(doc.split_whitespace()).rev().collect();
+        assert_eq!(words.len(), 7);
+        assert_eq!(words[0], "parent,,x");
+    }
+
+    #[test]
+    fn test_lines_shared() {
+        let log = SemiStr::new("short\r\na log line longer than twelve bytes\n\nlast");
+        let lines: Vec<SemiStr> = log.lines_shared().collect();
+        drop(log);
+        assert_eq!(lines, ["short", "a log line longer than twelve bytes", "", "last"]);
+        assert_eq!(lines[1].ref_count(), Some(1));
+        assert!(lines[0].is_inline());
+        assert_eq!(SemiStr::default().lines_shared().count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_shared_iter_foreign() {
+        let s = SemiStr::new("a string longer than 12");
+        s.shared_iter(["foreign"].into_iter()).for_each(drop);
+    }
+}
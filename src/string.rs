@@ -0,0 +1,393 @@
+use crate::{adopt_block, alloc_block, block_cap, realloc_block, release_block, try_alloc_block, try_realloc_block,
+    Error, Result, SemiStr, MAX_LEN};
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+
+/// Minimal non-zero capacity of SemiString.
+const MIN_CAP: usize = 16;
+
+/// SemiString is a growable string, which can be frozen into SemiStr.
+///
+/// Its buffer has the same layout as heap block of SemiStr, so
+/// [`SemiString::freeze`] transfers the buffer without copying.
+/// Length is limited to 4GB, same as SemiStr.
+pub struct SemiString {
+    // payload pointer of uniquely owned shared block,
+    // null if no buffer is allocated.
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl SemiString {
+    #[inline]
+    pub const fn new() -> Self {
+        SemiString{ptr: std::ptr::null_mut(), len: 0}
+    }
+
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut res = SemiString::new();
+        res.reserve(capacity);
+        res
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        if self.ptr.is_null() {
+            0
+        } else {
+            unsafe { block_cap(self.ptr) }
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        if self.ptr.is_null() {
+            return ""
+        }
+        unsafe { std::str::from_utf8_unchecked(std::slice::from_raw_parts(self.ptr, self.len)) }
+    }
+
+    #[inline]
+    pub fn as_mut_str(&mut self) -> &mut str {
+        if self.ptr.is_null() {
+            return Default::default()
+        }
+        unsafe { std::str::from_utf8_unchecked_mut(std::slice::from_raw_parts_mut(self.ptr, self.len)) }
+    }
+
+    /// Reserve capacity for at least additional more bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new length would be greater than 4GB.
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional)
+            .filter(|n| *n <= MAX_LEN)
+            .expect("capacity overflow");
+        let cap = self.capacity();
+        if required <= cap {
+            return
+        }
+        let new_cap = grown_cap(cap, required);
+        self.ptr = unsafe {
+            if self.ptr.is_null() {
+                alloc_block(new_cap)
+            } else {
+                realloc_block(self.ptr, new_cap)
+            }
+        };
+    }
+
+    /// Reserve capacity for at least additional more bytes, same as
+    /// [`SemiString::reserve`], but returns error instead of panicking
+    /// or aborting. The string is unchanged on error.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<()> {
+        let required = self.len.checked_add(additional)
+            .filter(|n| *n <= MAX_LEN)
+            .ok_or(Error::StringTooLong(self.len.saturating_add(additional)))?;
+        let cap = self.capacity();
+        if required <= cap {
+            return Ok(())
+        }
+        let new_cap = grown_cap(cap, required);
+        let ptr = unsafe {
+            if self.ptr.is_null() {
+                try_alloc_block(new_cap)
+            } else {
+                try_realloc_block(self.ptr, new_cap)
+            }
+        };
+        self.ptr = ptr.ok_or(Error::AllocFailed(new_cap))?;
+        Ok(())
+    }
+
+    /// Shrink capacity to length.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        if self.ptr.is_null() || self.capacity() == self.len {
+            return
+        }
+        if self.len == 0 {
+            unsafe { release_block(self.ptr) };
+            self.ptr = std::ptr::null_mut();
+        } else {
+            self.ptr = unsafe { realloc_block(self.ptr, self.len) };
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0u8; 4]))
+    }
+
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        self.reserve(s.len());
+        unsafe {
+            std::ptr::copy_nonoverlapping(s.as_ptr(), self.ptr.add(self.len), s.len());
+        }
+        self.len += s.len();
+    }
+
+    /// Append a string, returns error instead of panicking or aborting
+    /// if the buffer cannot grow. The string is unchanged on error.
+    #[inline]
+    pub fn try_push_str(&mut self, s: &str) -> Result<()> {
+        self.try_reserve(s.len())?;
+        self.push_str(s);
+        Ok(())
+    }
+
+    /// Insert a char at given byte position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if idx is not on char boundary, or is past the end.
+    #[inline]
+    pub fn insert(&mut self, idx: usize, ch: char) {
+        self.insert_str(idx, ch.encode_utf8(&mut [0u8; 4]))
+    }
+
+    /// Insert a string at given byte position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if idx is not on char boundary, or is past the end.
+    pub fn insert_str(&mut self, idx: usize, s: &str) {
+        assert!(self.as_str().is_char_boundary(idx), "insertion index {} is not a char boundary", idx);
+        self.reserve(s.len());
+        unsafe {
+            std::ptr::copy(self.ptr.add(idx), self.ptr.add(idx + s.len()), self.len - idx);
+            std::ptr::copy_nonoverlapping(s.as_ptr(), self.ptr.add(idx), s.len());
+        }
+        self.len += s.len();
+    }
+
+    /// Remove the last char and return it.
+    #[inline]
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = self.as_str().chars().next_back()?;
+        self.len -= ch.len_utf8();
+        Some(ch)
+    }
+
+    /// Shorten the string to given byte length.
+    /// No effect if new_len is greater than current length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new_len is not on char boundary.
+    #[inline]
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.len {
+            assert!(self.as_str().is_char_boundary(new_len), "new length {} is not a char boundary", new_len);
+            self.len = new_len;
+        }
+    }
+
+    /// Returns pointer to the buffer, which is null if no buffer is
+    /// allocated.
+    #[inline]
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr
+    }
+
+    /// Set length of the string.
+    ///
+    /// # Safety
+    ///
+    /// new_len must be no more than capacity, and the first new_len
+    /// bytes of the buffer must be valid utf-8 string.
+    #[inline]
+    pub(crate) unsafe fn set_len(&mut self, new_len: usize) {
+        self.len = new_len;
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Convert into SemiStr.
+    ///
+    /// String no longer than 12 bytes is copied inline, otherwise the
+    /// buffer is transferred without copying, including spare capacity.
+    /// Call [`SemiString::shrink_to_fit`] beforehand to release it.
+    #[inline]
+    pub fn freeze(self) -> SemiStr {
+        let this = ManuallyDrop::new(self);
+        if this.ptr.is_null() {
+            return SemiStr::default()
+        }
+        // SAFETY
+        //
+        // buffer is uniquely owned, and length is limited by reserve().
+        unsafe { adopt_block(this.ptr, this.len) }
+    }
+}
+
+/// Returns capacity to grow to for required length, which at least
+/// doubles current capacity.
+#[inline]
+fn grown_cap(cap: usize, required: usize) -> usize {
+    required.max(cap * 2).clamp(MIN_CAP, MAX_LEN)
+}
+
+// SAFETY
+//
+// SemiString uniquely owns its buffer.
+unsafe impl Send for SemiString {}
+unsafe impl Sync for SemiString {}
+
+impl Drop for SemiString {
+    #[inline]
+    fn drop(&mut self) {
+        if !self.ptr.is_null() {
+            unsafe { release_block(self.ptr) }
+        }
+    }
+}
+
+impl Default for SemiString {
+    #[inline]
+    fn default() -> Self {
+        SemiString::new()
+    }
+}
+
+impl Clone for SemiString {
+    #[inline]
+    fn clone(&self) -> Self {
+        SemiString::from(self.as_str())
+    }
+}
+
+impl Deref for SemiString {
+    type Target = str;
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SemiString {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for SemiString {
+    #[inline]
+    fn from(s: &str) -> Self {
+        let mut res = SemiString::with_capacity(s.len());
+        res.push_str(s);
+        res
+    }
+}
+
+impl From<SemiString> for SemiStr {
+    #[inline]
+    fn from(s: SemiString) -> Self {
+        s.freeze()
+    }
+}
+
+impl PartialEq for SemiString {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for SemiString {}
+
+impl PartialEq<str> for SemiString {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&'_ str> for SemiString {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl fmt::Debug for SemiString {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for SemiString {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semi_string() {
+        let mut s = SemiString::new();
+        assert!(s.is_empty());
+        assert_eq!(s.capacity(), 0);
+        s.push_str("hello");
+        s.push(' ');
+        s.push_str("world");
+        assert_eq!(s, "hello world");
+        s.insert(0, '>');
+        s.insert_str(6, ",");
+        assert_eq!(s, ">hello, world");
+        assert_eq!(s.pop(), Some('d'));
+        s.truncate(6);
+        assert_eq!(s, ">hello");
+        s.as_mut_str().make_ascii_uppercase();
+        assert_eq!(s.freeze(), ">HELLO");
+
+        let mut s = SemiString::with_capacity(64);
+        s.push_str("a string longer than inline capacity");
+        let ptr = s.as_ptr();
+        let frozen = s.freeze();
+        assert_eq!(frozen, "a string longer than inline capacity");
+        assert_eq!(frozen.payload_ptr(), ptr);
+
+        let mut s = SemiString::from("ünïcode string");
+        s.shrink_to_fit();
+        assert_eq!(s.capacity(), s.len());
+        s.clear();
+        s.shrink_to_fit();
+        assert_eq!(s.capacity(), 0);
+        assert_eq!(SemiString::new().freeze(), "");
+    }
+
+    #[test]
+    fn test_try_reserve() {
+        let mut s = SemiString::new();
+        s.try_push_str("a string longer than inline capacity").unwrap();
+        assert_eq!(s, "a string longer than inline capacity");
+        s.try_reserve(100).unwrap();
+        assert!(s.capacity() >= s.len() + 100);
+        assert!(matches!(s.try_reserve(MAX_LEN), Err(Error::StringTooLong(_))));
+        assert_eq!(s, "a string longer than inline capacity");
+    }
+}
@@ -0,0 +1,303 @@
+use crate::arena::Arena;
+use crate::{Bitmap, SemiStr};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+/// SemiStrVec is a column of SemiStr values, which may contain nulls.
+///
+/// It dereferences to a slice of headers, so all kernels in
+/// [`crate::kernel`] can be applied directly. Null rows hold empty
+/// strings in the slice, and are tracked by a separate validity bitmap.
+#[derive(Debug, Clone, Default)]
+pub struct SemiStrVec {
+    values: Vec<SemiStr>,
+    // None if there is no null.
+    validity: Option<Bitmap>,
+}
+
+impl SemiStrVec {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        SemiStrVec{values: Vec::with_capacity(capacity), validity: None}
+    }
+
+    #[inline]
+    pub fn push(&mut self, value: SemiStr) {
+        self.values.push(value);
+        if let Some(validity) = self.validity.as_mut() {
+            validity.push(true);
+        }
+    }
+
+    /// Append a null value.
+    #[inline]
+    pub fn push_null(&mut self) {
+        let validity = self.validity.get_or_insert_with(|| Bitmap::new_set(self.values.len()));
+        validity.push(false);
+        self.values.push(SemiStr::default());
+    }
+
+    /// Returns true if value at given position is null.
+    ///
+    /// # Panics
+    ///
+    /// Panics if idx is out of bounds.
+    #[inline]
+    pub fn is_null(&self, idx: usize) -> bool {
+        assert!(idx < self.values.len());
+        self.validity.as_ref().is_some_and(|v| !v.get(idx))
+    }
+
+    /// Returns number of null values.
+    #[inline]
+    pub fn null_count(&self) -> usize {
+        self.validity.as_ref().map_or(0, |v| v.len() - v.count_ones())
+    }
+
+    /// Returns validity bitmap, whose bit is set for non-null value.
+    /// Returns None if there is no null.
+    #[inline]
+    pub fn validity(&self) -> Option<&Bitmap> {
+        self.validity.as_ref()
+    }
+
+    /// Returns value at given position, or None if it is null.
+    #[inline]
+    pub fn get_opt(&self, idx: usize) -> Option<&SemiStr> {
+        if self.is_null(idx) { None } else { Some(&self.values[idx]) }
+    }
+
+    /// Compact all values, see [`SemiStr::compact`].
+    #[inline]
+    pub fn compact(&mut self) {
+        SemiStr::compact_all(&mut self.values)
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[SemiStr] {
+        &self.values
+    }
+
+    /// Returns all values, nulls are converted to empty strings.
+    #[inline]
+    pub fn into_vec(self) -> Vec<SemiStr> {
+        self.values
+    }
+
+    /// Gather values at given positions.
+    ///
+    /// Only headers are copied, long values share buffers with self.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index is out of bounds.
+    #[inline]
+    pub fn take(&self, indices: &[usize]) -> SemiStrVec {
+        let values = indices.iter().map(|i| self.values[*i].share()).collect();
+        let validity = self.validity.as_ref().map(|v| indices.iter().map(|i| v.get(*i)).collect());
+        SemiStrVec{values, validity}
+    }
+
+    /// Select values whose corresponding bit is set.
+    ///
+    /// Only headers are copied, long values share buffers with self.
+    ///
+    /// # Panics
+    ///
+    /// Panics if length of bitmap does not match length of self.
+    #[inline]
+    pub fn filter(&self, bitmap: &Bitmap) -> SemiStrVec {
+        assert_eq!(bitmap.len(), self.len());
+        let values = bitmap.iter_ones().map(|i| self.values[i].share()).collect();
+        let validity = self.validity.as_ref().map(|v| bitmap.iter_ones().map(|i| v.get(i)).collect());
+        SemiStrVec{values, validity}
+    }
+}
+
+impl PartialEq for SemiStrVec {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.values.len() == other.values.len() && (0..self.values.len()).all(|i| self.get_opt(i) == other.get_opt(i))
+    }
+}
+
+impl Eq for SemiStrVec {}
+
+impl Hash for SemiStrVec {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.values.len());
+        for i in 0..self.values.len() {
+            self.get_opt(i).hash(state);
+        }
+    }
+}
+
+impl Deref for SemiStrVec {
+    type Target = [SemiStr];
+    #[inline]
+    fn deref(&self) -> &[SemiStr] {
+        &self.values
+    }
+}
+
+impl From<Vec<SemiStr>> for SemiStrVec {
+    #[inline]
+    fn from(values: Vec<SemiStr>) -> Self {
+        SemiStrVec{values, validity: None}
+    }
+}
+
+impl FromIterator<SemiStr> for SemiStrVec {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = SemiStr>>(iter: I) -> Self {
+        SemiStrVec{values: iter.into_iter().collect(), validity: None}
+    }
+}
+
+impl<'a> FromIterator<&'a str> for SemiStrVec {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        SemiStrVec{values: iter.into_iter().map(SemiStr::new).collect(), validity: None}
+    }
+}
+
+impl Extend<SemiStr> for SemiStrVec {
+    #[inline]
+    fn extend<I: IntoIterator<Item = SemiStr>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|v| self.push(v))
+    }
+}
+
+impl<'a> IntoIterator for &'a SemiStrVec {
+    type Item = &'a SemiStr;
+    type IntoIter = std::slice::Iter<'a, SemiStr>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.iter()
+    }
+}
+
+impl IntoIterator for SemiStrVec {
+    type Item = SemiStr;
+    type IntoIter = std::vec::IntoIter<SemiStr>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+/// SemiStrVecBuilder builds SemiStrVec value by value.
+///
+/// Long strings appended by [`SemiStrVecBuilder::append`] are written
+/// back to back into shared buffers, instead of allocating per value.
+#[derive(Default)]
+pub struct SemiStrVecBuilder {
+    values: Vec<SemiStr>,
+    validity: Option<Bitmap>,
+    arena: Arena,
+}
+
+impl SemiStrVecBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a builder with capacity of given number of values, and
+    /// given number of bytes for long strings.
+    #[inline]
+    pub fn with_capacity(len: usize, bytes: usize) -> Self {
+        SemiStrVecBuilder{values: Vec::with_capacity(len), validity: None, arena: Arena::with_capacity(bytes)}
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Append a string by copying it into the shared buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is longer than 4GB.
+    #[inline]
+    pub fn append(&mut self, value: &str) {
+        let value = self.arena.push(&[value]);
+        self.append_semistr(value)
+    }
+
+    /// Append a SemiStr, which keeps its own buffer without copying.
+    #[inline]
+    pub fn append_semistr(&mut self, value: SemiStr) {
+        self.values.push(value);
+        if let Some(validity) = self.validity.as_mut() {
+            validity.push(true);
+        }
+    }
+
+    /// Append a null value.
+    #[inline]
+    pub fn append_null(&mut self) {
+        let validity = self.validity.get_or_insert_with(|| Bitmap::new_set(self.values.len()));
+        validity.push(false);
+        self.values.push(SemiStr::default());
+    }
+
+    #[inline]
+    pub fn finish(self) -> SemiStrVec {
+        SemiStrVec{values: self.values, validity: self.validity}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_filter() {
+        let col: SemiStrVec = ["a", "long string number one", "b", "long string number two"].into_iter().collect();
+        let res = col.take(&[3, 0, 3]);
+        assert_eq!(res.iter().map(|s| s.as_str()).collect::<Vec<_>>(), vec!["long string number two", "a", "long string number two"]);
+        assert_eq!(res[0].payload_ptr(), col[3].payload_ptr());
+        let res = col.filter(&Bitmap::from(&[false, true, true, false][..]));
+        assert_eq!(res.iter().map(|s| s.as_str()).collect::<Vec<_>>(), vec!["long string number one", "b"]);
+        assert_eq!(res[0].payload_ptr(), col[1].payload_ptr());
+        drop(col);
+        assert_eq!(res[0], "long string number one");
+    }
+
+    #[test]
+    fn test_builder() {
+        let mut builder = SemiStrVecBuilder::with_capacity(4, 64);
+        builder.append("a");
+        builder.append("long string number one");
+        builder.append_null();
+        builder.append_semistr(SemiStr::new("long string number two"));
+        assert_eq!(builder.len(), 4);
+        let col = builder.finish();
+        assert_eq!(col.null_count(), 1);
+        assert!(col.is_null(2) && !col.is_null(1));
+        assert_eq!(col.get_opt(1).unwrap(), "long string number one");
+        assert_eq!(col.get_opt(2), None);
+        let res = col.take(&[2, 3]);
+        assert_eq!(res.validity().unwrap().iter().collect::<Vec<_>>(), vec![false, true]);
+        let mut expected = SemiStrVec::new();
+        expected.push_null();
+        expected.push(SemiStr::new("long string number two"));
+        assert_eq!(res, expected);
+        let res = col.filter(&Bitmap::from(&[true, true, false, false][..]));
+        assert_eq!(res.null_count(), 0);
+        assert_eq!(res, ["a", "long string number one"].into_iter().collect());
+    }
+}
@@ -0,0 +1,69 @@
+//! Conversions between SemiStr and JavaScript strings.
+//!
+//! ```no_run
+//! use js_sys::JsString;
+//! use semistr::SemiStr;
+//!
+//! let js = JsString::from("a string from JavaScript");
+//! let s = SemiStr::from(&js);
+//! assert_eq!(s, "a string from JavaScript");
+//! assert_eq!(JsString::from(&s), js);
+//! ```
+
+use crate::SemiStr;
+use js_sys::JsString;
+use wasm_bindgen::JsValue;
+
+impl From<SemiStr> for JsValue {
+    #[inline]
+    fn from(value: SemiStr) -> Self {
+        JsValue::from_str(value.as_str())
+    }
+}
+
+impl From<&SemiStr> for JsValue {
+    #[inline]
+    fn from(value: &SemiStr) -> Self {
+        JsValue::from_str(value.as_str())
+    }
+}
+
+/// The value is given back in the error if it is not a string.
+impl TryFrom<JsValue> for SemiStr {
+    type Error = JsValue;
+    #[inline]
+    fn try_from(value: JsValue) -> Result<Self, JsValue> {
+        match value.as_string() {
+            Some(s) => SemiStr::try_from(s).map_err(|_| value),
+            None => Err(value),
+        }
+    }
+}
+
+impl From<SemiStr> for JsString {
+    #[inline]
+    fn from(value: SemiStr) -> Self {
+        JsString::from(value.as_str())
+    }
+}
+
+impl From<&SemiStr> for JsString {
+    #[inline]
+    fn from(value: &SemiStr) -> Self {
+        JsString::from(value.as_str())
+    }
+}
+
+/// JavaScript strings are UTF-16, and converted by a single call if
+/// valid. Otherwise code units are read one by one, and unpaired
+/// surrogates are replaced with U+FFFD.
+impl From<&JsString> for SemiStr {
+    #[inline]
+    fn from(value: &JsString) -> Self {
+        if let Some(s) = value.as_string().and_then(|s| SemiStr::try_from(s).ok()) {
+            return s
+        }
+        let units: Vec<u16> = value.iter().collect();
+        SemiStr::from_utf16_lossy(&units)
+    }
+}
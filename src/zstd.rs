@@ -0,0 +1,119 @@
+//! Compression of individual strings with trained zstd dictionary.
+//!
+//! Strings of tens to hundreds of bytes hardly compress on their own,
+//! as there is little repetition inside each of them. A dictionary
+//! trained over a sample of the corpus captures the repetition across
+//! strings, so that each string can still be compressed and
+//! decompressed independently.
+
+use crate::{Error, Result, SemiStr};
+use ::zstd::bulk::{Compressor, Decompressor};
+
+/// Default compression level of [`ZstdCodec`].
+pub const DEFAULT_LEVEL: i32 = 3;
+
+/// Default limit of decompressed length of [`ZstdCodec`].
+pub const DEFAULT_MAX_LEN: usize = 1 << 20;
+
+/// Train a zstd dictionary of at most dict_size bytes from a sample
+/// of the corpus.
+///
+/// At most sample_size strings are sampled at even stride, so that the
+/// sample covers the whole corpus. Zstd requires enough samples to
+/// train a dictionary, typically at least hundreds of strings.
+pub fn train_dictionary(corpus: &[SemiStr], sample_size: usize, dict_size: usize) -> Result<Vec<u8>> {
+    let stride = corpus.len().div_ceil(sample_size.max(1)).max(1);
+    let samples: Vec<&[u8]> = corpus.iter()
+        .step_by(stride)
+        .map(SemiStr::as_bytes)
+        .filter(|s| !s.is_empty())
+        .collect();
+    ::zstd::dict::from_samples(&samples, dict_size).map_err(zstd_err)
+}
+
+/// ZstdCodec compresses and decompresses strings with a dictionary.
+pub struct ZstdCodec {
+    compressor: Compressor<'static>,
+    decompressor: Decompressor<'static>,
+    max_len: usize,
+}
+
+impl ZstdCodec {
+    /// Create a codec with dictionary and default compression level.
+    #[inline]
+    pub fn new(dict: &[u8]) -> Result<Self> {
+        Self::with_level(dict, DEFAULT_LEVEL)
+    }
+
+    /// Create a codec with dictionary and compression level.
+    #[inline]
+    pub fn with_level(dict: &[u8], level: i32) -> Result<Self> {
+        let compressor = Compressor::with_dictionary(level, dict).map_err(zstd_err)?;
+        let decompressor = Decompressor::with_dictionary(dict).map_err(zstd_err)?;
+        Ok(ZstdCodec{compressor, decompressor, max_len: DEFAULT_MAX_LEN})
+    }
+
+    /// Set limit of decompressed length, frames declaring a larger
+    /// content size are rejected before allocation.
+    #[inline]
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len.min(u32::MAX as usize);
+        self
+    }
+
+    /// Compress the string into a zstd frame, which records the
+    /// decompressed size.
+    #[inline]
+    pub fn compress(&mut self, s: &str) -> Result<Vec<u8>> {
+        self.compressor.compress(s.as_bytes()).map_err(zstd_err)
+    }
+
+    /// Decompress a frame produced by [`ZstdCodec::compress`].
+    ///
+    /// Returns error if the frame is corrupted, its declared content
+    /// size exceeds the limit, see [`ZstdCodec::with_max_len`], or the
+    /// decompressed bytes are not a valid string.
+    pub fn decompress(&mut self, frame: &[u8]) -> Result<SemiStr> {
+        let size = ::zstd::zstd_safe::get_frame_content_size(frame)
+            .ok()
+            .flatten()
+            .ok_or_else(|| Error::Zstd("missing frame content size".to_string()))?;
+        if size > self.max_len as u64 {
+            return Err(Error::StringTooLong(size.try_into().unwrap_or(usize::MAX)))
+        }
+        let bytes = self.decompressor.decompress(frame, size as usize).map_err(zstd_err)?;
+        SemiStr::try_from(&bytes[..])
+    }
+}
+
+#[inline]
+fn zstd_err(e: std::io::Error) -> Error {
+    Error::Zstd(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zstd_dictionary() {
+        let corpus: Vec<SemiStr> = (0..2000).map(|i| {
+            SemiStr::new(&format!("{{\"user_id\":{},\"event\":\"page_view\",\"path\":\"/products/{}\",\"ok\":true}}", i * 7919 % 10007, i % 97))
+        }).collect();
+        let dict = train_dictionary(&corpus, 1000, 4096).unwrap();
+        assert!(!dict.is_empty() && dict.len() <= 4096);
+        let mut codec = ZstdCodec::new(&dict).unwrap();
+        let plain = ::zstd::bulk::compress(corpus[5].as_bytes(), DEFAULT_LEVEL).unwrap();
+        let frame = codec.compress(&corpus[5]).unwrap();
+        assert!(frame.len() < plain.len());
+        assert_eq!(codec.decompress(&frame).unwrap(), corpus[5]);
+        assert!(codec.decompress(&frame[..frame.len() / 2]).is_err());
+        let mut codec = ZstdCodec::new(&dict).unwrap().with_max_len(16);
+        assert!(matches!(codec.decompress(&frame), Err(Error::StringTooLong(n)) if n == corpus[5].len()));
+        // frame header declaring 4GB content without any data.
+        let mut hostile = vec![0x28, 0xb5, 0x2f, 0xfd, 0xa0];
+        hostile.extend_from_slice(&u32::MAX.to_le_bytes());
+        let mut codec = ZstdCodec::new(&dict).unwrap();
+        assert!(matches!(codec.decompress(&hostile), Err(Error::StringTooLong(_))));
+    }
+}